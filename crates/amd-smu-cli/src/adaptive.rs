@@ -0,0 +1,85 @@
+use amd_smu_lib::PmTable;
+use std::time::Duration;
+
+/// Change in package power (W) between samples that counts as activity
+const POWER_DELTA_W: f32 = 5.0;
+/// Change in Tctl (°C) between samples that counts as activity
+const TEMP_DELTA_C: f32 = 2.0;
+/// Factor the interval grows by for each quiet sample
+const BACKOFF: f64 = 1.5;
+
+/// Watch-mode interval that drops to `min` when readings move and backs off
+/// towards `max` while they stay quiet
+pub struct AdaptiveInterval {
+    min: Duration,
+    max: Duration,
+    current: Duration,
+    last: Option<(f32, f32)>,
+}
+
+impl AdaptiveInterval {
+    pub fn new(min: Duration, max: Duration) -> Self {
+        let max = max.max(min);
+        Self { min, max, current: min, last: None }
+    }
+
+    pub fn current(&self) -> Duration {
+        self.current
+    }
+
+    /// Feed a new sample and get the interval to sleep before the next one
+    pub fn update(&mut self, table: &PmTable) -> Duration {
+        let sample = (table.ppt_value, table.tctl);
+        let active = match self.last {
+            Some((power, tctl)) => {
+                (sample.0 - power).abs() >= POWER_DELTA_W
+                    || (sample.1 - tctl).abs() >= TEMP_DELTA_C
+                    || table.is_throttling()
+            }
+            None => true,
+        };
+        self.last = Some(sample);
+
+        self.current = if active {
+            self.min
+        } else {
+            self.current.mul_f64(BACKOFF).min(self.max)
+        };
+        self.current
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(ppt_value: f32, tctl: f32) -> PmTable {
+        PmTable { ppt_value, ppt_limit: 142.0, tctl, thm_limit: 90.0, ..Default::default() }
+    }
+
+    #[test]
+    fn test_backs_off_when_idle_and_snaps_back_on_load() {
+        let min = Duration::from_millis(200);
+        let max = Duration::from_secs(2);
+        let mut interval = AdaptiveInterval::new(min, max);
+
+        assert_eq!(interval.update(&sample(30.0, 45.0)), min);
+        assert_eq!(interval.update(&sample(31.0, 45.5)), Duration::from_millis(300));
+        assert_eq!(interval.update(&sample(30.5, 45.0)), Duration::from_millis(450));
+        for _ in 0..10 {
+            interval.update(&sample(30.0, 45.0));
+        }
+        assert_eq!(interval.current(), max);
+
+        // Load step: power jumps
+        assert_eq!(interval.update(&sample(120.0, 46.0)), min);
+    }
+
+    #[test]
+    fn test_throttling_keeps_fast_rate() {
+        let min = Duration::from_millis(200);
+        let mut interval = AdaptiveInterval::new(min, Duration::from_secs(2));
+        interval.update(&sample(140.0, 80.0));
+        assert_eq!(interval.update(&sample(140.0, 80.0)), min);
+    }
+}
@@ -0,0 +1,74 @@
+use amd_smu_lib::PmTable;
+use std::fmt;
+
+/// Exit code when a single-shot reading breaches an alert threshold
+pub const EXIT_ALERT: i32 = 2;
+
+/// A reading that went over its alert threshold
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Alert {
+    /// Tctl in °C
+    Tctl { value: f32, threshold: f32 },
+    /// Package power (PPT) in W
+    Power { value: f32, threshold: f32 },
+}
+
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tctl { value, threshold } => {
+                write!(f, "Tctl {:.1}°C exceeds alert threshold {:.1}°C", value, threshold)
+            }
+            Self::Power { value, threshold } => {
+                write!(f, "package power {:.1}W exceeds alert threshold {:.1}W", value, threshold)
+            }
+        }
+    }
+}
+
+/// Thresholds from `--alert-tctl` / `--alert-power`
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AlertThresholds {
+    pub tctl: Option<f32>,
+    pub power: Option<f32>,
+}
+
+impl AlertThresholds {
+    /// Readings above their threshold; power is compared after calibration
+    pub fn check(&self, table: &PmTable, power_calibration: f32) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+        if let Some(threshold) = self.tctl
+            && table.tctl > threshold
+        {
+            alerts.push(Alert::Tctl { value: table.tctl, threshold });
+        }
+        let power = table.ppt_value * power_calibration;
+        if let Some(threshold) = self.power
+            && power > threshold
+        {
+            alerts.push(Alert::Power { value: power, threshold });
+        }
+        alerts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_thresholds() {
+        let table = PmTable { tctl: 91.0, ppt_value: 100.0, ..Default::default() };
+        let thresholds = AlertThresholds { tctl: Some(90.0), power: Some(120.0) };
+
+        assert_eq!(thresholds.check(&table, 1.0), vec![Alert::Tctl { value: 91.0, threshold: 90.0 }]);
+        // Calibration can push power over its threshold
+        assert_eq!(thresholds.check(&table, 1.25).len(), 2);
+    }
+
+    #[test]
+    fn test_no_thresholds() {
+        let table = PmTable { tctl: 95.0, ppt_value: 200.0, ..Default::default() };
+        assert!(AlertThresholds::default().check(&table, 1.0).is_empty());
+    }
+}
@@ -0,0 +1,751 @@
+use crate::logger::LogFormat;
+use crate::output::{BriefField, ColorChoice, TextField, TimeFormat};
+use amd_smu_lib::{parse_interval, parse_pm_table_version, parse_smoothing, Codename};
+use clap::{Args as ClapArgs, Parser, Subcommand};
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[derive(Parser, Debug)]
+#[command(name = "amd-smu-sensors")]
+#[command(about = "Read AMD Ryzen CPU sensors via ryzen_smu kernel module")]
+#[command(version = amd_smu_lib::full_version())]
+#[command(after_help = "Without a command, takes a single reading (`read`); the --watch, --serve,\n--socket-server and --check flags of earlier releases still select the\nother modes.\n\nExit codes:\n  0  success\n  1  error reading sensors\n  2  an --alert-* threshold was exceeded (single-shot mode), a reading\n     drifted from --baseline, or a limit differs from its --expect-*\n     value (limits)\n\ncheck exits 0 when healthy, 1 when readings have warnings and 2 when\nthe driver is missing, unreadable or unsupported")]
+pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    #[command(flatten)]
+    pub output: OutputArgs,
+
+    #[command(flatten)]
+    pub source: SourceArgs,
+
+    /// Sampling interval for watch mode, servers and --diff (e.g., "500ms",
+    /// "1s"; at least 100ms)
+    #[arg(short, long, global = true, default_value = "1s", value_parser = parse_interval)]
+    pub interval: Duration,
+
+    /// Alert when Tctl exceeds this many °C: exit with code 2 in
+    /// single-shot mode, flash a warning in watch mode
+    #[arg(long, global = true, value_name = "CELSIUS")]
+    pub alert_tctl: Option<f32>,
+
+    /// Alert when package power (PPT) exceeds this many watts
+    #[arg(long, global = true, value_name = "WATTS")]
+    pub alert_power: Option<f32>,
+
+    /// Compare every reading with a table saved with --json, reporting
+    /// readings that drifted beyond --baseline-tolerance; exits with code
+    /// 2 if any did
+    #[arg(long, global = true, value_name = "FILE", conflicts_with = "all_sockets")]
+    pub baseline: Option<PathBuf>,
+
+    /// How far a reading may drift from --baseline, in percent
+    #[arg(long, global = true, value_name = "PERCENT", default_value_t = 10.0, requires = "baseline")]
+    pub baseline_tolerance: f32,
+
+    /// List the PM table versions this build can parse, and the processors
+    /// known to report each, then exit
+    #[arg(long)]
+    pub list_supported: bool,
+
+    /// Launch TUI dashboard
+    #[arg(long)]
+    pub tui: bool,
+
+    // Mode flags from before subcommands; `mode` maps them onto commands
+    #[command(flatten)]
+    pub read: ReadArgs,
+
+    /// Same as the `watch` command
+    #[arg(short, long)]
+    pub watch: bool,
+
+    #[command(flatten, next_help_heading = "Watch options (with --watch)")]
+    pub watch_options: WatchArgs,
+
+    #[arg(long, value_name = "ADDR", hide = true)]
+    pub serve: Option<String>,
+
+    #[arg(long, value_name = "PATH", hide = true)]
+    pub socket_server: Option<PathBuf>,
+
+    #[arg(long, hide = true)]
+    pub check: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Take a single reading (the default)
+    Read(ReadArgs),
+    /// Continuously update readings
+    Watch(WatchArgs),
+    /// Serve readings to Prometheus or local subscribers
+    Serve(ServeArgs),
+    /// Write the raw PM table for later use with --from-file, or a
+    /// diagnostic bundle for bug reports
+    Dump(DumpArgs),
+    /// Probe the driver and print a JSON health report (module loaded,
+    /// readable, PM table version supported, codename, core count), then
+    /// exit 0/1/2 for healthy/warnings/unusable
+    Check,
+    /// Read the PM table back to back for a while and report read
+    /// latency and throughput
+    Profile(ProfileArgs),
+    /// Print the PPT/TDC/EDC limits and check them against what was set in
+    /// the BIOS
+    Limits(LimitsArgs),
+}
+
+/// What to run, whether chosen by command or by the older mode flags
+#[derive(Debug)]
+pub enum Mode {
+    Read(ReadArgs),
+    Watch(WatchArgs),
+    Serve(ServeArgs),
+    Dump(DumpArgs),
+    Check,
+    Profile(ProfileArgs),
+    Limits(LimitsArgs),
+}
+
+/// How readings are printed; valid before or after the command
+#[derive(ClapArgs, Debug)]
+pub struct OutputArgs {
+    /// Output in JSON format
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    /// Output one compact JSON object per line with a Unix-millis timestamp
+    /// (for piping watch mode into log processors)
+    #[arg(long, global = true, conflicts_with = "json")]
+    pub json_lines: bool,
+
+    /// Output CSV rows (header first) for logging over time
+    #[arg(long, global = true, conflicts_with_all = ["json", "json_lines"])]
+    pub csv: bool,
+
+    /// Output metrics in Prometheus text exposition format
+    #[arg(long, global = true, conflicts_with_all = ["json", "json_lines", "csv"])]
+    pub prometheus: bool,
+
+    /// Output InfluxDB line protocol, one line per sample with a
+    /// nanosecond timestamp
+    #[arg(long, global = true, conflicts_with_all = ["json", "json_lines", "csv", "prometheus"])]
+    pub influx: bool,
+
+    /// Output a chip block like lm-sensors' `sensors` (temp1, in0, power1,
+    /// ...), for scripts that parse it
+    #[arg(long, global = true, conflicts_with_all = ["json", "json_lines", "csv", "prometheus", "influx"])]
+    pub sensors_format: bool,
+
+    /// Nest --json output under `pm_table`, alongside `smu_version` and
+    /// `driver_version`
+    #[arg(long, global = true, requires = "json")]
+    pub json_envelope: bool,
+
+    /// Nest per-core --json data by CCD: `ccds: [{index, cores: [...]}]`
+    #[arg(long, global = true, requires = "json", conflicts_with = "json_envelope")]
+    pub json_grouped: bool,
+
+    /// Rename a JSON key, e.g. `tctl=cpu_temp` (repeatable)
+    #[arg(long = "json-alias", global = true, value_name = "FIELD=KEY", value_parser = parse_alias)]
+    pub json_aliases: Vec<(String, String)>,
+
+    /// Show a short summary of the most useful readings
+    #[arg(long, global = true, conflicts_with_all = ["json", "json_lines", "csv", "prometheus", "influx", "sensors_format"])]
+    pub summary: bool,
+
+    /// Print one short line per reading, e.g. "65°C 88W 4.5GHz", for status
+    /// bars
+    #[arg(
+        long,
+        global = true,
+        conflicts_with_all = ["json", "json_lines", "csv", "prometheus", "influx", "sensors_format", "summary", "all_sockets"]
+    )]
+    pub brief: bool,
+
+    /// Readings on the --brief line, in order
+    #[arg(
+        long,
+        global = true,
+        value_name = "LIST",
+        value_enum,
+        value_delimiter = ',',
+        default_values_t = [BriefField::Tctl, BriefField::Power, BriefField::Freq],
+        requires = "brief"
+    )]
+    pub brief_fields: Vec<BriefField>,
+
+    /// Show only temperature readings
+    #[arg(long, global = true)]
+    pub temps: bool,
+
+    /// Show only power readings
+    #[arg(long, global = true)]
+    pub power: bool,
+
+    /// Show only frequency readings
+    #[arg(long, global = true)]
+    pub freq: bool,
+
+    /// Show only these readings in text output, e.g. "tctl,ppt,fclk,core_temps"
+    /// (overrides --temps/--power/--freq)
+    #[arg(long, global = true, value_name = "LIST", value_enum, value_delimiter = ',')]
+    pub fields: Vec<TextField>,
+
+    /// Print only the requested data: no text header and no notices or
+    /// warnings on stderr (errors still print; exit codes still report
+    /// alerts and drift). check prints nothing and only sets the exit code
+    #[arg(short, long, global = true, conflicts_with_all = ["verbose", "debug"])]
+    pub quiet: bool,
+
+    /// Print every reading with N decimals in text and CSV output (and
+    /// --log-file CSV) instead of each reading's usual precision
+    #[arg(long, global = true, value_name = "N", value_parser = clap::value_parser!(u8).range(0..=6))]
+    pub precision: Option<u8>,
+
+    /// Timestamp of --json-lines, --csv and --influx records (and
+    /// --log-file): Unix millis (nanoseconds for --influx), ISO-8601 UTC,
+    /// or none at all. --influx takes unix or none
+    #[arg(long, global = true, value_enum, value_name = "FORMAT", default_value_t = TimeFormat::Unix)]
+    pub time_format: TimeFormat,
+
+    /// Telemetry current calibration factor applied to TDC/EDC readings
+    /// (match your board's BIOS telemetry setting)
+    #[arg(long, global = true, default_value_t = 1.0)]
+    pub current_calibration: f32,
+
+    /// Telemetry power calibration factor applied to power readings
+    #[arg(long, global = true, default_value_t = 1.0)]
+    pub power_calibration: f32,
+
+    /// List per-core data in physical die order (by CCD and core id)
+    /// instead of logical index order
+    #[arg(long, global = true)]
+    pub physical_order: bool,
+
+    /// Show temperatures in Fahrenheit in text output
+    /// (machine-readable formats always report Celsius)
+    #[arg(long, global = true)]
+    pub fahrenheit: bool,
+
+    /// When to color text output and use other ANSI escapes (auto: only on
+    /// a terminal, unless $NO_COLOR is set)
+    #[arg(long, global = true, value_enum, value_name = "WHEN", default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
+
+    /// Never emit ANSI escape sequences (same as --color never)
+    #[arg(long, global = true, conflicts_with = "color")]
+    pub no_color: bool,
+
+    /// Read warning/critical color thresholds from this TOML file
+    /// (default ~/.config/amd-smu/config.toml, if it exists)
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Show parked cores (present but at ~0 effective frequency) instead of hiding them
+    #[arg(long, global = true)]
+    pub show_parked: bool,
+
+    /// Leave out the PBO guess (limits compared with the processor's stock
+    /// values) from text output and check
+    #[arg(long, global = true)]
+    pub no_pbo_detect: bool,
+
+    /// Number cores within their CCD in text output (CCD1/Core0 rather
+    /// than Core 8)
+    #[arg(long, global = true)]
+    pub ccd_relative: bool,
+}
+
+/// Where readings come from; valid before or after the command
+#[derive(ClapArgs, Debug)]
+pub struct SourceArgs {
+    /// Print raw PM table details (size, CRC32) to stderr with each reading
+    #[arg(short, long, global = true)]
+    pub verbose: bool,
+
+    /// Print the PM table layout used, and the raw bytes and parsed value
+    /// at each of its offsets, to stderr with the first reading; for
+    /// working out new layouts. Driver reads only
+    #[arg(long, global = true, conflicts_with = "from_file")]
+    pub debug: bool,
+
+    /// ryzen_smu sysfs directory (overrides $AMD_SMU_SYSFS; default
+    /// /sys/kernel/ryzen_smu_drv)
+    #[arg(long, global = true, value_name = "DIR")]
+    pub sysfs_path: Option<PathBuf>,
+
+    /// Read socket N on a multi-socket system (the driver instance
+    /// ryzen_smu_drv for 0, ryzen_smu_drvN otherwise)
+    #[arg(long, global = true, value_name = "N", conflicts_with_all = ["all_sockets", "from_file"])]
+    pub socket: Option<usize>,
+
+    /// Read every socket once, labelling each (text, summary or JSON array);
+    /// with check, report every socket as a JSON array
+    #[arg(
+        long,
+        global = true,
+        conflicts_with_all = ["json_lines", "csv", "prometheus", "sensors_format", "json_envelope", "from_file"]
+    )]
+    pub all_sockets: bool,
+
+    /// Parse a PM table dumped to a file instead of reading the driver
+    /// (give it twice with --diff to compare two dumps)
+    #[arg(long, global = true, value_name = "PATH", requires = "pm_version")]
+    pub from_file: Vec<PathBuf>,
+
+    /// PM table version of the --from-file dump (e.g. 0x240903)
+    #[arg(long, global = true, value_name = "VERSION", value_parser = parse_pm_version)]
+    pub pm_version: Option<u32>,
+
+    /// Parse PM tables as this processor (e.g. "Granite Ridge") instead of
+    /// the codename the driver reports, or the --from-file dump's
+    #[arg(long, global = true, value_name = "NAME")]
+    pub codename: Option<Codename>,
+
+    /// Size per-core readings for N cores (1-96) instead of counting them
+    /// in /proc/cpuinfo, for systems where the count comes out wrong; with
+    /// --from-file, instead of stopping at the dump's last populated core.
+    /// A count past what the layout holds is capped, with a warning
+    #[arg(long, global = true, env = "AMD_SMU_CORES", value_name = "N")]
+    pub cores: Option<usize>,
+
+    /// Parse PM tables with the offsets of this supported version (e.g.
+    /// 0x240903) whatever version the driver reports, for new firmware
+    /// whose table hasn't moved; readings may be wrong. Driver reads only
+    #[arg(long, global = true, value_name = "VERSION", value_parser = parse_pm_version, conflicts_with = "from_file")]
+    pub force_offsets: Option<u32>,
+
+    /// Parse PM tables with offsets from this TOML or JSON (.json) file,
+    /// for hardware without a built-in layout: every PmTableOffsets field,
+    /// 0xFFFF for readings the table lacks. Readings may be wrong
+    #[arg(long, global = true, value_name = "FILE", conflicts_with = "force_offsets")]
+    pub offsets: Option<PathBuf>,
+
+    /// Multiply power readings (PPT, package, SoC and per-core power) by
+    /// this factor as they are parsed, for boards whose telemetry is off by
+    /// a known amount; unlike --power-calibration, every output format
+    /// sees the scaled values. Not with --power-calibration, which would
+    /// apply on top
+    #[arg(long, global = true, value_name = "FACTOR", value_parser = parse_scale, conflicts_with = "power_calibration")]
+    pub power_scale: Option<f32>,
+
+    /// Multiply TDC and EDC current readings by this factor as they are
+    /// parsed; limits are never scaled. Not with --current-calibration
+    #[arg(long, global = true, value_name = "FACTOR", value_parser = parse_scale, conflicts_with = "current_calibration")]
+    pub current_scale: Option<f32>,
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct ReadArgs {
+    /// Take a single reading and exit (default)
+    #[arg(long)]
+    pub once: bool,
+
+    /// Print a single reading's value and nothing else, e.g. `tctl`,
+    /// `package_power` or `core_temp.3`
+    #[arg(
+        long,
+        value_name = "FIELD",
+        conflicts_with_all = ["json", "json_lines", "csv", "prometheus", "influx", "sensors_format", "summary", "brief", "all_sockets"]
+    )]
+    pub query: Option<String>,
+
+    /// Print each reading's change between two samples taken --interval
+    /// apart, or between two --from-file dumps
+    #[arg(
+        long,
+        conflicts_with_all = ["query", "all_sockets", "json", "json_lines", "csv", "prometheus", "influx", "sensors_format", "summary", "brief"]
+    )]
+    pub diff: bool,
+}
+
+impl ReadArgs {
+    fn is_set(&self) -> bool {
+        self.once || self.query.is_some() || self.diff
+    }
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct WatchArgs {
+    /// Track min/max/average over the session and print a final summary
+    /// on Ctrl-C
+    #[arg(long)]
+    pub stats: bool,
+
+    /// Smooth text/summary readings with an exponential moving average,
+    /// ALPHA being the newest sample's weight (1 = raw readings, lower =
+    /// smoother, 0 = off). JSON/CSV and logs stay raw
+    #[arg(long, value_name = "ALPHA", value_parser = parse_smoothing)]
+    pub smooth: Option<f32>,
+
+    /// Sample faster while readings change, slower while idle
+    #[arg(long)]
+    pub adaptive: bool,
+
+    /// Stop after N readings instead of running until Ctrl-C, e.g. for a
+    /// fixed-size --csv or --json-lines capture
+    #[arg(long, alias = "watch-count", value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    pub count: Option<u64>,
+
+    /// After the first full reading, print only the readings that changed
+    /// since the previous one (by more than 0.005 in their unit), as a
+    /// running change log (text output only)
+    #[arg(long, conflicts_with_all = ["json", "json_lines", "csv", "prometheus", "influx", "sensors_format", "summary", "brief"])]
+    pub delta_only: bool,
+
+    /// Append each reading instead of clearing the screen first; the
+    /// default when stdout isn't a terminal
+    #[arg(long)]
+    pub no_clear: bool,
+
+    /// Also append each sample to this file
+    #[arg(long, value_name = "PATH")]
+    pub log_file: Option<PathBuf>,
+
+    /// Record format for --log-file
+    #[arg(long, value_enum, default_value = "jsonl", requires = "log_file")]
+    pub log_format: LogFormat,
+
+    /// Rotate --log-file once it reaches this size (e.g. 10MB), keeping up
+    /// to 5 old files as PATH.1 (newest) to PATH.5
+    #[arg(long, value_name = "SIZE", value_parser = parse_size, requires = "log_file")]
+    pub log_rotate: Option<u64>,
+
+    /// Fastest adaptive sampling interval
+    #[arg(long, default_value = "250ms", value_parser = parse_duration, requires = "adaptive")]
+    pub min_interval: Duration,
+
+    /// Slowest adaptive sampling interval
+    #[arg(long, default_value = "5s", value_parser = parse_duration, requires = "adaptive")]
+    pub max_interval: Duration,
+
+    /// Take one sample per SIGUSR1 (`kill -USR1 <pid>`) instead of every
+    /// --interval; signals arriving mid-sample coalesce into one more sample
+    #[cfg(target_os = "linux")]
+    #[arg(long, conflicts_with = "adaptive")]
+    pub on_signal: bool,
+}
+
+impl WatchArgs {
+    // The rest only apply alongside these, which clap already checks
+    fn is_set(&self) -> bool {
+        self.stats
+            || self.smooth.is_some()
+            || self.adaptive
+            || self.count.is_some()
+            || self.delta_only
+            || self.no_clear
+            || self.log_file.is_some()
+            || self.on_signal()
+    }
+
+    #[cfg(target_os = "linux")]
+    fn on_signal(&self) -> bool {
+        self.on_signal
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn on_signal(&self) -> bool {
+        false
+    }
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+#[group(required = true)]
+pub struct ServeArgs {
+    /// Serve Prometheus metrics (/metrics), JSON (/pm_table) and /health
+    /// over HTTP at ADDR (e.g. 0.0.0.0:9101), reading the PM table per request
+    #[arg(long, value_name = "ADDR", conflicts_with = "unix")]
+    pub http: Option<String>,
+
+    /// Stream JSON lines to every client of a Unix socket at PATH, sampling
+    /// every --interval
+    #[arg(long, value_name = "PATH")]
+    pub unix: Option<PathBuf>,
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct DumpArgs {
+    /// Write the PM table to PATH instead of stdout
+    #[arg(short, long, value_name = "PATH")]
+    pub output: Option<PathBuf>,
+
+    /// Write a diagnostic bundle for bug reports into directory DIR: the
+    /// raw PM table, driver versions and sizes, /proc/cpuinfo and build info
+    #[arg(long, value_name = "DIR", conflicts_with = "output")]
+    pub bundle: Option<PathBuf>,
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct ProfileArgs {
+    /// How long to keep reading (e.g. "10s", "1m")
+    #[arg(long, default_value = "10s", value_parser = parse_duration)]
+    pub duration: Duration,
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct LimitsArgs {
+    /// Expected PPT limit; exit 2 if the SMU reports another
+    #[arg(long, value_name = "WATTS")]
+    pub expect_ppt: Option<f32>,
+
+    /// Expected TDC limit
+    #[arg(long, value_name = "AMPS")]
+    pub expect_tdc: Option<f32>,
+
+    /// Expected EDC limit
+    #[arg(long, value_name = "AMPS")]
+    pub expect_edc: Option<f32>,
+
+    /// How far a limit may be from its expected value, in percent
+    #[arg(long, value_name = "PERCENT", default_value_t = 1.0)]
+    pub tolerance: f32,
+
+    /// Set the PPT limit through the SMU (until reboot) before reading the
+    /// limits back. Needs root and --i-understand-the-risks; Matisse,
+    /// Vermeer, Raphael and Granite Ridge only, as reported by the driver
+    /// (--codename is ignored) and matching the PM table version
+    #[arg(long, value_name = "WATTS", requires = "i_understand_the_risks")]
+    pub set_ppt: Option<f32>,
+
+    /// Set the TDC limit, like --set-ppt
+    #[arg(long, value_name = "AMPS", requires = "i_understand_the_risks")]
+    pub set_tdc: Option<f32>,
+
+    /// Set the EDC limit, like --set-ppt
+    #[arg(long, value_name = "AMPS", requires = "i_understand_the_risks")]
+    pub set_edc: Option<f32>,
+
+    /// Confirm --set-* limits: limits beyond what the board and cooling
+    /// handle can crash the system or damage the processor
+    #[arg(long)]
+    pub i_understand_the_risks: bool,
+}
+
+impl LimitsArgs {
+    /// Whether any limit is to be written
+    pub fn sets_limits(&self) -> bool {
+        self.set_ppt.is_some() || self.set_tdc.is_some() || self.set_edc.is_some()
+    }
+}
+
+impl Args {
+    /// The mode to run: the command if one was given, else whatever the
+    /// top-level mode flags select
+    pub fn mode(&self) -> Result<Mode, String> {
+        let legacy_modes = [
+            ("--watch", self.watch),
+            ("--serve", self.serve.is_some()),
+            ("--socket-server", self.socket_server.is_some()),
+            ("--check", self.check),
+        ];
+        let legacy: Vec<&str> = legacy_modes.iter().filter(|(_, set)| *set).map(|(flag, _)| *flag).collect();
+
+        let mode = match &self.command {
+            Some(command) => {
+                if let Some(flag) = legacy.first() {
+                    return Err(format!("{} can't be combined with a command", flag));
+                }
+                if self.read.is_set() || self.watch_options.is_set() {
+                    return Err("mode options go after the command, e.g. `watch --stats`".to_string());
+                }
+                match command {
+                    Command::Read(read) => Mode::Read(read.clone()),
+                    Command::Watch(watch) => Mode::Watch(watch.clone()),
+                    Command::Serve(serve) => Mode::Serve(serve.clone()),
+                    Command::Dump(dump) => Mode::Dump(dump.clone()),
+                    Command::Check => Mode::Check,
+                    Command::Profile(profile) => Mode::Profile(profile.clone()),
+                    Command::Limits(limits) => Mode::Limits(limits.clone()),
+                }
+            }
+            None => {
+                if legacy.len() > 1 {
+                    return Err(format!("{} and {} can't be used together", legacy[0], legacy[1]));
+                }
+                if !legacy.is_empty() && self.read.is_set() {
+                    return Err(format!("{} can't be combined with --once, --query or --diff", legacy[0]));
+                }
+                if !self.watch && self.watch_options.is_set() {
+                    return Err("--stats, --smooth, --adaptive, --log-file and --on-signal need --watch".to_string());
+                }
+                if self.watch {
+                    Mode::Watch(self.watch_options.clone())
+                } else if self.serve.is_some() || self.socket_server.is_some() {
+                    Mode::Serve(ServeArgs { http: self.serve.clone(), unix: self.socket_server.clone() })
+                } else if self.check {
+                    Mode::Check
+                } else {
+                    Mode::Read(self.read.clone())
+                }
+            }
+        };
+
+        if self.output.influx && self.output.time_format == TimeFormat::Iso8601 {
+            return Err("InfluxDB line protocol timestamps are numeric; use --time-format unix or none".to_string());
+        }
+
+        // Checks that span the global options and the mode
+        let source = &self.source;
+        let offline = !source.from_file.is_empty();
+        match &mode {
+            Mode::Read(read) => {
+                let max_files = if read.diff { 2 } else { 1 };
+                // A file reads the same twice, so one dump has nothing to compare
+                if read.diff && source.from_file.len() == 1 {
+                    return Err("--diff compares two dumps; give --from-file twice".to_string());
+                }
+                if source.from_file.len() > max_files {
+                    return Err(format!(
+                        "--from-file given {} times; at most {} allowed",
+                        source.from_file.len(),
+                        max_files
+                    ));
+                }
+            }
+            Mode::Watch(_) if offline => return Err("watch needs the driver, not --from-file".to_string()),
+            Mode::Watch(_) if source.all_sockets => return Err("--all-sockets takes a single reading".to_string()),
+            Mode::Limits(_) if source.all_sockets => return Err("limits checks one socket; pick it with --socket".to_string()),
+            Mode::Limits(limits) if limits.sets_limits() && (offline || source.socket.is_some()) => {
+                return Err("--set-* writes to the default driver instance (or --sysfs-path)".to_string());
+            }
+            Mode::Serve(_) if offline || source.all_sockets => {
+                return Err("serve reads one driver instance; drop --from-file/--all-sockets".to_string());
+            }
+            Mode::Dump(_) if offline || source.all_sockets || source.socket.is_some() => {
+                return Err("dump reads the default driver instance (or --sysfs-path)".to_string());
+            }
+            Mode::Profile(_) if offline || source.all_sockets || source.socket.is_some() => {
+                return Err("profile times reads of the default driver instance (or --sysfs-path)".to_string());
+            }
+            Mode::Check if offline || source.socket.is_some() => {
+                return Err("check probes the default driver instance (or --sysfs-path); use --all-sockets for every socket".to_string());
+            }
+            _ => {}
+        }
+        Ok(mode)
+    }
+}
+
+fn parse_duration(s: &str) -> Result<Duration, String> {
+    humantime::parse_duration(s).map_err(|e| e.to_string())
+}
+
+fn parse_scale(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(scale) if scale.is_finite() && scale > 0.0 => Ok(scale),
+        _ => Err(format!("scale factor must be a positive number, got '{}'", s)),
+    }
+}
+
+/// Byte size with an optional binary unit suffix: 512K, 10MB, 1GiB
+fn parse_size(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (digits, unit) = s.split_at(split);
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1 << 10,
+        "M" | "MB" | "MIB" => 1 << 20,
+        "G" | "GB" | "GIB" => 1 << 30,
+        _ => return Err(format!("invalid size unit in '{}'", s)),
+    };
+    let value: u64 = digits.parse().map_err(|_| format!("invalid size '{}'", s))?;
+    value
+        .checked_mul(multiplier)
+        .filter(|bytes| *bytes > 0)
+        .ok_or_else(|| format!("invalid size '{}'", s))
+}
+
+fn parse_pm_version(s: &str) -> Result<u32, String> {
+    parse_pm_table_version(s).ok_or_else(|| format!("invalid PM table version '{}'", s.trim()))
+}
+
+fn parse_alias(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((field, key)) if !field.is_empty() && !key.is_empty() => {
+            Ok((field.to_string(), key.to_string()))
+        }
+        _ => Err(format!("expected FIELD=KEY, got '{}'", s)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    fn mode(args: &[&str]) -> Result<Mode, String> {
+        let args = Args::try_parse_from(std::iter::once("amd-smu-sensors").chain(args.iter().copied()))
+            .map_err(|e| e.to_string())?;
+        args.mode()
+    }
+
+    #[test]
+    fn test_args_definition() {
+        Args::command().debug_assert();
+    }
+
+    #[test]
+    fn test_commands_and_legacy_flags_agree() {
+        assert!(matches!(mode(&[]), Ok(Mode::Read(_))));
+        assert!(matches!(mode(&["read", "--query", "tctl"]), Ok(Mode::Read(r)) if r.query.as_deref() == Some("tctl")));
+        assert!(matches!(mode(&["--query", "tctl"]), Ok(Mode::Read(r)) if r.query.as_deref() == Some("tctl")));
+        assert!(matches!(mode(&["watch", "--stats", "--json-lines"]), Ok(Mode::Watch(w)) if w.stats));
+        assert!(matches!(mode(&["--json-lines", "--watch", "--stats"]), Ok(Mode::Watch(w)) if w.stats));
+        assert!(matches!(mode(&["watch", "--count", "10"]), Ok(Mode::Watch(w)) if w.count == Some(10)));
+        assert!(matches!(mode(&["--watch", "--delta-only"]), Ok(Mode::Watch(w)) if w.delta_only));
+        assert!(matches!(mode(&["watch", "--no-clear"]), Ok(Mode::Watch(w)) if w.no_clear));
+        assert!(matches!(mode(&["serve", "--http", "127.0.0.1:9101"]), Ok(Mode::Serve(s)) if s.http.is_some()));
+        assert!(matches!(mode(&["--socket-server", "/tmp/s"]), Ok(Mode::Serve(s)) if s.unix.is_some()));
+        assert!(matches!(mode(&["--check"]), Ok(Mode::Check)));
+        assert!(matches!(mode(&["dump", "-o", "pm.bin"]), Ok(Mode::Dump(_))));
+        assert!(matches!(mode(&["check", "--all-sockets"]), Ok(Mode::Check)));
+        assert!(matches!(mode(&["profile", "--duration", "2s"]), Ok(Mode::Profile(p)) if p.duration == Duration::from_secs(2)));
+        assert!(matches!(mode(&["limits", "--expect-ppt", "142"]), Ok(Mode::Limits(l)) if l.expect_ppt == Some(142.0)));
+        assert!(matches!(
+            mode(&["limits", "--set-ppt", "120", "--i-understand-the-risks"]),
+            Ok(Mode::Limits(l)) if l.sets_limits() && l.set_ppt == Some(120.0)
+        ));
+    }
+
+    #[test]
+    fn test_mode_conflicts() {
+        assert!(mode(&["--stats"]).is_err());
+        assert!(mode(&["--count", "10"]).is_err());
+        assert!(mode(&["watch", "--count", "0"]).is_err());
+        assert!(mode(&["watch", "--interval", "50ms"]).is_err());
+        assert!(mode(&["--delta-only"]).is_err());
+        assert!(mode(&["watch", "--delta-only", "--csv"]).is_err());
+        for format in ["--json", "--prometheus", "--sensors-format", "--summary"] {
+            assert!(mode(&["watch", "--delta-only", format]).is_err(), "{}", format);
+        }
+        assert!(mode(&["--offsets", "o.toml", "--force-offsets", "0x240903"]).is_err());
+        assert!(mode(&["--power-scale", "0"]).is_err());
+        assert!(mode(&["--power-scale", "1.1", "--power-calibration", "1.1"]).is_err());
+        assert!(mode(&["--current-calibration", "1.1", "--current-scale", "1.1"]).is_err());
+        assert!(mode(&["--power-scale", "1.1", "--current-calibration", "1.1"]).is_ok());
+        assert!(mode(&["--current-scale", "-1.5"]).is_err());
+        assert!(mode(&["--watch", "--query", "tctl"]).is_err());
+        assert!(mode(&["--watch", "--check"]).is_err());
+        assert!(mode(&["check", "--socket", "1"]).is_err());
+        assert!(mode(&["--watch", "read"]).is_err());
+        assert!(mode(&["--stats", "watch"]).is_err());
+        assert!(mode(&["serve"]).is_err());
+        assert!(mode(&["watch", "--from-file", "pm.bin", "--pm-version", "0x240903"]).is_err());
+        assert!(mode(&["read", "--query", "tctl", "--json"]).is_err());
+        assert!(mode(&["--quiet", "--verbose"]).is_err());
+        assert!(mode(&["--influx", "--time-format", "iso8601"]).is_err());
+        assert!(mode(&["limits", "--set-ppt", "120"]).is_err());
+        assert!(mode(&["limits", "--set-ppt", "120", "--socket", "1", "--i-understand-the-risks"]).is_err());
+        assert!(mode(&["watch", "--csv", "--time-format", "iso8601"]).is_ok());
+        assert!(mode(&["--debug", "--from-file", "pm.bin", "--pm-version", "0x240903"]).is_err());
+        assert!(mode(&["--diff", "--from-file", "pm.bin", "--pm-version", "0x240903"]).is_err());
+        assert!(mode(&["--diff", "--from-file", "a.bin", "--from-file", "b.bin", "--pm-version", "0x240903"]).is_ok());
+        assert!(mode(&["--socket", "1", "--from-file", "pm.bin", "--pm-version", "0x240903"]).is_err());
+        assert!(mode(&["--all-sockets", "--from-file", "pm.bin", "--pm-version", "0x240903"]).is_err());
+    }
+}
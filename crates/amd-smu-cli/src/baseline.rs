@@ -0,0 +1,130 @@
+use amd_smu_lib::{PmTable, ValueDiff};
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+/// Exit code when a reading drifts from `--baseline` by more than the
+/// tolerance
+pub const EXIT_DEVIATION: i32 = 2;
+
+/// A reading further from the baseline than the tolerance allows
+#[derive(Debug, Clone, PartialEq)]
+pub struct Deviation {
+    /// JSON field name, or `core_temp.3` style for per-core readings
+    pub field: String,
+    /// Baseline (before) and live (after) values
+    pub diff: ValueDiff,
+}
+
+impl fmt::Display for Deviation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {:.2} is {:+.1}% from baseline {:.2}",
+            self.field,
+            self.diff.after,
+            self.diff.percent().unwrap_or_default(),
+            self.diff.before
+        )
+    }
+}
+
+/// A known-good reading from `--baseline`, and how far live readings may
+/// stray from it
+#[derive(Debug, Clone)]
+pub struct Baseline {
+    table: PmTable,
+    /// Allowed change, in percent of the baseline value
+    tolerance: f32,
+}
+
+impl Baseline {
+    pub fn new(table: PmTable, tolerance: f32) -> Self {
+        Self { table, tolerance }
+    }
+
+    /// Load a table saved with `--json`
+    pub fn load(path: &Path, tolerance: f32) -> io::Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        let table = serde_json::from_str(&json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Ok(Self::new(table, tolerance))
+    }
+
+    /// Readings in `table` more than the tolerance away from the baseline
+    ///
+    /// Fields the baseline has as 0 (not reported by its layout) have no
+    /// percentage to compare and are never flagged; per-core readings cover
+    /// the cores both tables have.
+    pub fn deviations(&self, table: &PmTable) -> Vec<Deviation> {
+        let diff = self.table.diff(table);
+        let mut all: Vec<(String, ValueDiff)> =
+            diff.fields.into_iter().map(|(name, value)| (name.to_string(), value)).collect();
+        for (name, values) in [
+            ("core_temp", diff.core_temps),
+            ("core_freq", diff.core_freqs),
+            ("core_freq_eff", diff.core_freqs_eff),
+            ("core_power", diff.core_power),
+            ("core_c0", diff.core_c0),
+            ("core_voltage", diff.core_voltages),
+        ] {
+            all.extend(values.into_iter().enumerate().map(|(i, value)| (format!("{}.{}", name, i), value)));
+        }
+        all.into_iter()
+            .filter(|(_, diff)| diff.percent().is_some_and(|p| p.abs() > self.tolerance))
+            .map(|(field, diff)| Deviation { field, diff })
+            .collect()
+    }
+}
+
+/// `EXIT_DEVIATION` if anything drifted, 0 otherwise
+pub fn exit_code(deviations: &[Deviation]) -> i32 {
+    if deviations.is_empty() { 0 } else { EXIT_DEVIATION }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reference() -> PmTable {
+        PmTable {
+            tctl: 65.0,
+            ppt_value: 88.0,
+            fclk: 1800.0,
+            core_temps: vec![60.0, 62.0],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_deviations_beyond_tolerance() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("baseline.json");
+        std::fs::write(&path, serde_json::to_string(&reference()).unwrap()).unwrap();
+        let baseline = Baseline::load(&path, 10.0).unwrap();
+
+        assert!(baseline.deviations(&baseline.table).is_empty());
+
+        // Tctl +20% and core 1 +25% drift; PPT +5% is within tolerance, and
+        // SoC temp has no baseline to compare against
+        let sample = PmTable { tctl: 78.0, ppt_value: 92.4, soc_temp: 45.0, core_temps: vec![61.0, 77.5], ..reference() };
+        let deviations = baseline.deviations(&sample);
+        let fields: Vec<&str> = deviations.iter().map(|d| d.field.as_str()).collect();
+        assert_eq!(fields, vec!["tctl", "core_temp.1"]);
+        assert_eq!(deviations[0].to_string(), "tctl 78.00 is +20.0% from baseline 65.00");
+        assert_eq!(exit_code(&deviations), EXIT_DEVIATION);
+        assert_eq!(exit_code(&baseline.deviations(&PmTable { ppt_value: 92.4, ..reference() })), 0);
+
+        // A drop counts as much as a rise
+        let cooler = PmTable { fclk: 1200.0, ..reference() };
+        assert_eq!(baseline.deviations(&cooler)[0].field, "fclk");
+    }
+
+    #[test]
+    fn test_load_rejects_non_table_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("baseline.json");
+        std::fs::write(&path, "[1, 2]").unwrap();
+        assert_eq!(Baseline::load(&path, 10.0).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert!(Baseline::load(&dir.path().join("missing.json"), 10.0).is_err());
+    }
+}
@@ -0,0 +1,126 @@
+use amd_smu_lib::{full_version, supported_pm_table_versions, SmuReader};
+use serde::Serialize;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Raw PM table blob in a bundle
+pub const PM_TABLE_FILE: &str = "pm_table.bin";
+/// `BundleInfo` as JSON
+pub const INFO_FILE: &str = "info.json";
+/// Copy of /proc/cpuinfo
+pub const CPUINFO_FILE: &str = "cpuinfo.txt";
+
+/// Driver and build facts recorded by `dump --bundle`
+///
+/// Every probe failure is recorded rather than aborting, so a bundle from
+/// an unsupported or half-working system still says as much as it can.
+#[derive(Debug, Default, Serialize)]
+pub struct BundleInfo {
+    /// This tool's version, git commit and build date
+    pub tool_version: String,
+    pub sysfs_path: String,
+    pub smu_version: Option<String>,
+    pub driver_version: Option<String>,
+    pub codename: Option<String>,
+    /// Hex, e.g. "0x240903"
+    pub pm_table_version: Option<String>,
+    /// Size the driver reports
+    pub pm_table_size: Option<usize>,
+    /// Bytes actually read into `pm_table.bin`
+    pub pm_table_bytes: Option<usize>,
+    /// Whether this build has a layout for the PM table version
+    pub supported: bool,
+    /// Probes that failed, as "<probe>: <error>"
+    pub errors: Vec<String>,
+}
+
+/// Write a diagnostic bundle for `reader` into `dir`, creating it
+///
+/// The bundle holds the raw PM table (`pm_table.bin`, read without parsing
+/// so unsupported versions work too), `info.json` and a copy of `cpuinfo`
+/// (`cpuinfo.txt`). Files that can't be captured are left out and the
+/// reason is listed in `info.json`. Fails only if the bundle itself can't
+/// be written.
+pub fn write_bundle(reader: &SmuReader, dir: &Path, cpuinfo: &Path) -> io::Result<BundleInfo> {
+    fs::create_dir_all(dir)?;
+    let mut info = BundleInfo {
+        tool_version: full_version().to_string(),
+        sysfs_path: reader.sysfs_path().display().to_string(),
+        ..Default::default()
+    };
+    let mut errors = Vec::new();
+    match reader.smu_version() {
+        Ok(version) => info.smu_version = Some(version.trim().to_string()),
+        Err(e) => errors.push(format!("smu_version: {}", e)),
+    }
+    match reader.driver_version() {
+        Ok(version) => info.driver_version = Some(version.trim().to_string()),
+        Err(e) => errors.push(format!("driver_version: {}", e)),
+    }
+    match reader.codename() {
+        Ok(codename) => info.codename = Some(codename.to_string()),
+        Err(e) => errors.push(format!("codename: {}", e)),
+    }
+    match reader.pm_table_version() {
+        Ok(version) => {
+            info.pm_table_version = Some(format!("{:#x}", version));
+            info.supported = supported_pm_table_versions().contains(&version);
+        }
+        Err(e) => errors.push(format!("pm_table_version: {}", e)),
+    }
+    match reader.pm_table_size() {
+        Ok(size) => info.pm_table_size = Some(size),
+        Err(e) => errors.push(format!("pm_table_size: {}", e)),
+    }
+    match reader.read_pm_table_raw() {
+        Ok(data) => {
+            fs::write(dir.join(PM_TABLE_FILE), &data)?;
+            info.pm_table_bytes = Some(data.len());
+        }
+        Err(e) => errors.push(format!("pm_table: {}", e)),
+    }
+    match fs::read(cpuinfo) {
+        Ok(data) => fs::write(dir.join(CPUINFO_FILE), data)?,
+        Err(e) => errors.push(format!("{}: {}", cpuinfo.display(), e)),
+    }
+    info.errors = errors;
+
+    let json = serde_json::to_string_pretty(&info).map_err(io::Error::other)?;
+    fs::write(dir.join(INFO_FILE), json + "\n")?;
+    Ok(info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundle_for_unsupported_version() {
+        let sysfs = tempfile::tempdir().unwrap();
+        fs::write(sysfs.path().join("version"), "SMU v46.54.0\n").unwrap();
+        fs::write(sysfs.path().join("codename"), "12\n").unwrap();
+        fs::write(sysfs.path().join("pm_table_version"), 0x123456u32.to_le_bytes()).unwrap();
+        fs::write(sysfs.path().join("pm_table_size"), "8\n").unwrap();
+        fs::write(sysfs.path().join("pm_table"), [1u8, 2, 3, 4, 5, 6, 7, 8]).unwrap();
+        let cpuinfo = sysfs.path().join("cpuinfo");
+        fs::write(&cpuinfo, "processor\t: 0\n").unwrap();
+
+        let out = tempfile::tempdir().unwrap();
+        let dir = out.path().join("bundle");
+        let reader = SmuReader::with_path(sysfs.path()).unwrap();
+        let info = write_bundle(&reader, &dir, &cpuinfo).unwrap();
+
+        assert_eq!(fs::read(dir.join(PM_TABLE_FILE)).unwrap(), [1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(fs::read_to_string(dir.join(CPUINFO_FILE)).unwrap(), "processor\t: 0\n");
+        assert_eq!(info.pm_table_version.as_deref(), Some("0x123456"));
+        assert!(!info.supported);
+        // No drv_version file in this mock
+        assert!(info.errors.iter().any(|e| e.starts_with("driver_version: ")), "{:?}", info.errors);
+
+        let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(dir.join(INFO_FILE)).unwrap()).unwrap();
+        assert_eq!(json["codename"], "Vermeer");
+        assert_eq!(json["pm_table_bytes"], 8);
+        assert_eq!(json["smu_version"], "SMU v46.54.0");
+    }
+}
@@ -0,0 +1,212 @@
+use amd_smu_lib::{supported_pm_table_versions, Codename, SmuError, SmuReader};
+use serde::Serialize;
+use std::path::Path;
+
+/// Exit code for a driver that reads fine but reports suspect values
+pub const EXIT_WARNING: i32 = 1;
+/// Exit code for a missing, unreadable or unsupported driver
+pub const EXIT_CRITICAL: i32 = 2;
+
+/// Result of the `check` command, printed as JSON
+///
+/// Every probe failure is recorded rather than aborting, so monitoring gets
+/// a complete answer even when the driver is missing.
+#[derive(Debug, Default, Serialize)]
+pub struct HealthStatus {
+    pub module_loaded: bool,
+    pub readable: bool,
+    /// Hex, e.g. "0x240903"
+    pub pm_table_version: Option<String>,
+    /// Whether this build has a layout for the PM table version
+    pub supported: bool,
+    pub codename: Option<String>,
+    /// Guess at PBO from the limits ("likely enabled", "likely off" or
+    /// "unknown"); null without a reading or with --no-pbo-detect
+    pub pbo: Option<String>,
+    pub core_count: usize,
+    /// SMU mailboxes with a command file, e.g. ["RSMU", "MP1"]
+    pub mailboxes: Vec<String>,
+    /// Validation warnings from the last read
+    pub warnings: Vec<String>,
+    /// The probe that failed first, if any
+    pub error: Option<String>,
+}
+
+impl HealthStatus {
+    /// Nagios-style exit code: 0 healthy, 1 warnings, 2 not usable
+    pub fn exit_code(&self) -> i32 {
+        if !(self.module_loaded && self.readable && self.supported) {
+            EXIT_CRITICAL
+        } else if !self.warnings.is_empty() {
+            EXIT_WARNING
+        } else {
+            0
+        }
+    }
+}
+
+/// `check` result for one socket, as listed by `check --all-sockets`
+#[derive(Debug, Serialize)]
+pub struct SocketStatus {
+    pub socket: usize,
+    #[serde(flatten)]
+    pub status: HealthStatus,
+}
+
+/// Worst exit code across sockets, so one bad socket fails the whole check
+pub fn exit_code_all(sockets: &[SocketStatus]) -> i32 {
+    sockets.iter().map(|s| s.status.exit_code()).max().unwrap_or(EXIT_CRITICAL)
+}
+
+/// Probe the driver at `sysfs_path` (or the default location) step by step
+pub fn check(sysfs_path: Option<&Path>, codename: Option<Codename>, detect_pbo: bool) -> HealthStatus {
+    let mut status = HealthStatus::default();
+
+    let reader = match SmuReader::with_path_or_env(sysfs_path) {
+        Ok(reader) => reader,
+        Err(e) => {
+            status.error = Some(e.to_string());
+            return status;
+        }
+    };
+    status.module_loaded = true;
+    let reader = match codename {
+        Some(codename) => reader.with_codename(codename),
+        None => reader,
+    };
+
+    match codename.map_or_else(|| reader.codename(), Ok) {
+        Ok(codename) => status.codename = Some(codename.to_string()),
+        Err(e) => status.error = Some(e.to_string()),
+    }
+    if let Ok(mailboxes) = reader.mailbox_interfaces() {
+        status.mailboxes = mailboxes.iter().map(ToString::to_string).collect();
+    }
+    match reader.pm_table_version() {
+        Ok(version) => {
+            status.pm_table_version = Some(format!("{:#x}", version));
+            status.supported = supported_pm_table_versions().contains(&version);
+        }
+        Err(e) => {
+            status.error.get_or_insert(e.to_string());
+        }
+    }
+
+    match reader.read_pm_table() {
+        Ok(table) => {
+            status.readable = true;
+            status.core_count = table.core_temps.len();
+            if detect_pbo {
+                status.pbo = Some(table.pbo_status().to_string());
+            }
+            status.warnings = table.warnings;
+        }
+        Err(e) => {
+            // An unsupported layout still means the blob itself was readable
+            status.readable = matches!(e, SmuError::UnsupportedPmTableVersion(_))
+                && reader.read_pm_table_raw().is_ok();
+            status.error.get_or_insert(e.to_string());
+        }
+    }
+    status
+}
+
+/// Probe every socket's driver instance next to `sysfs_path` (or the
+/// default location), in socket order
+///
+/// A single-socket system is a one-element list; so is one with no driver
+/// at all, whose entry says the module isn't loaded.
+pub fn check_all(sysfs_path: Option<&Path>, codename: Option<Codename>, detect_pbo: bool) -> Vec<SocketStatus> {
+    let discovered = match sysfs_path {
+        Some(path) => SmuReader::discover_from(path),
+        None => SmuReader::discover_all(),
+    };
+    match discovered {
+        Ok(readers) => readers
+            .iter()
+            .enumerate()
+            .map(|(socket, reader)| SocketStatus { socket, status: check(Some(reader.sysfs_path()), codename, detect_pbo) })
+            .collect(),
+        Err(e) => vec![SocketStatus { socket: 0, status: HealthStatus { error: Some(e.to_string()), ..Default::default() } }],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn mock_sysfs(dir: &Path, version: u32) {
+        fs::write(dir.join("version"), "SMU v46.54.0\n").unwrap();
+        fs::write(dir.join("codename"), "12\n").unwrap();
+        fs::write(dir.join("pm_table_version"), version.to_le_bytes()).unwrap();
+        fs::write(dir.join("pm_table_size"), "6832\n").unwrap();
+        let mut table = vec![0u8; 6832];
+        table[0x014..0x018].copy_from_slice(&65.2f32.to_le_bytes());
+        fs::write(dir.join("pm_table"), table).unwrap();
+    }
+
+    #[test]
+    fn test_check_healthy() {
+        let dir = tempfile::tempdir().unwrap();
+        mock_sysfs(dir.path(), 0x240903);
+        fs::write(dir.path().join("mp1_smu_cmd"), [0u8; 4]).unwrap();
+        let status = check(Some(dir.path()), None, true);
+        assert!(status.module_loaded && status.readable && status.supported);
+        assert_eq!(status.mailboxes, vec!["MP1"]);
+        assert_eq!(status.pm_table_version.as_deref(), Some("0x240903"));
+        assert_eq!(status.codename.as_deref(), Some("Vermeer"));
+        // The mock has no limits to compare
+        assert_eq!(status.pbo.as_deref(), Some("unknown"));
+        assert!(status.core_count > 0);
+        assert_eq!(check(Some(dir.path()), None, false).pbo, None);
+        assert_eq!(status.exit_code(), 0);
+    }
+
+    #[test]
+    fn test_check_missing_module() {
+        let dir = tempfile::tempdir().unwrap();
+        let status = check(Some(&dir.path().join("missing")), None, true);
+        assert!(!status.module_loaded);
+        assert!(status.error.unwrap().contains("not loaded"));
+        assert_eq!(status.pm_table_version, None);
+    }
+
+    #[test]
+    fn test_check_unsupported_version() {
+        let dir = tempfile::tempdir().unwrap();
+        mock_sysfs(dir.path(), 0x123456);
+        let status = check(Some(dir.path()), None, true);
+        assert!(status.module_loaded && status.readable);
+        assert!(!status.supported);
+        assert_eq!(status.pm_table_version.as_deref(), Some("0x123456"));
+        assert_eq!(status.exit_code(), EXIT_CRITICAL);
+    }
+
+    #[test]
+    fn test_check_all_sockets() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().join("ryzen_smu_drv");
+        for (path, version) in [(&base, 0x240903), (&dir.path().join("ryzen_smu_drv1"), 0x123456)] {
+            fs::create_dir(path).unwrap();
+            mock_sysfs(path, version);
+        }
+        let sockets = check_all(Some(&base), None, true);
+        assert_eq!(sockets.iter().map(|s| s.socket).collect::<Vec<_>>(), vec![0, 1]);
+        assert!(sockets[0].status.supported && !sockets[1].status.supported);
+        assert_eq!(exit_code_all(&sockets), EXIT_CRITICAL);
+
+        let json = serde_json::to_value(&sockets[0]).unwrap();
+        assert_eq!(json["socket"], 0);
+        assert_eq!(json["codename"], "Vermeer");
+    }
+
+    #[test]
+    fn test_check_all_without_driver() {
+        let dir = tempfile::tempdir().unwrap();
+        let sockets = check_all(Some(&dir.path().join("ryzen_smu_drv")), None, true);
+        assert_eq!(sockets.len(), 1);
+        assert!(!sockets[0].status.module_loaded);
+        assert!(sockets[0].status.error.as_ref().unwrap().contains("not loaded"));
+    }
+}
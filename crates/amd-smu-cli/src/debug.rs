@@ -0,0 +1,72 @@
+use amd_smu_lib::{pm_table_offsets, PmTable, PmTableOffsets};
+use std::fmt::Write;
+
+/// `--debug` breakdown of one read: the layout it was parsed with, then
+/// each located reading's offset, raw bytes and parsed value
+///
+/// `layout` is the version whose offsets were used, which differs from the
+/// table's own version under `--force-offsets`; `custom` are the offsets
+/// from `--offsets`, used instead. Parsed values are after validation, so a
+/// reading reset to 0 next to a wild raw value points at a wrong offset.
+pub fn format_offset_report(table: &PmTable, raw: &[u8], layout: u32, custom: Option<&PmTableOffsets>) -> String {
+    let mut out = format!(
+        "PM table version {:#x} ({}, {} cores, {} bytes)\n",
+        table.version,
+        table.codename_str,
+        table.core_temps.len(),
+        raw.len()
+    );
+    if let Some(offsets) = custom {
+        let _ = writeln!(out, "Layout from --offsets: {:#x?}", offsets);
+    }
+    let Some(offsets) = custom.copied().or_else(|| pm_table_offsets(layout)) else {
+        let _ = writeln!(out, "No layout for version {:#x}", layout);
+        return out;
+    };
+    if custom.is_none() {
+        let _ = writeln!(out, "Layout {:#x}: {:#x?}", layout, offsets);
+    }
+    let _ = writeln!(out, "Core power sum: {:.1} W (package cores: {:.1} W)", table.core_power_sum(), table.package_core_power());
+
+    let metrics = table.as_metric_map();
+    let _ = writeln!(out, "{:<16} {:>7}  {:<10}  {:>12}  {:>12}", "Reading", "Offset", "Raw u32", "As f32", "Parsed");
+    for (name, offset) in offsets.named() {
+        let bytes = raw.get(offset..offset + 4).and_then(|b| <[u8; 4]>::try_from(b).ok());
+        let (word, float) = match bytes {
+            Some(bytes) => (format!("{:#010x}", u32::from_le_bytes(bytes)), format!("{:.3}", f32::from_le_bytes(bytes))),
+            None => ("—".to_string(), "—".to_string()),
+        };
+        let parsed = metrics.get(name).map_or_else(|| "—".to_string(), |value| format!("{:.3}", value));
+        let _ = writeln!(out, "{:<16} {:>#7x}  {:<10}  {:>12}  {:>12}", name, offset, word, float, parsed);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use amd_smu_lib::Codename;
+
+    #[test]
+    fn test_offset_report() {
+        let mut raw = vec![0u8; 0x400];
+        raw[0x014..0x018].copy_from_slice(&65.2f32.to_le_bytes());
+        raw[0x0C0..0x0C4].copy_from_slice(&f32::NAN.to_le_bytes());
+        let table = PmTable::parse(&raw, 0x240903, Codename::Vermeer, 1).unwrap();
+
+        let report = format_offset_report(&table, &raw, 0x240903, None);
+        assert!(report.starts_with("PM table version 0x240903 (Vermeer, 1 cores, 1024 bytes)\n"), "{}", report);
+        assert!(report.contains("Layout 0x240903: PmTableOffsets {"));
+        assert!(report.contains("\nCore power sum: 0.0 W (package cores: 0.0 W)\n"), "{}", report);
+        assert!(report.contains("\ntctl                0x14  0x42826666        65.200        65.200\n"), "{}", report);
+        // Garbage is reset by validation, leaving the raw value to explain it
+        assert!(report.contains("\nfclk                0xc0  0x7fc00000           NaN         0.000\n"), "{}", report);
+
+        assert!(format_offset_report(&table, &raw, 0x123456, None).ends_with("No layout for version 0x123456\n"));
+
+        let custom = pm_table_offsets(0x240903).unwrap();
+        let report = format_offset_report(&table, &raw, 0x123456, Some(&custom));
+        assert!(report.contains("Layout from --offsets: PmTableOffsets {"), "{}", report);
+        assert!(report.contains("\ntctl                0x14  0x42826666        65.200        65.200\n"), "{}", report);
+    }
+}
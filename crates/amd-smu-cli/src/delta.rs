@@ -0,0 +1,66 @@
+use amd_smu_lib::{PmTable, ValueDiff};
+use std::fmt::Write;
+
+/// Smallest change `--delta-only` reports, in the reading's own unit (°C,
+/// W, A, MHz, V or %)
+///
+/// Small enough to keep a 10 mV voltage step, large enough to drop float
+/// noise on readings that haven't really moved.
+pub const DELTA_EPSILON: f32 = 0.005;
+
+fn changed(diff: &ValueDiff) -> bool {
+    diff.delta().abs() > DELTA_EPSILON
+}
+
+/// `--delta-only` lines for the readings that moved from `before` to
+/// `after`, e.g. "tctl: 65.20 -> 71.40 (+6.20)"; empty when nothing did
+///
+/// Per-core readings are named `core_temp.3` and so on. A change in core
+/// count gets its own line, and cores that only `after` has are listed
+/// with their value.
+pub fn format_changes(before: &PmTable, after: &PmTable) -> String {
+    let mut out = String::new();
+    let diff = before.diff(after);
+    for (name, value) in diff.fields.iter().filter(|(_, value)| changed(value)) {
+        let _ = writeln!(out, "{}: {:.2} -> {:.2} ({:+.2})", name, value.before, value.after, value.delta());
+    }
+
+    if before.core_temps.len() != after.core_temps.len() {
+        let _ = writeln!(out, "cores: {} -> {}", before.core_temps.len(), after.core_temps.len());
+    }
+    for (name, values, current) in [
+        ("core_temp", &diff.core_temps, &after.core_temps),
+        ("core_freq", &diff.core_freqs, &after.core_freqs),
+        ("core_freq_eff", &diff.core_freqs_eff, &after.core_freqs_eff),
+        ("core_power", &diff.core_power, &after.core_power),
+        ("core_c0", &diff.core_c0, &after.core_c0),
+        ("core_voltage", &diff.core_voltages, &after.core_voltages),
+    ] {
+        for (i, value) in values.iter().enumerate().filter(|(_, value)| changed(value)) {
+            let _ = writeln!(out, "{}.{}: {:.2} -> {:.2} ({:+.2})", name, i, value.before, value.after, value.delta());
+        }
+        for (i, value) in current.iter().enumerate().skip(values.len()) {
+            let _ = writeln!(out, "{}.{}: {:.2} (new)", name, i, value);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_changes() {
+        let before = PmTable { tctl: 65.2, fclk: 1800.0, core_temps: vec![60.0, 62.0], ..Default::default() };
+        assert_eq!(format_changes(&before, &before), "");
+
+        // Float noise below the epsilon is dropped
+        let after = PmTable { tctl: 71.4, fclk: 1800.001, core_temps: vec![60.0, 64.5], ..before.clone() };
+        assert_eq!(format_changes(&before, &after), "tctl: 65.20 -> 71.40 (+6.20)\ncore_temp.1: 62.00 -> 64.50 (+2.50)\n");
+
+        let more = PmTable { core_temps: vec![60.0, 62.0, 58.0], ..before.clone() };
+        assert_eq!(format_changes(&before, &more), "cores: 2 -> 3\ncore_temp.2: 58.00 (new)\n");
+        assert_eq!(format_changes(&more, &before), "cores: 3 -> 2\n");
+    }
+}
@@ -0,0 +1,127 @@
+use crate::output::{format_json, format_prometheus, FieldAliases};
+use amd_smu_lib::PmTableSource;
+use serde_json::json;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+
+const PROMETHEUS_TYPE: &str = "text/plain; version=0.0.4";
+const JSON_TYPE: &str = "application/json";
+
+/// Serve readings over HTTP, reading the PM table on every request
+///
+/// Routes are `GET /metrics` (Prometheus), `GET /pm_table` (JSON, as
+/// `--json` prints it) and `GET /health`. Requests are handled one at a
+/// time; scrapes are infrequent and a read takes milliseconds, so there's
+/// nothing to gain from concurrency here.
+pub fn serve_http(addr: &str, source: &mut impl PmTableSource, aliases: &FieldAliases) -> io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    let addr = listener.local_addr()?;
+    notice!("Serving http://{0}/metrics (Prometheus), http://{0}/pm_table (JSON) and http://{0}/health", addr);
+    serve(&listener, source, aliases);
+    Ok(())
+}
+
+fn serve(listener: &TcpListener, source: &mut impl PmTableSource, aliases: &FieldAliases) {
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                if let Err(e) = handle(stream, source, aliases) {
+                    eprintln!("Warning: request failed: {}", e);
+                }
+            }
+            Err(e) => eprintln!("Warning: accept failed: {}", e),
+        }
+    }
+}
+
+fn handle(mut stream: TcpStream, source: &mut impl PmTableSource, aliases: &FieldAliases) -> io::Result<()> {
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+
+    let (status, content_type, body) = match (method, path) {
+        ("GET", "/metrics") => match source.read() {
+            Ok(table) => ("200 OK", PROMETHEUS_TYPE, format_prometheus(&table)),
+            Err(e) => ("503 Service Unavailable", PROMETHEUS_TYPE, format!("# error reading PM table: {}\n", e)),
+        },
+        ("GET", "/pm_table") => match source.read() {
+            Ok(table) => ("200 OK", JSON_TYPE, format_json(&table, aliases)),
+            Err(e) => ("503 Service Unavailable", JSON_TYPE, json!({ "error": e.to_string() }).to_string()),
+        },
+        ("GET", "/health") => {
+            let (status, health) = health(source);
+            (status, JSON_TYPE, health.to_string())
+        }
+        ("GET", _) => ("404 Not Found", "text/plain", "not found\n".to_string()),
+        _ => ("405 Method Not Allowed", "text/plain", "method not allowed\n".to_string()),
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+/// `/health` answer: whether a fresh read succeeds, plus what it came from
+fn health(source: &mut impl PmTableSource) -> (&'static str, serde_json::Value) {
+    let smu_version = source.smu_version();
+    match source.read() {
+        Ok(table) => ("200 OK", json!({
+            "status": "ok",
+            "smu_version": smu_version,
+            "codename": table.codename_str,
+            "pm_table_version": format!("{:#x}", table.version),
+            "warnings": table.warnings,
+        })),
+        Err(e) => ("503 Service Unavailable", json!({
+            "status": "error",
+            "smu_version": smu_version,
+            "error": e.to_string(),
+        })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use amd_smu_lib::{MockSource, PmTable};
+    use std::io::Read;
+
+    fn get(addr: std::net::SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn test_json_routes() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let table = PmTable { version: 0x240903, codename_str: "Vermeer".into(), tctl: 65.5, ..Default::default() };
+        std::thread::spawn(move || serve(&listener, &mut MockSource::new(vec![table]), &FieldAliases::default()));
+
+        let response = get(addr, "/pm_table");
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("Content-Type: application/json"));
+        let body: serde_json::Value = serde_json::from_str(response.split("\r\n\r\n").nth(1).unwrap()).unwrap();
+        assert_eq!(body["tctl"], 65.5);
+
+        let response = get(addr, "/health");
+        let body: serde_json::Value = serde_json::from_str(response.split("\r\n\r\n").nth(1).unwrap()).unwrap();
+        assert_eq!(body["status"], "ok");
+        assert_eq!(body["codename"], "Vermeer");
+        assert_eq!(body["pm_table_version"], "0x240903");
+
+        assert!(get(addr, "/metrics").contains("amd_smu_tctl_celsius 65.5"));
+        assert!(get(addr, "/nope").starts_with("HTTP/1.1 404"));
+    }
+}
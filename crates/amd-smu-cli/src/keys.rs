@@ -0,0 +1,130 @@
+use amd_smu_lib::{OutputSections, MIN_INTERVAL};
+use crossterm::terminal;
+use std::io::{self, IsTerminal, Read};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+/// How often a waiting watch loop checks for Ctrl-C
+const POLL: Duration = Duration::from_millis(50);
+/// What Ctrl-C reads as while the terminal is in raw mode
+const CTRL_C: u8 = 0x03;
+/// Interval change per `+`/`-`, as in the TUI
+const INTERVAL_STEP: Duration = Duration::from_millis(100);
+
+/// Key reference for the bottom of the watch screen
+pub const FOOTER: &str = "[+/-] Interval  [t] Temps  [p] Power  [f] Freq  [Ctrl-C] Quit";
+
+/// Single-key watch-mode command, mirroring the TUI's keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyCommand {
+    /// `+` (or `=`): sample more often
+    Faster,
+    /// `-`: sample less often
+    Slower,
+    /// `t`, `p` or `f`: show or hide a section
+    Toggle(OutputSections),
+    /// Ctrl-C typed while waiting for a key
+    Quit,
+}
+
+impl KeyCommand {
+    pub fn from_key(key: u8) -> Option<Self> {
+        match key {
+            b'+' | b'=' => Some(Self::Faster),
+            b'-' => Some(Self::Slower),
+            b't' => Some(Self::Toggle(OutputSections::TEMPS)),
+            b'p' => Some(Self::Toggle(OutputSections::POWER)),
+            b'f' => Some(Self::Toggle(OutputSections::FREQ)),
+            CTRL_C => Some(Self::Quit),
+            _ => None,
+        }
+    }
+
+    /// `interval` after this command, in 100ms steps down to 100ms
+    pub fn adjust_interval(self, interval: Duration) -> Duration {
+        match self {
+            Self::Faster => interval.saturating_sub(INTERVAL_STEP).max(MIN_INTERVAL),
+            Self::Slower => interval.saturating_add(INTERVAL_STEP),
+            Self::Toggle(_) | Self::Quit => interval,
+        }
+    }
+}
+
+/// Keys typed on a terminal stdin, read in the background
+///
+/// The terminal is in raw mode only while `wait` runs, so keys take effect
+/// without Enter and don't echo, while output is still printed with the
+/// usual line handling. Raw mode turns Ctrl-C into a key, hence
+/// `KeyCommand::Quit`.
+pub struct KeyReader {
+    commands: Receiver<KeyCommand>,
+}
+
+impl KeyReader {
+    /// Start reading keys, or None if stdin isn't a terminal (e.g. piped)
+    pub fn spawn() -> Option<Self> {
+        if !io::stdin().is_terminal() {
+            return None;
+        }
+
+        let (tx, commands) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut key = [0u8; 1];
+            while let Ok(1) = io::stdin().lock().read(&mut key) {
+                if let Some(command) = KeyCommand::from_key(key[0])
+                    && tx.send(command).is_err()
+                {
+                    break;
+                }
+            }
+        });
+        Some(Self { commands })
+    }
+
+    /// Wait up to `timeout` for a command; None on timeout or once
+    /// `running` is cleared
+    pub fn wait(&self, running: &AtomicBool, timeout: Duration) -> Option<KeyCommand> {
+        // Without raw mode keys still arrive, just a line at a time
+        let _ = terminal::enable_raw_mode();
+        let command = self.recv(running, timeout);
+        let _ = terminal::disable_raw_mode();
+        command
+    }
+
+    fn recv(&self, running: &AtomicBool, timeout: Duration) -> Option<KeyCommand> {
+        let deadline = Instant::now() + timeout;
+        while running.load(Ordering::SeqCst) {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            match self.commands.recv_timeout(remaining.min(POLL)) {
+                Ok(command) => return Some(command),
+                Err(RecvTimeoutError::Timeout) => {}
+                // Stdin closed; keep the interval
+                Err(RecvTimeoutError::Disconnected) => std::thread::sleep(remaining.min(POLL)),
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_commands() {
+        assert_eq!(KeyCommand::from_key(b'+'), Some(KeyCommand::Faster));
+        assert_eq!(KeyCommand::from_key(b'p'), Some(KeyCommand::Toggle(OutputSections::POWER)));
+        assert_eq!(KeyCommand::from_key(b'x'), None);
+        assert_eq!(KeyCommand::from_key(CTRL_C), Some(KeyCommand::Quit));
+
+        let second = Duration::from_secs(1);
+        assert_eq!(KeyCommand::Faster.adjust_interval(second), Duration::from_millis(900));
+        assert_eq!(KeyCommand::Slower.adjust_interval(second), Duration::from_millis(1100));
+        assert_eq!(KeyCommand::Faster.adjust_interval(Duration::from_millis(150)), MIN_INTERVAL);
+        assert_eq!(KeyCommand::Toggle(OutputSections::FREQ).adjust_interval(second), second);
+    }
+}
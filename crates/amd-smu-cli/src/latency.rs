@@ -0,0 +1,120 @@
+use amd_smu_lib::Result;
+use std::time::{Duration, Instant};
+
+/// Read latencies collected by the `profile` command
+#[derive(Debug, Default)]
+pub struct LatencyStats {
+    /// Successful reads, sorted once profiling ends
+    latencies: Vec<Duration>,
+    pub errors: usize,
+    /// Wall-clock time spent reading
+    pub elapsed: Duration,
+}
+
+impl LatencyStats {
+    pub fn reads(&self) -> usize {
+        self.latencies.len()
+    }
+
+    pub fn min(&self) -> Duration {
+        self.latencies.first().copied().unwrap_or_default()
+    }
+
+    pub fn max(&self) -> Duration {
+        self.latencies.last().copied().unwrap_or_default()
+    }
+
+    pub fn mean(&self) -> Duration {
+        match self.latencies.len() {
+            0 => Duration::ZERO,
+            n => self.latencies.iter().sum::<Duration>() / n as u32,
+        }
+    }
+
+    /// Nearest-rank percentile, `p` in 0..=100
+    pub fn percentile(&self, p: f64) -> Duration {
+        if self.latencies.is_empty() {
+            return Duration::ZERO;
+        }
+        let rank = (p / 100.0 * self.latencies.len() as f64).ceil() as usize;
+        self.latencies[rank.clamp(1, self.latencies.len()) - 1]
+    }
+
+    /// Successful reads per second
+    pub fn rate(&self) -> f64 {
+        match self.elapsed.as_secs_f64() {
+            secs if secs > 0.0 => self.reads() as f64 / secs,
+            _ => 0.0,
+        }
+    }
+}
+
+/// Call `read` back to back for `duration`, timing each call
+///
+/// Failed reads are counted but not timed.
+pub fn profile<T>(duration: Duration, mut read: impl FnMut() -> Result<T>) -> LatencyStats {
+    let mut stats = LatencyStats::default();
+    let start = Instant::now();
+    while start.elapsed() < duration {
+        let before = Instant::now();
+        match read() {
+            Ok(_) => stats.latencies.push(before.elapsed()),
+            Err(_) => stats.errors += 1,
+        }
+    }
+    stats.elapsed = start.elapsed();
+    stats.latencies.sort();
+    stats
+}
+
+pub fn format_latency(stats: &LatencyStats) -> String {
+    let micros = |d: Duration| d.as_secs_f64() * 1e6;
+    format!(
+        "Profiled {} reads in {:.2}s ({:.1} reads/s, {} errors)\n\
+         Latency: min {:.1}µs  mean {:.1}µs  p99 {:.1}µs  max {:.1}µs\n",
+        stats.reads(),
+        stats.elapsed.as_secs_f64(),
+        stats.rate(),
+        stats.errors,
+        micros(stats.min()),
+        micros(stats.mean()),
+        micros(stats.percentile(99.0)),
+        micros(stats.max()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use amd_smu_lib::SmuError;
+
+    fn stats(micros: impl IntoIterator<Item = u64>) -> LatencyStats {
+        let mut latencies: Vec<Duration> = micros.into_iter().map(Duration::from_micros).collect();
+        latencies.sort();
+        LatencyStats { latencies, errors: 0, elapsed: Duration::from_secs(2) }
+    }
+
+    #[test]
+    fn test_latency_summary() {
+        let stats = stats((1..=100).rev());
+        assert_eq!(stats.min(), Duration::from_micros(1));
+        assert_eq!(stats.max(), Duration::from_micros(100));
+        assert_eq!(stats.percentile(99.0), Duration::from_micros(99));
+        assert_eq!(stats.percentile(0.0), Duration::from_micros(1));
+        assert_eq!(stats.mean(), Duration::from_nanos(50_500));
+        assert_eq!(stats.rate(), 50.0);
+        assert!(format_latency(&stats).contains("p99 99.0µs"));
+    }
+
+    #[test]
+    fn test_profile_counts_errors() {
+        let mut calls = 0;
+        let stats = profile(Duration::from_millis(5), || {
+            calls += 1;
+            if calls % 2 == 0 { Err(SmuError::UnsupportedProcessor(0)) } else { Ok(()) }
+        });
+        assert!(stats.reads() > 0);
+        assert!(stats.errors.abs_diff(stats.reads()) <= 1);
+        assert!(stats.elapsed >= Duration::from_millis(5));
+    }
+}
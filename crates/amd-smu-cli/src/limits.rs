@@ -0,0 +1,87 @@
+use amd_smu_lib::{Limit, PmTable};
+
+/// Exit code when a limit differs from its `--expect-*` value
+pub const EXIT_MISMATCH: i32 = 2;
+
+/// A configured limit, and what it was expected to be
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LimitAudit {
+    pub limit: Limit,
+    pub actual: f32,
+    pub expected: Option<f32>,
+}
+
+impl LimitAudit {
+    /// Whether `actual` is within `tolerance` percent of `expected`; true
+    /// when nothing was expected
+    pub fn matches(&self, tolerance: f32) -> bool {
+        self.expected
+            .is_none_or(|expected| (self.actual - expected).abs() <= expected.abs() * tolerance / 100.0)
+    }
+
+    fn unit(&self) -> &'static str {
+        match self.limit {
+            Limit::Ppt => "W",
+            Limit::Thermal => "°C",
+            Limit::Tdc | Limit::Edc => "A",
+        }
+    }
+}
+
+/// PPT, TDC and EDC limits from `table`, against `[ppt, tdc, edc]`
+pub fn audit_limits(table: &PmTable, expected: [Option<f32>; 3]) -> [LimitAudit; 3] {
+    let [ppt, tdc, edc] = expected;
+    [
+        LimitAudit { limit: Limit::Ppt, actual: table.ppt_limit, expected: ppt },
+        LimitAudit { limit: Limit::Tdc, actual: table.tdc_limit, expected: tdc },
+        LimitAudit { limit: Limit::Edc, actual: table.edc_limit, expected: edc },
+    ]
+}
+
+pub fn format_limits(audits: &[LimitAudit], tolerance: f32) -> String {
+    let mut out = String::from("Limits:\n");
+    for audit in audits {
+        let unit = audit.unit();
+        let mut line = format!("  {:<6}{:>7.1}{}", format!("{}:", audit.limit), audit.actual, unit);
+        if let Some(expected) = audit.expected {
+            line.push_str(&format!("  expected {:.1}{}", expected, unit));
+            if audit.matches(tolerance) {
+                line.push_str("  OK");
+            } else if expected != 0.0 {
+                line.push_str(&format!("  MISMATCH ({:+.1}%)", (audit.actual - expected) / expected.abs() * 100.0));
+            } else {
+                line.push_str("  MISMATCH");
+            }
+        }
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> PmTable {
+        PmTable { ppt_limit: 142.0, tdc_limit: 95.0, edc_limit: 140.0, ..Default::default() }
+    }
+
+    #[test]
+    fn test_limits_match_within_tolerance() {
+        let audits = audit_limits(&table(), [Some(142.0), Some(95.5), None]);
+        assert!(audits.iter().all(|a| a.matches(1.0)));
+        let text = format_limits(&audits, 1.0);
+        assert!(text.contains("PPT:    142.0W  expected 142.0W  OK"), "{}", text);
+        assert!(text.contains("EDC:    140.0A\n"), "{}", text);
+    }
+
+    #[test]
+    fn test_limits_mismatch() {
+        let audits = audit_limits(&table(), [Some(142.0), Some(100.0), Some(140.0)]);
+        assert!(!audits[1].matches(1.0));
+        // A wide enough tolerance accepts it
+        assert!(audits[1].matches(5.0));
+        assert!(format_limits(&audits, 1.0).contains("TDC:     95.0A  expected 100.0A  MISMATCH (-5.0%)"));
+    }
+}
@@ -0,0 +1,169 @@
+use crate::output::{csv_header, format_csv, format_json_line, FieldAliases, TimeFormat};
+use amd_smu_lib::PmTable;
+use clap::ValueEnum;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Rotated files kept next to the active log (`.1` is the newest)
+const MAX_ROTATED: u32 = 5;
+
+/// Record format for `--log-file`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LogFormat {
+    Csv,
+    Jsonl,
+}
+
+/// Appends one record per watch-mode sample to a file, rotating by size
+pub struct Logger {
+    path: PathBuf,
+    format: LogFormat,
+    aliases: FieldAliases,
+    /// Rotate once the file reaches this many bytes
+    rotate_bytes: Option<u64>,
+    /// Decimals for CSV values (full precision when None)
+    precision: Option<usize>,
+    time_format: TimeFormat,
+    writer: BufWriter<File>,
+    written: u64,
+}
+
+impl Logger {
+    pub fn open(
+        path: &Path,
+        format: LogFormat,
+        aliases: FieldAliases,
+        rotate_bytes: Option<u64>,
+    ) -> io::Result<Self> {
+        let (writer, written) = open_append(path)?;
+        Ok(Self { path: path.to_path_buf(), format, aliases, rotate_bytes, precision: None, time_format: TimeFormat::Unix, writer, written })
+    }
+
+    /// Round CSV values to `precision` decimals, as `--precision` does
+    pub fn with_precision(mut self, precision: Option<usize>) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    /// Timestamp records as `--time-format` does
+    pub fn with_time_format(mut self, time_format: TimeFormat) -> Self {
+        self.time_format = time_format;
+        self
+    }
+
+    /// Append a record for `table`, stamped with `time`, and flush it to disk
+    pub fn log(&mut self, table: &PmTable, time: SystemTime) -> io::Result<()> {
+        if self.rotate_bytes.is_some_and(|limit| self.written >= limit) {
+            self.rotate()?;
+        }
+
+        let mut record = String::new();
+        match self.format {
+            LogFormat::Csv => {
+                // Every file, including freshly rotated ones, starts with a header
+                if self.written == 0 {
+                    record.push_str(&csv_header(table, self.time_format));
+                }
+                record.push_str(&format_csv(table, time, self.time_format, self.precision));
+            }
+            LogFormat::Jsonl => {
+                record.push_str(&format_json_line(table, time, self.time_format, &self.aliases));
+                record.push('\n');
+            }
+        }
+
+        self.writer.write_all(record.as_bytes())?;
+        self.writer.flush()?;
+        self.written += record.len() as u64;
+        Ok(())
+    }
+
+    /// Shift `log.N` to `log.N+1`, move the active file to `log.1` and start
+    /// a new one
+    fn rotate(&mut self) -> io::Result<()> {
+        self.writer.flush()?;
+        for n in (1..MAX_ROTATED).rev() {
+            let from = rotated_path(&self.path, n);
+            if from.exists() {
+                fs::rename(&from, rotated_path(&self.path, n + 1))?;
+            }
+        }
+        fs::rename(&self.path, rotated_path(&self.path, 1))?;
+
+        let (writer, written) = open_append(&self.path)?;
+        self.writer = writer;
+        self.written = written;
+        Ok(())
+    }
+}
+
+fn open_append(path: &Path) -> io::Result<(BufWriter<File>, u64)> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let written = file.metadata()?.len();
+    Ok((BufWriter::new(file), written))
+}
+
+fn rotated_path(path: &Path, n: u32) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", n));
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> PmTable {
+        PmTable { tctl: 65.5, core_temps: vec![60.0, 61.0], ..Default::default() }
+    }
+
+    fn at(millis: u64) -> SystemTime {
+        std::time::UNIX_EPOCH + std::time::Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn test_csv_log_writes_header_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sensors.csv");
+
+        let mut logger = Logger::open(&path, LogFormat::Csv, FieldAliases::new(), None).unwrap();
+        logger.log(&table(), at(1)).unwrap();
+        logger.log(&table(), at(2)).unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        assert_eq!(contents.lines().count(), 3);
+        assert!(contents.starts_with("timestamp,"));
+    }
+
+    #[test]
+    fn test_rotation_by_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sensors.jsonl");
+
+        // Tiny limit: every record after the first triggers a rotation
+        let mut logger = Logger::open(&path, LogFormat::Jsonl, FieldAliases::new(), Some(1)).unwrap();
+        for ts in 0..3 {
+            logger.log(&table(), at(ts)).unwrap();
+        }
+
+        let newest = fs::read_to_string(&path).unwrap();
+        assert!(newest.contains("\"timestamp\":2"));
+        assert!(fs::read_to_string(rotated_path(&path, 1)).unwrap().contains("\"timestamp\":1"));
+        assert!(fs::read_to_string(rotated_path(&path, 2)).unwrap().contains("\"timestamp\":0"));
+        assert!(!rotated_path(&path, 3).exists());
+    }
+
+    #[test]
+    fn test_rotated_csv_gets_header() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sensors.csv");
+
+        let mut logger = Logger::open(&path, LogFormat::Csv, FieldAliases::new(), Some(1)).unwrap();
+        logger.log(&table(), at(0)).unwrap();
+        logger.log(&table(), at(1)).unwrap();
+
+        assert!(fs::read_to_string(&path).unwrap().starts_with("timestamp,"));
+    }
+}
@@ -1,46 +1,69 @@
-mod output;
-
-use amd_smu_lib::SmuReader;
-use clap::Parser;
-use output::{format_json, format_text, OutputOptions};
-use std::time::Duration;
-
-#[derive(Parser, Debug)]
-#[command(name = "amd-smu-sensors")]
-#[command(about = "Read AMD Ryzen CPU sensors via ryzen_smu kernel module")]
-#[command(version)]
-pub struct Args {
-    /// Output in JSON format
-    #[arg(long)]
-    pub json: bool,
-
-    /// Watch mode: continuously update readings
-    #[arg(short, long)]
-    pub watch: bool,
-
-    /// Update interval for watch mode (e.g., "500ms", "1s")
-    #[arg(short, long, default_value = "1s", value_parser = parse_duration)]
-    pub interval: Duration,
+/// Set by `--quiet`; silences `notice!`
+static QUIET: AtomicBool = AtomicBool::new(false);
 
-    /// Show only temperature readings
-    #[arg(long)]
-    pub temps: bool,
-
-    /// Show only power readings
-    #[arg(long)]
-    pub power: bool,
+/// `eprintln!` for notices and non-fatal warnings, which `--quiet` drops;
+/// errors keep using `eprintln!`
+macro_rules! notice {
+    ($($arg:tt)*) => {
+        if !$crate::QUIET.load(std::sync::atomic::Ordering::Relaxed) {
+            eprintln!($($arg)*);
+        }
+    };
+}
 
-    /// Show only frequency readings
-    #[arg(long)]
-    pub freq: bool,
+mod adaptive;
+mod alert;
+mod args;
+mod baseline;
+mod bundle;
+mod check;
+mod debug;
+mod delta;
+mod http;
+#[cfg(target_os = "linux")]
+mod keys;
+mod latency;
+mod limits;
+mod logger;
+mod output;
+mod query;
+mod server;
+#[cfg(target_os = "linux")]
+mod trigger;
 
-    /// Launch TUI dashboard
-    #[arg(long)]
-    pub tui: bool,
-}
+use adaptive::AdaptiveInterval;
+use alert::{AlertThresholds, EXIT_ALERT};
+use baseline::Baseline;
+use amd_smu_lib::{
+    crc32, fan_speeds, pm_table_offsets, supported_pm_tables, with_retry, Codename, CoreTopology, FileSource, Limit, OutputSections, PmTable,
+    PmTableOffsets, PmTableSource, ReconnectingReader, RiskAcknowledged, Smoother, SmuError, SmuReader, TempUnit, ThresholdConfig, READ_RETRIES,
+    RETRY_DELAY, VersionTracker,
+};
+use args::{Args, LimitsArgs, Mode, ReadArgs, ServeArgs, SourceArgs};
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser};
+use logger::Logger;
+use output::{
+    check_aliases, csv_header, format_brief, format_csv, format_cooling, format_diff, format_influx, format_json, format_json_envelope, format_json_grouped, format_json_line, format_prometheus, format_sensors,
+    format_session_peaks, format_stats, format_summary, format_text, taken_at, ColorChoice, OutputFormat,
+    OutputOptions, SessionPeaks, StatsAccumulator, Versions,
+};
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
 
-fn parse_duration(s: &str) -> Result<Duration, String> {
-    humantime::parse_duration(s).map_err(|e| e.to_string())
+/// Resolve `--color`: auto mode stays plain under $NO_COLOR or when stdout
+/// is redirected
+fn use_ansi(choice: ColorChoice) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty()) && std::io::stdout().is_terminal()
+        }
+    }
 }
 
 fn main() {
@@ -51,35 +74,474 @@ fn main() {
         std::process::exit(1);
     }
 
-    let reader = match SmuReader::new() {
-        Ok(r) => r,
+    if args.list_supported {
+        print_supported();
+        return;
+    }
+
+    let mode = match args.mode() {
+        Ok(mode) => mode,
+        Err(e) => Args::command().error(ErrorKind::ArgumentConflict, e).exit(),
+    };
+    let Args { output, source: source_args, interval, alert_tctl, alert_power, baseline, baseline_tolerance, .. } = args;
+    QUIET.store(output.quiet, Ordering::Relaxed);
+
+    if let Mode::Check = mode
+        && source_args.all_sockets
+    {
+        let sockets = check::check_all(source_args.sysfs_path.as_deref(), source_args.codename, !output.no_pbo_detect);
+        if !output.quiet {
+            match serde_json::to_string_pretty(&sockets) {
+                Ok(json) => println!("{}", json),
+                Err(e) => eprintln!("Error serializing health report: {}", e),
+            }
+        }
+        std::process::exit(check::exit_code_all(&sockets));
+    }
+    if let Mode::Check = mode {
+        let status = check::check(source_args.sysfs_path.as_deref(), source_args.codename, !output.no_pbo_detect);
+        if !output.quiet {
+            match serde_json::to_string_pretty(&status) {
+                Ok(json) => println!("{}", json),
+                Err(e) => eprintln!("Error serializing health report: {}", e),
+            }
+        }
+        std::process::exit(status.exit_code());
+    }
+
+    if let Mode::Dump(dump) = &mode {
+        match &dump.bundle {
+            Some(dir) => run_bundle(source_args.sysfs_path.as_deref(), dir),
+            None => run_dump(source_args.sysfs_path.as_deref(), dump.output.as_deref()),
+        }
+        return;
+    }
+
+    if let Mode::Profile(profile) = &mode {
+        run_profile(source_args.sysfs_path.as_deref(), &reader_config(&source_args), profile.duration);
+        return;
+    }
+
+    if let Mode::Limits(limits) = &mode {
+        set_limits(source_args.sysfs_path.as_deref(), limits);
+    }
+
+    let core_order = if output.physical_order {
+        match CoreTopology::read() {
+            Ok(topology) => Some(topology.physical_order(topology.cores.len())),
+            Err(e) => {
+                notice!("Warning: could not read CPU topology, using logical order: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let thresholds = match ThresholdConfig::load_or_default(output.config.as_deref()) {
+        Ok(thresholds) => thresholds,
         Err(e) => {
             eprintln!("Error: {}", e);
             std::process::exit(1);
         }
     };
 
-    let smu_version = reader.smu_version().unwrap_or_else(|_| "Unknown".to_string());
-    let opts = OutputOptions {
-        temps_only: args.temps,
-        power_only: args.power,
-        freq_only: args.freq,
+    let json_aliases = match check_aliases(output.json_aliases) {
+        Ok(aliases) => aliases,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let mut opts = OutputOptions {
+        sections: OutputSections::from_filters(output.temps, output.power, output.freq),
+        current_calibration: output.current_calibration,
+        power_calibration: output.power_calibration,
+        core_order,
+        show_parked: output.show_parked,
+        json_aliases,
+        temp_unit: if output.fahrenheit { TempUnit::Fahrenheit } else { TempUnit::Celsius },
+        json_envelope: output.json_envelope.then(Versions::default),
+        json_grouped: output.json_grouped,
+        socket: source_args.socket.unwrap_or_default(),
+        fields: output.fields,
+        cooling: source_args.from_file.is_empty(),
+        thresholds,
+        ccd_relative: output.ccd_relative,
+        brief_fields: output.brief_fields,
+        precision: output.precision.map(usize::from),
+        header: !output.quiet,
+        pbo: !output.no_pbo_detect,
+        time_format: output.time_format,
+        ansi: use_ansi(if output.no_color { ColorChoice::Never } else { output.color }),
+    };
+
+    let format = if output.json {
+        OutputFormat::Json
+    } else if output.json_lines {
+        OutputFormat::JsonLines
+    } else if output.csv {
+        OutputFormat::Csv
+    } else if output.prometheus {
+        OutputFormat::Prometheus
+    } else if output.influx {
+        OutputFormat::Influx
+    } else if output.sensors_format {
+        OutputFormat::Sensors
+    } else if output.summary {
+        OutputFormat::Summary
+    } else if output.brief {
+        OutputFormat::Brief
+    } else {
+        OutputFormat::Text
+    };
+
+    let alerts = AlertThresholds { tctl: alert_tctl, power: alert_power };
+    let baseline = baseline.map(|path| match Baseline::load(&path, baseline_tolerance) {
+        Ok(baseline) => baseline,
+        Err(e) => {
+            eprintln!("Error loading baseline {}: {}", path.display(), e);
+            std::process::exit(1);
+        }
+    });
+
+    let config = reader_config(&source_args);
+    let SourceArgs { verbose, debug, sysfs_path, socket, all_sockets, from_file, pm_version, codename, .. } = source_args;
+    let file_source = |path: &PathBuf| {
+        let version = pm_version.unwrap_or_default();
+        let mut file = FileSource::new(path, version);
+        if let Some(codename) = codename {
+            file = file.with_codename(codename);
+        }
+        if let Some(cores) = config.cores {
+            file = file.with_core_count(cores);
+        }
+        match config.file_offsets(version) {
+            Some(offsets) => file.with_offsets(offsets),
+            None => file,
+        }
+    };
+
+    // Offline mode never touches the driver
+    let mut source = if let Some(path) = from_file.first() {
+        let source: Box<dyn PmTableSource> = Box::new(file_source(path));
+        source
+    } else if socket.is_some() || all_sockets {
+        let discovered = match &sysfs_path {
+            Some(path) => SmuReader::discover_from(path),
+            None => SmuReader::discover_all(),
+        };
+        let mut readers = match discovered {
+            Ok(readers) => readers,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        };
+        if all_sockets {
+            let mut sources: Vec<_> = readers.into_iter().map(|r| driver_source(r, &config, verbose, debug, &mut opts))
+                .collect();
+            run_all_sockets(&mut sources, &opts, format, alerts);
+            return;
+        }
+        let socket = socket.unwrap_or_default();
+        if socket >= readers.len() {
+            eprintln!("Error: socket {} not found ({} detected)", socket, readers.len());
+            std::process::exit(1);
+        }
+        driver_source(readers.swap_remove(socket), &config, verbose, debug, &mut opts)
+    } else {
+        match SmuReader::with_path_or_env(sysfs_path.as_ref()) {
+            Ok(r) => driver_source(r, &config, verbose, debug, &mut opts),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+    };
+
+    let smu_version = source.smu_version();
+
+    match mode {
+        Mode::Read(read) if read.diff => {
+            let mut second = from_file.get(1).map(file_source);
+            run_diff(&mut source, second.as_mut(), interval);
+        }
+        Mode::Read(ReadArgs { query: Some(field), .. }) => match source.read() {
+            Ok(table) => {
+                print_warnings(&table);
+                print_query(&table, &field);
+            }
+            Err(e) => {
+                eprintln!("Error reading PM table: {}", e);
+                std::process::exit(1);
+            }
+        },
+        Mode::Read(_) => run_single_shot(&mut source, &smu_version, &opts, format, alerts, baseline.as_ref()),
+        Mode::Limits(limits) => run_limits(&mut source, &limits),
+        Mode::Serve(ServeArgs { http: Some(addr), .. }) => {
+            if let Err(e) = http::serve_http(&addr, &mut source, &opts.json_aliases) {
+                eprintln!("Error serving HTTP on {}: {}", addr, e);
+                std::process::exit(1);
+            }
+        }
+        Mode::Serve(ServeArgs { unix: Some(path), .. }) => {
+            let running = shutdown_flag();
+            if let Err(e) = server::serve_samples(&path, &mut source, interval, &opts.json_aliases, opts.time_format, &running) {
+                eprintln!("Error serving on {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        }
+        Mode::Watch(watch) => {
+            let logger = match &watch.log_file {
+                Some(path) => {
+                    let aliases = opts.json_aliases.clone();
+                    match Logger::open(path, watch.log_format, aliases, watch.log_rotate) {
+                        Ok(logger) => Some(logger.with_precision(opts.precision).with_time_format(opts.time_format)),
+                        Err(e) => {
+                            eprintln!("Error opening log file {}: {}", path.display(), e);
+                            std::process::exit(1);
+                        }
+                    }
+                }
+                None => None,
+            };
+            let watch = WatchOptions {
+                interval,
+                adaptive: watch.adaptive.then(|| AdaptiveInterval::new(watch.min_interval, watch.max_interval)),
+                stats: watch.stats.then(StatsAccumulator::default),
+                smoother: watch.smooth.and_then(Smoother::for_factor),
+                alerts,
+                baseline,
+                logger,
+                count: watch.count,
+                delta_only: watch.delta_only,
+                // Escape codes would end up in the file or pipe
+                clear: !watch.no_clear && std::io::stdout().is_terminal(),
+                #[cfg(target_os = "linux")]
+                trigger: watch.on_signal.then(signal_trigger),
+            };
+            run_watch_mode(&mut source, &smu_version, &mut opts, format, watch);
+        }
+        // Serve always has one target (clap requires it); dump, check and profile ran above
+        Mode::Serve(_) | Mode::Dump(_) | Mode::Check | Mode::Profile(_) => unreachable!(),
+    }
+}
+
+fn render(table: &PmTable, smu_version: &str, opts: &OutputOptions, format: OutputFormat) -> String {
+    match format {
+        OutputFormat::Text if opts.cooling => {
+            let fans = fan_speeds().unwrap_or_default();
+            format_text(table, smu_version, opts) + &format_cooling(&fans, opts)
+        }
+        OutputFormat::Text => format_text(table, smu_version, opts),
+        OutputFormat::Json => match &opts.json_envelope {
+            Some(versions) => format!("{}\n", format_json_envelope(table, versions, &opts.json_aliases)),
+            None => format!("{}\n", json_document(table, opts)),
+        },
+        OutputFormat::JsonLines => format!(
+            "{}\n",
+            format_json_line(table, taken_at(table), opts.time_format, &opts.json_aliases)
+        ),
+        OutputFormat::Csv => format_csv(table, taken_at(table), opts.time_format, opts.precision),
+        OutputFormat::Prometheus => format_prometheus(table),
+        OutputFormat::Influx => format_influx(table, opts.time_format.influx(taken_at(table)), opts.socket),
+        OutputFormat::Sensors => format_sensors(table),
+        OutputFormat::Summary => format_summary(table, opts),
+        OutputFormat::Brief => format_brief(table, &opts.brief_fields, opts),
+    }
+}
+
+/// A table as a `--json` document, flat or grouped by CCD
+fn json_document(table: &PmTable, opts: &OutputOptions) -> String {
+    if opts.json_grouped {
+        format_json_grouped(table, &opts.json_aliases)
+    } else {
+        format_json(table, &opts.json_aliases)
+    }
+}
+
+/// Offsets to parse tables with instead of their own version's layout
+#[derive(Debug, Clone)]
+enum LayoutOverride {
+    /// `--force-offsets`: another supported version's layout
+    Version(u32),
+    /// `--offsets`: loaded from a file
+    Custom(Box<PmTableOffsets>),
+}
+
+/// How tables are parsed: `--codename`, `--cores`, `--force-offsets` or
+/// `--offsets`, `--power-scale` and `--current-scale`
+#[derive(Debug, Clone)]
+struct ReaderConfig {
+    codename: Option<Codename>,
+    cores: Option<usize>,
+    layout: Option<LayoutOverride>,
+    power_scale: Option<f32>,
+    current_scale: Option<f32>,
+}
+
+impl ReaderConfig {
+    /// Offsets for a `--from-file` dump of `version`, when the options
+    /// change them
+    fn file_offsets(&self, version: u32) -> Option<PmTableOffsets> {
+        let mut offsets = match &self.layout {
+            Some(LayoutOverride::Custom(offsets)) => **offsets,
+            _ if self.power_scale.is_some() || self.current_scale.is_some() => pm_table_offsets(version)?,
+            _ => return None,
+        };
+        offsets.power_scale = self.power_scale.unwrap_or(offsets.power_scale);
+        offsets.current_scale = self.current_scale.unwrap_or(offsets.current_scale);
+        Some(offsets)
+    }
+}
+
+/// Reader options from the command line, exiting if the `--offsets` file
+/// can't be loaded
+fn reader_config(args: &SourceArgs) -> ReaderConfig {
+    let layout = match &args.offsets {
+        Some(path) => match PmTableOffsets::load(path) {
+            Ok(offsets) => Some(LayoutOverride::Custom(Box::new(offsets))),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        },
+        None => args.force_offsets.map(LayoutOverride::Version),
+    };
+    ReaderConfig {
+        codename: args.codename,
+        cores: args.cores,
+        layout,
+        power_scale: args.power_scale,
+        current_scale: args.current_scale,
+    }
+}
+
+/// Apply the `ReaderConfig` options to a reader, exiting on a core count
+/// or layout version that isn't supported
+fn configure_reader(reader: SmuReader, config: &ReaderConfig) -> SmuReader {
+    let reader = match config.codename {
+        Some(codename) => reader.with_codename(codename),
+        None => reader,
+    };
+    let reader = match config.power_scale {
+        Some(scale) => reader.with_power_scale(scale),
+        None => reader,
+    };
+    let reader = match config.current_scale {
+        Some(scale) => reader.with_current_scale(scale),
+        None => reader,
+    };
+    let reader = match config.cores {
+        Some(cores) => reader.with_core_count(cores),
+        None => Ok(reader),
+    };
+    let reader = match (reader, &config.layout) {
+        (Ok(reader), Some(LayoutOverride::Version(version))) => reader.with_forced_layout(*version),
+        (Ok(reader), Some(LayoutOverride::Custom(offsets))) => Ok(reader.with_offsets(**offsets)),
+        (reader, _) => reader,
     };
+    match reader {
+        Ok(reader) => reader,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
 
-    if args.watch {
-        run_watch_mode(&reader, &smu_version, &opts, args.json, args.interval);
+/// Source for a driver instance, applying the `ReaderConfig` options and
+/// logging raw table checksums with `--verbose` and the offset breakdown
+/// with `--debug`; fills in the `--json-envelope` versions as a side effect
+fn driver_source(
+    reader: SmuReader,
+    config: &ReaderConfig,
+    verbose: bool,
+    debug: bool,
+    opts: &mut OutputOptions,
+) -> Box<dyn PmTableSource> {
+    let reader = configure_reader(reader, config);
+    if opts.json_envelope.is_some() {
+        opts.json_envelope = Some(Versions {
+            smu_version: reader.smu_version().ok().map(|v| v.trim().to_string()),
+            driver_version: reader.driver_version().ok().map(|v| v.trim().to_string()),
+        });
+    }
+    // Watch and server modes ride out a driver reload
+    let reader = ReconnectingReader::new(reader);
+    if verbose || debug {
+        Box::new(VerboseReader { reader, checksums: verbose, debug, layout: config.layout.clone() })
     } else {
-        run_single_shot(&reader, &smu_version, &opts, args.json);
+        Box::new(reader)
+    }
+}
+
+/// Reader that reports the size and CRC32 of every raw table it reads
+/// (`--verbose`) and breaks the first one down by offset (`--debug`)
+struct VerboseReader {
+    reader: ReconnectingReader,
+    checksums: bool,
+    /// Cleared once the breakdown is printed
+    debug: bool,
+    /// Layout the tables are parsed with, when not their own version's
+    layout: Option<LayoutOverride>,
+}
+
+impl PmTableSource for VerboseReader {
+    fn read(&mut self) -> amd_smu_lib::Result<PmTable> {
+        let (table, raw) = self.reader.read_with_raw()?;
+        if self.checksums {
+            eprintln!("PM table: {} bytes, CRC32 {:#010x}", raw.len(), crc32(&raw));
+        }
+        if self.debug {
+            let (version, custom) = match &self.layout {
+                Some(LayoutOverride::Version(version)) => (*version, None),
+                Some(LayoutOverride::Custom(offsets)) => (table.version, Some(&**offsets)),
+                None => (table.version, None),
+            };
+            eprint!("{}", debug::format_offset_report(&table, &raw, version, custom));
+            self.debug = false;
+        }
+        Ok(table)
+    }
+
+    fn smu_version(&self) -> String {
+        PmTableSource::smu_version(&self.reader)
     }
 }
 
-fn run_single_shot(reader: &SmuReader, smu_version: &str, opts: &OutputOptions, json: bool) {
-    match reader.read_pm_table() {
+fn run_single_shot(
+    source: &mut impl PmTableSource,
+    smu_version: &str,
+    opts: &OutputOptions,
+    format: OutputFormat,
+    alerts: AlertThresholds,
+    baseline: Option<&Baseline>,
+) {
+    match source.read() {
         Ok(table) => {
-            if json {
-                println!("{}", format_json(&table));
-            } else {
-                print!("{}", format_text(&table, smu_version, opts));
+            print_warnings(&table);
+            if format == OutputFormat::Csv {
+                print!("{}", csv_header(&table, opts.time_format));
+            }
+            print!("{}", render(&table, smu_version, opts, format));
+
+            // Report everything before picking the exit code
+            let breached = alerts.check(&table, opts.power_calibration);
+            for alert in &breached {
+                notice!("Warning: {}", alert);
+            }
+            let deviations = baseline.map(|b| b.deviations(&table)).unwrap_or_default();
+            for deviation in &deviations {
+                notice!("Warning: {}", deviation);
+            }
+            if !breached.is_empty() {
+                std::process::exit(EXIT_ALERT);
+            }
+            if !deviations.is_empty() {
+                std::process::exit(baseline::exit_code(&deviations));
             }
         }
         Err(e) => {
@@ -89,30 +551,461 @@ fn run_single_shot(reader: &SmuReader, smu_version: &str, opts: &OutputOptions,
     }
 }
 
-fn run_watch_mode(
-    reader: &SmuReader,
-    smu_version: &str,
+/// Print the package limits, exiting with `EXIT_MISMATCH` if any differs
+/// from its expected value
+fn run_limits(source: &mut impl PmTableSource, args: &LimitsArgs) {
+    let table = match source.read() {
+        Ok(table) => table,
+        Err(e) => {
+            eprintln!("Error reading PM table: {}", e);
+            std::process::exit(1);
+        }
+    };
+    print_warnings(&table);
+    let audits = limits::audit_limits(&table, [args.expect_ppt, args.expect_tdc, args.expect_edc]);
+    print!("{}", limits::format_limits(&audits, args.tolerance));
+    if !audits.iter().all(|audit| audit.matches(args.tolerance)) {
+        std::process::exit(limits::EXIT_MISMATCH);
+    }
+}
+
+/// Write the `--set-*` limits, exiting at the first that fails
+fn set_limits(sysfs_path: Option<&Path>, args: &LimitsArgs) {
+    if !args.sets_limits() {
+        return;
+    }
+    // No --codename here: the write uses the driver's codename
+    let reader = match SmuReader::with_path_or_env(sysfs_path) {
+        Ok(reader) => reader,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+    // clap only lets --set-* through with --i-understand-the-risks
+    let ack = RiskAcknowledged::i_understand_the_risks();
+    type Setter = fn(&SmuReader, f32, RiskAcknowledged) -> amd_smu_lib::Result<()>;
+    let setters: [(Limit, Option<f32>, Setter); 3] = [
+        (Limit::Ppt, args.set_ppt, SmuReader::set_ppt_limit),
+        (Limit::Tdc, args.set_tdc, SmuReader::set_tdc_limit),
+        (Limit::Edc, args.set_edc, SmuReader::set_edc_limit),
+    ];
+    for (limit, value, set) in setters {
+        let Some(value) = value else { continue };
+        if let Err(e) = set(&reader, value, ack) {
+            eprintln!("Error setting {} limit: {}", limit, e);
+            std::process::exit(1);
+        }
+        notice!("Set {} limit to {}", limit, value);
+    }
+}
+
+/// Compare a reading from `source` with one from `second`, or with another
+/// from `source` taken `interval` later
+fn run_diff(source: &mut impl PmTableSource, second: Option<&mut FileSource>, interval: Duration) {
+    let before = source.read();
+    let after = match second {
+        Some(second) => second.read(),
+        None => {
+            std::thread::sleep(interval);
+            source.read()
+        }
+    };
+    match (before, after) {
+        (Ok(before), Ok(after)) => {
+            print_warnings(&before);
+            print_warnings(&after);
+            print!("{}", format_diff(&before.diff(&after)));
+        }
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("Error reading PM table: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// One reading per socket: labelled sections, a JSON array in socket order,
+/// or one socket-tagged InfluxDB line each
+fn run_all_sockets(
+    sources: &mut [Box<dyn PmTableSource>],
     opts: &OutputOptions,
-    json: bool,
+    format: OutputFormat,
+    alerts: AlertThresholds,
+) {
+    let mut tables = Vec::with_capacity(sources.len());
+    for (socket, source) in sources.iter_mut().enumerate() {
+        match source.read() {
+            Ok(table) => {
+                print_warnings(&table);
+                tables.push(table);
+            }
+            Err(e) => {
+                eprintln!("Error reading PM table on socket {}: {}", socket, e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if format == OutputFormat::Json {
+        let docs: Vec<String> = tables.iter().map(|t| json_document(t, opts)).collect();
+        println!("[{}]", docs.join(",\n"));
+    } else if format == OutputFormat::Influx {
+        // One timestamp, tagged per socket
+        let timestamp = opts.time_format.influx(SystemTime::now());
+        for (socket, table) in tables.iter().enumerate() {
+            print!("{}", format_influx(table, timestamp, socket));
+        }
+    } else {
+        for (socket, (source, table)) in sources.iter().zip(&tables).enumerate() {
+            println!("=== Socket {} ===", socket);
+            print!("{}", render(table, &source.smu_version(), opts, format));
+        }
+    }
+
+    let mut breached = false;
+    for (socket, table) in tables.iter().enumerate() {
+        for alert in alerts.check(table, opts.power_calibration) {
+            notice!("Warning: socket {}: {}", socket, alert);
+            breached = true;
+        }
+    }
+    if breached {
+        std::process::exit(EXIT_ALERT);
+    }
+}
+
+struct WatchOptions {
     interval: Duration,
+    adaptive: Option<AdaptiveInterval>,
+    stats: Option<StatsAccumulator>,
+    smoother: Option<Smoother>,
+    alerts: AlertThresholds,
+    baseline: Option<Baseline>,
+    logger: Option<Logger>,
+    /// Stop after this many readings
+    count: Option<u64>,
+    /// Print only changed readings after the first
+    delta_only: bool,
+    /// Clear the screen before each full reading
+    clear: bool,
+    /// Sample once per SIGUSR1 instead of on a timer
+    #[cfg(target_os = "linux")]
+    trigger: Option<trigger::SignalTrigger>,
+}
+
+fn run_watch_mode(
+    source: &mut impl PmTableSource,
+    smu_version: &str,
+    opts: &mut OutputOptions,
+    format: OutputFormat,
+    mut watch: WatchOptions,
 ) {
-    loop {
-        // Clear screen
-        print!("\x1B[2J\x1B[1;1H");
+    let mut peaks = SessionPeaks::default();
+    let mut header_written = false;
+    let mut versions = VersionTracker::default();
+    // Kept on screen for the rest of the run once the version changes
+    let mut version_notice: Option<String> = None;
+    let mut drifted = false;
+    let mut readings: u64 = 0;
+    // Last reading shown, kept only for --delta-only
+    let mut previous: Option<PmTable> = None;
+
+    let running = shutdown_flag();
+    // Single-key commands on screen-refreshing output, when typed on a terminal
+    #[cfg(target_os = "linux")]
+    let keys = if format.is_streaming() || watch.trigger.is_some() || watch.delta_only { None } else { keys::KeyReader::spawn() };
 
-        match reader.read_pm_table() {
+    while running.load(Ordering::SeqCst) {
+        #[cfg(target_os = "linux")]
+        if let Some(trigger) = &watch.trigger
+            && !trigger.wait(&running)
+        {
+            break;
+        }
+
+        // Ride out brief SMU hiccups instead of flashing an error
+        match with_retry(READ_RETRIES, RETRY_DELAY, || source.read()) {
             Ok(table) => {
-                if json {
-                    println!("{}", format_json(&table));
+                readings += 1;
+                // After the first reading --delta-only appends like a stream
+                let full = previous.is_none();
+                // Clear screen, unless we're appending records to a stream;
+                // appended readings get a blank line between them instead
+                if !format.is_streaming() && full {
+                    if watch.clear {
+                        print!("{}", opts.clear_screen());
+                    } else if readings > 1 {
+                        println!();
+                    }
+                }
+                print_warnings(&table);
+                if let Some(previous) = versions.update(&table) {
+                    let notice = format!(
+                        "PM table version changed from {:#x} to {:#x} (firmware update or resume?); \
+                         readings now use the new version's layout",
+                        previous, table.version
+                    );
+                    if format.is_streaming() || !full {
+                        notice!("Warning: {}", notice);
+                    }
+                    version_notice = Some(notice);
+                }
+                if !format.is_streaming()
+                    && full
+                    && let Some(notice) = &version_notice
+                {
+                    notice!("Warning: {}", notice);
+                }
+                peaks.update(&table);
+                if let Some(adaptive) = watch.adaptive.as_mut() {
+                    adaptive.update(&table);
+                }
+                if let Some(stats) = watch.stats.as_mut() {
+                    stats.update(&table);
+                }
+                if let Some(logger) = watch.logger.as_mut()
+                    && let Err(e) = logger.log(&table, taken_at(&table))
+                {
+                    notice!("Warning: could not write log file: {}", e);
+                }
+                if format == OutputFormat::Csv && !header_written {
+                    print!("{}", csv_header(&table, opts.time_format));
+                    header_written = true;
+                }
+                // Only what people read is smoothed; records stay raw
+                let smoothed = watch.smoother.as_mut()
+                    .filter(|_| matches!(format, OutputFormat::Text | OutputFormat::Summary | OutputFormat::Brief))
+                    .map(|smoother| smoother.apply(&table));
+                let shown = smoothed.as_ref().unwrap_or(&table);
+                if let Some(previous) = &previous {
+                    print!("{}", delta::format_changes(previous, shown));
                 } else {
-                    print!("{}", format_text(&table, smu_version, opts));
+                    print!("{}", render(shown, smu_version, opts, format));
+                    if format == OutputFormat::Text {
+                        print!("{}", format_session_peaks(&peaks, opts));
+                    }
+                    if format == OutputFormat::Text
+                        && let Some(stats) = &watch.stats
+                    {
+                        print!("{}", format_stats(stats, opts));
+                    }
+                    #[cfg(target_os = "linux")]
+                    if keys.is_some() {
+                        println!("\n{}  (interval {}ms)", keys::FOOTER, watch.interval.as_millis());
+                    }
                 }
+                if watch.delta_only {
+                    previous = Some(shown.clone());
+                }
+                for deviation in watch.baseline.iter().flat_map(|b| b.deviations(&table)) {
+                    drifted = true;
+                    if format.is_streaming() {
+                        notice!("Warning: {}", deviation);
+                    } else {
+                        println!("DRIFT: {}", deviation);
+                    }
+                }
+                for alert in watch.alerts.check(&table, opts.power_calibration) {
+                    if format.is_streaming() {
+                        notice!("Warning: {}", alert);
+                    } else if opts.ansi {
+                        // Bold, blinking red so it stands out on a refreshing screen
+                        println!("\x1B[1;5;31mALERT: {}\x1B[0m", alert);
+                    } else {
+                        println!("ALERT: {}", alert);
+                    }
+                }
+            }
+            Err(e @ SmuError::ModuleNotLoaded(_)) => {
+                notice!("Driver unavailable ({}), reconnecting...", e);
             }
             Err(e) => {
+                // The last good reading stays on screen above this
                 eprintln!("Error reading PM table: {}", e);
             }
         }
+        if watch.count.is_some_and(|count| readings >= count) {
+            break;
+        }
+
+        #[cfg(target_os = "linux")]
+        if watch.trigger.is_some() {
+            continue;
+        }
+        let next = watch.adaptive.as_ref().map_or(watch.interval, AdaptiveInterval::current);
+        // A key redraws straight away, with the change applied
+        #[cfg(target_os = "linux")]
+        if let Some(keys) = &keys {
+            match keys.wait(&running, next) {
+                Some(keys::KeyCommand::Toggle(section)) => opts.sections.toggle(section),
+                Some(keys::KeyCommand::Quit) => break,
+                Some(command) => watch.interval = command.adjust_interval(watch.interval),
+                None => {}
+            }
+            continue;
+        }
+        sleep_while_running(&running, next);
+    }
+
+    // Undo any half-written styling (e.g. an alert interrupted mid-line)
+    // and make sure the cursor is visible again
+    if !format.is_streaming() {
+        if opts.ansi {
+            print!("\x1B[0m\x1B[?25h");
+        }
+        println!();
+    }
+
+    if let Some(stats) = &watch.stats {
+        // Keep streamed output machine-readable
+        if format.is_streaming() {
+            eprint!("{}", format_stats(stats, opts));
+        } else {
+            print!("{}", format_stats(stats, opts));
+        }
+    }
+    if drifted {
+        // exit() skips destructors, so hand the terminal back first
+        #[cfg(target_os = "linux")]
+        drop(keys);
+        std::process::exit(baseline::EXIT_DEVIATION);
+    }
+}
+
+/// Flag cleared by Ctrl-C, for loops that should finish cleanly
+fn shutdown_flag() -> Arc<AtomicBool> {
+    let running = Arc::new(AtomicBool::new(true));
+    let handler_flag = Arc::clone(&running);
+    if let Err(e) = ctrlc::set_handler(move || handler_flag.store(false, Ordering::SeqCst)) {
+        notice!("Warning: could not install Ctrl-C handler: {}", e);
+    }
+    running
+}
+
+/// SIGUSR1 handler for `--on-signal`, announcing the pid to signal
+#[cfg(target_os = "linux")]
+fn signal_trigger() -> trigger::SignalTrigger {
+    match trigger::SignalTrigger::install() {
+        Ok(trigger) => {
+            notice!("Waiting for SIGUSR1 (kill -USR1 {})", std::process::id());
+            trigger
+        }
+        Err(e) => {
+            eprintln!("Error installing SIGUSR1 handler: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Longest stretch the watch loop sleeps without checking for Ctrl-C
+const SHUTDOWN_POLL: Duration = Duration::from_millis(50);
+
+/// Sleep for `duration`, returning early once `running` is cleared
+fn sleep_while_running(running: &AtomicBool, duration: Duration) {
+    let deadline = Instant::now() + duration;
+    while running.load(Ordering::SeqCst) {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            break;
+        }
+        std::thread::sleep(remaining.min(SHUTDOWN_POLL));
+    }
+}
+
+/// Write the raw PM table blob for `--from-file`, noting its version on
+/// stderr
+fn run_dump(sysfs_path: Option<&Path>, output: Option<&Path>) {
+    let reader = match SmuReader::with_path_or_env(sysfs_path) {
+        Ok(reader) => reader,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+    if output.is_none() && std::io::stdout().is_terminal() {
+        eprintln!("Error: not writing a binary PM table to a terminal; pass -o PATH or redirect stdout");
+        std::process::exit(1);
+    }
+    let (data, version) = match reader.read_pm_table_raw().and_then(|data| Ok((data, reader.pm_table_version()?))) {
+        Ok(dump) => dump,
+        Err(e) => {
+            eprintln!("Error reading PM table: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let written = match output {
+        Some(path) => std::fs::write(path, &data),
+        None => std::io::stdout().write_all(&data),
+    };
+    if let Err(e) = written {
+        eprintln!("Error writing PM table: {}", e);
+        std::process::exit(1);
+    }
+    notice!("Dumped {} bytes (PM table version {:#x}); read it back with --from-file PATH --pm-version {:#x}",
+        data.len(), version, version);
+}
+
+/// Write a `dump --bundle` directory, listing what couldn't be captured
+fn run_bundle(sysfs_path: Option<&Path>, dir: &Path) {
+    let reader = match SmuReader::with_path_or_env(sysfs_path) {
+        Ok(reader) => reader,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let info = match bundle::write_bundle(&reader, dir, Path::new("/proc/cpuinfo")) {
+        Ok(info) => info,
+        Err(e) => {
+            eprintln!("Error writing bundle to {}: {}", dir.display(), e);
+            std::process::exit(1);
+        }
+    };
+    for error in &info.errors {
+        notice!("Warning: could not capture {}", error);
+    }
+    notice!("Wrote diagnostic bundle to {}; attach the whole directory to the bug report", dir.display());
+}
+
+/// Time back-to-back PM table reads (sysfs read plus parse) for `duration`
+fn run_profile(sysfs_path: Option<&Path>, config: &ReaderConfig, duration: Duration) {
+    let reader = match SmuReader::with_path_or_env(sysfs_path) {
+        Ok(reader) => configure_reader(reader, config),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+    // Load the cached metadata up front so it isn't part of the first read
+    if let Err(e) = reader.read_pm_table() {
+        eprintln!("Error reading PM table: {}", e);
+        std::process::exit(1);
+    }
+    notice!("Reading the PM table for {}...", humantime::format_duration(duration));
+    let stats = latency::profile(duration, || reader.read_pm_table());
+    print!("{}", latency::format_latency(&stats));
+}
+
+fn print_query(table: &PmTable, field: &str) {
+    match query::resolve(table, field) {
+        Ok(value) => println!("{}", value),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn print_supported() {
+    println!("Supported PM table versions:");
+    for (version, codenames) in supported_pm_tables() {
+        let names: Vec<&str> = codenames.iter().map(Codename::as_str).collect();
+        println!("  {:#08x}  {}", version, names.join(", "));
+    }
+}
 
-        std::thread::sleep(interval);
+fn print_warnings(table: &PmTable) {
+    for warning in &table.warnings {
+        notice!("Warning: {}", warning);
     }
 }
@@ -1,50 +1,332 @@
-use amd_smu_lib::PmTable;
+use amd_smu_lib::{EnergyMeter, FreqSource, Level, Limit, OutputSections, PmTable, PmTableDiff, TempUnit, Threshold, ThresholdConfig, ThrottleStatus, ValueDiff, ACTIVE_C0_PERCENT};
+use clap::ValueEnum;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Maps `PmTable` JSON field names to the keys they should be emitted as
+pub type FieldAliases = BTreeMap<String, String>;
+
+/// Shape of the rendered output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    /// One compact JSON object per line, with a timestamp
+    JsonLines,
+    /// Comma-separated rows, with a header before the first row
+    Csv,
+    /// Prometheus text exposition format
+    Prometheus,
+    /// InfluxDB line protocol, one line per sample
+    Influx,
+    Summary,
+    /// One short line per sample, for status bars
+    Brief,
+    /// lm-sensors' `sensors` chip block
+    Sensors,
+}
+
+impl OutputFormat {
+    /// Streaming formats append one record per sample and must never be
+    /// interleaved with screen-control sequences in watch mode
+    pub fn is_streaming(&self) -> bool {
+        matches!(self, Self::JsonLines | Self::Csv | Self::Influx | Self::Brief)
+    }
+}
+
+/// When to emit colors and other ANSI escapes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    /// Only on a terminal, and not when $NO_COLOR is set
+    Auto,
+    Always,
+    Never,
+}
+
+/// How CSV, JSON-lines and InfluxDB records are timestamped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum TimeFormat {
+    /// Milliseconds since the Unix epoch (nanoseconds for InfluxDB)
+    #[default]
+    Unix,
+    /// UTC with milliseconds, e.g. 2023-11-14T22:13:20.123Z
+    Iso8601,
+    /// No timestamp column or field
+    None,
+}
+
+impl TimeFormat {
+    /// `time` as a CSV column; None for `TimeFormat::None`
+    pub fn format(&self, time: SystemTime) -> Option<String> {
+        match self {
+            Self::Unix => Some(unix_millis(time).to_string()),
+            Self::Iso8601 => Some(humantime::format_rfc3339_millis(time).to_string()),
+            Self::None => None,
+        }
+    }
+
+    /// `time` as a JSON value: a number for Unix millis, else a string
+    pub fn json(&self, time: SystemTime) -> Option<Value> {
+        match self {
+            Self::Unix => Some(Value::from(unix_millis(time))),
+            _ => self.format(time).map(Value::from),
+        }
+    }
+
+    /// `time` as an InfluxDB timestamp; the line protocol only takes
+    /// numbers, so this is Unix nanoseconds unless the format is `None`
+    pub fn influx(&self, time: SystemTime) -> Option<u64> {
+        (*self != Self::None).then(|| unix_nanos(time))
+    }
+}
+
+/// One piece of the text output, for `--fields`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[value(rename_all = "snake_case")]
+pub enum TextField {
+    Tctl,
+    SocTemp,
+    CoreTemps,
+    /// Package power against the PPT limit
+    Ppt,
+    Tdc,
+    Edc,
+    SocPower,
+    CorePower,
+    Fclk,
+    Mclk,
+    /// Per-core clocks, effective clocks and C0 residency
+    CoreFreqs,
+    Vcore,
+    Vsoc,
+    CoreVoltages,
+}
+
+/// One reading on the `--brief` line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum BriefField {
+    /// Tctl, e.g. "65°C"
+    Tctl,
+    /// Package power (PPT), e.g. "88W"
+    Power,
+    /// Fastest core clock, e.g. "4.5GHz"
+    Freq,
+    /// Package activity (mean C0), e.g. "37%"
+    Activity,
+}
+
+/// Green/yellow/red ANSI color for `value` against warn/crit thresholds
+fn level_color(value: f32, threshold: Threshold) -> &'static str {
+    match threshold.level(value) {
+        Level::Critical => "\x1B[31m",
+        Level::Warning => "\x1B[33m",
+        Level::Normal => "\x1B[32m",
+    }
+}
 
 pub struct OutputOptions {
-    pub temps_only: bool,
-    pub power_only: bool,
-    pub freq_only: bool,
+    /// Sections of text output to show
+    pub sections: OutputSections,
+    /// Multiplier applied to reported currents (TDC/EDC), matching the
+    /// board's telemetry calibration setting
+    pub current_calibration: f32,
+    /// Multiplier applied to reported power values
+    pub power_calibration: f32,
+    /// Order to list per-core data in (logical index order when None)
+    pub core_order: Option<Vec<usize>>,
+    /// List parked cores explicitly instead of omitting them
+    pub show_parked: bool,
+    /// Renamed keys for JSON output
+    pub json_aliases: FieldAliases,
+    /// Unit for temperatures in human-readable output
+    pub temp_unit: TempUnit,
+    /// Wrap `--json` output in an envelope carrying these versions
+    pub json_envelope: Option<Versions>,
+    /// Allow ANSI escape sequences (screen clearing, colors)
+    pub ansi: bool,
+    /// Nest per-core `--json` data under its CCD
+    pub json_grouped: bool,
+    /// Socket index for the InfluxDB `socket` tag
+    pub socket: usize,
+    /// Only these readings in text output, in this order (all sections
+    /// when empty)
+    pub fields: Vec<TextField>,
+    /// Add hwmon fan speeds to text output (live readings only)
+    pub cooling: bool,
+    /// Where colors switch to yellow and red
+    pub thresholds: ThresholdConfig,
+    /// Label cores as CCD<n>/Core<m> instead of by flat index
+    pub ccd_relative: bool,
+    /// Readings on the `--brief` line
+    pub brief_fields: Vec<BriefField>,
+    /// Decimals for every reading in text output (each reading's own
+    /// default when None)
+    pub precision: Option<usize>,
+    /// Print the processor and SMU version lines above text output
+    pub header: bool,
+    /// Show the PBO guess in the Power section
+    pub pbo: bool,
+    /// Timestamps on CSV, JSON-lines and InfluxDB records
+    pub time_format: TimeFormat,
+}
+
+/// Firmware and driver versions for the JSON envelope (None when unknown,
+/// e.g. for offline dumps)
+#[derive(Debug, Clone, Default)]
+pub struct Versions {
+    pub smu_version: Option<String>,
+    pub driver_version: Option<String>,
 }
 
 impl OutputOptions {
-    pub fn show_all(&self) -> bool {
-        !self.temps_only && !self.power_only && !self.freq_only
+    /// Apply the current calibration factor to a value in amps
+    pub fn current(&self, amps: f32) -> f32 {
+        amps * self.current_calibration
+    }
+
+    /// Apply the power calibration factor to a value in watts
+    pub fn power(&self, watts: f32) -> f32 {
+        watts * self.power_calibration
+    }
+
+    /// `PmTable::limit_utilization` of the calibrated reading
+    pub fn limit_utilization(&self, table: &PmTable, limit: Limit) -> f32 {
+        let calibration = match limit {
+            Limit::Ppt => self.power_calibration,
+            Limit::Tdc | Limit::Edc => self.current_calibration,
+            Limit::Thermal => 1.0,
+        };
+        table.limit_utilization(limit) * calibration
+    }
+
+    /// `PmTable::binding_limit` by calibrated utilization
+    pub fn binding_limit(&self, table: &PmTable) -> (Limit, f32) {
+        Limit::ALL
+            .into_iter()
+            .map(|limit| (limit, self.limit_utilization(table, limit)))
+            .fold((Limit::Ppt, f32::MIN), |best, cur| if cur.1 > best.1 { cur } else { best })
+    }
+
+    /// Escape sequence that clears the screen in watch mode, empty when
+    /// ANSI output is off
+    pub fn clear_screen(&self) -> &'static str {
+        if self.ansi { "\x1B[2J\x1B[1;1H" } else { "" }
+    }
+
+    /// Color `text` by where `value` falls against the thresholds, if ANSI
+    /// output is on
+    pub fn paint(&self, text: String, value: f32, threshold: Threshold) -> String {
+        if self.ansi {
+            format!("{}{}\x1B[0m", level_color(value, threshold), text)
+        } else {
+            text
+        }
+    }
+
+    /// "Core  3", or "CCD0/Core3" with `ccd_relative`
+    pub fn core_label(&self, table: &PmTable, core: usize) -> String {
+        if self.ccd_relative {
+            table.ccd_core_label(core)
+        } else {
+            format!("Core {:2}", core)
+        }
+    }
+
+    /// Color `text` by how close `limit`'s calibrated reading is to its
+    /// ceiling, if ANSI output is on
+    pub fn paint_limit(&self, text: String, table: &PmTable, limit: Limit) -> String {
+        let threshold = match limit {
+            Limit::Ppt => self.thresholds.ppt,
+            Limit::Tdc => self.thresholds.tdc,
+            Limit::Edc => self.thresholds.edc,
+            Limit::Thermal => return self.paint(text, table.tctl, self.thresholds.temperature),
+        };
+        self.paint(text, self.limit_utilization(table, limit) * 100.0, threshold)
+    }
+
+    /// Convert a Celsius reading into the display unit
+    pub fn temp(&self, celsius: f32) -> f32 {
+        self.temp_unit.convert(celsius)
+    }
+
+    /// Decimals to print a reading with, given its usual precision
+    pub fn decimals(&self, default: usize) -> usize {
+        self.precision.unwrap_or(default)
+    }
+
+    /// Core indices below `count` in display order
+    pub fn core_indices(&self, count: usize) -> Vec<usize> {
+        match &self.core_order {
+            Some(order) => {
+                // Cores past the known order keep their logical position
+                let mut indices: Vec<usize> = order.iter().copied().filter(|&i| i < count).collect();
+                indices.extend(order.len()..count);
+                indices
+            }
+            None => (0..count).collect(),
+        }
     }
 }
 
 pub fn format_text(table: &PmTable, smu_version: &str, opts: &OutputOptions) -> String {
+    if !opts.fields.is_empty() {
+        return format_fields(table, &opts.fields, opts);
+    }
     let mut out = String::new();
+    let core_rows = |count: usize| {
+        if opts.sections.contains(OutputSections::CORES) { opts.core_indices(count) } else { Vec::new() }
+    };
 
     // Header
-    out.push_str(&format!("AMD Ryzen ({})\n", table.codename_str));
-    out.push_str(&format!("{} | PM Table v{:#x}\n", smu_version, table.version));
-    out.push('\n');
+    if opts.header {
+        out.push_str(&format!("AMD Ryzen ({})\n", table.codename_str));
+        out.push_str(&format!("{} | PM Table v{:#x}\n", smu_version, table.version));
+        let present = table.present_core_count();
+        if present > 0 {
+            out.push_str(&format!("Active cores: {}/{}\n", table.active_core_count(ACTIVE_C0_PERCENT), present));
+        }
+        out.push('\n');
+    }
 
     // Temperatures
-    if opts.show_all() || opts.temps_only {
+    if opts.sections.contains(OutputSections::TEMPS) {
         out.push_str("Temperatures:\n");
-        out.push_str(&format!("  Tctl:           {:+.1}°C  (limit: {:.1}°C)\n",
-            table.tctl, table.thm_limit));
-        out.push_str(&format!("  SoC:            {:+.1}°C\n", table.soc_temp));
-
-        // Group cores by CCD (8 cores per CCD)
-        let cores_per_ccd = 8;
-        let total_cores = table.core_temps.len();
-        let num_ccds = (total_cores + cores_per_ccd - 1) / cores_per_ccd;
-
-        for ccd in 0..num_ccds {
-            let start = ccd * cores_per_ccd;
-            let end = (start + cores_per_ccd).min(total_cores);
-            let ccd_temps: Vec<_> = table.core_temps[start..end].iter()
-                .filter(|t| **t > 0.0)
+        let unit = opts.temp_unit.symbol();
+        let tctl = opts.paint(format!("{:+.*}", opts.decimals(1), Reading(opts.temp(table.tctl), unit)), table.tctl, opts.thresholds.temperature);
+        out.push_str(&format!("  Tctl:           {}  (limit: {:.*})\n",
+            tctl, opts.decimals(1), Reading(opts.temp(table.thm_limit), unit)));
+        let headroom = table.thermal_headroom();
+        if !headroom.is_finite() {
+            out.push_str("  Headroom:       —\n");
+        } else {
+            out.push_str(&format!("  Headroom:       {:.*}{}\n", opts.decimals(1), opts.temp_unit.convert_delta(headroom), unit));
+        }
+        out.push_str(&format!("  SoC:            {:+.*}\n", opts.decimals(1), Reading(opts.temp(table.soc_temp), unit)));
+        let spread = table.core_temp_spread();
+        if spread > 0.0 {
+            let delta = |celsius| opts.temp_unit.convert_delta(celsius);
+            out.push_str(&format!("  Core spread:    {:.*}{}  (stddev {:.*}{})\n",
+                opts.decimals(1), delta(spread), unit, opts.decimals(1), delta(table.core_temp_stddev()), unit));
+        }
+
+        // Group cores by CCD
+        let order = core_rows(table.core_temps.len());
+
+        for ccd in 0..table.ccd_count() {
+            let range = table.cores_in_ccd(ccd);
+            let ccd_cores: Vec<usize> = order.iter()
+                .copied()
+                .filter(|&i| range.contains(&i) && table.core_temps[i] > 0.0)
                 .collect();
 
-            if !ccd_temps.is_empty() {
+            if !ccd_cores.is_empty() {
                 out.push_str(&format!("  CCD{}:\n", ccd));
-                for (i, temp) in table.core_temps[start..end].iter().enumerate() {
-                    if *temp > 0.0 {
-                        out.push_str(&format!("    Core {:2}:      {:+.1}°C\n", start + i, temp));
-                    }
+                for i in ccd_cores {
+                    let temp = table.core_temps[i];
+                    out.push_str(&format!("    {:<14}{}\n",
+                        format!("{}:", opts.core_label(table, i)), opts.paint(format!("{:+.*}{}", opts.decimals(1), opts.temp(temp), unit), temp, opts.thresholds.temperature)));
                 }
             }
         }
@@ -52,53 +334,1125 @@ pub fn format_text(table: &PmTable, smu_version: &str, opts: &OutputOptions) ->
     }
 
     // Power
-    if opts.show_all() || opts.power_only {
+    if opts.sections.contains(OutputSections::POWER) {
         out.push_str("Power:\n");
-        out.push_str(&format!("  Package:        {:.1}W / {:.1}W (PPT)\n",
-            table.ppt_value, table.ppt_limit));
-        out.push_str(&format!("  TDC:            {:.1}A / {:.1}A\n",
-            table.tdc_value, table.tdc_limit));
-        out.push_str(&format!("  EDC:            {:.1}A / {:.1}A\n",
-            table.edc_value, table.edc_limit));
-        out.push_str(&format!("  SoC:            {:.1}W\n", table.soc_power));
-
-        for (i, power) in table.core_power.iter().enumerate() {
-            if *power > 0.0 {
-                out.push_str(&format!("  Core {:2}:        {:.2}W\n", i, power));
+        out.push_str(&format!("  Profile:        {}\n", table.power_profile()));
+        if opts.pbo {
+            out.push_str(&format!("  PBO:            {} (guessed from limits)\n", table.pbo_status()));
+        }
+        out.push_str(&format!("  Limited by:     {}\n", table.throttle_status()));
+        let ppt = opts.paint_limit(format!("{:.*}", opts.decimals(1), Reading(opts.power(table.ppt_value), "W")), table, Limit::Ppt);
+        out.push_str(&format!("  Package:        {} / {:.*} (PPT)\n", ppt, opts.decimals(1), Reading(table.ppt_limit, "W")));
+        let tdc = opts.paint_limit(format!("{:.*}", opts.decimals(1), Reading(opts.current(table.tdc_value), "A")), table, Limit::Tdc);
+        out.push_str(&format!("  TDC:            {} / {:.*}\n", tdc, opts.decimals(1), Reading(table.tdc_limit, "A")));
+        let edc = opts.paint_limit(format!("{:.*}", opts.decimals(1), Reading(opts.current(table.edc_value), "A")), table, Limit::Edc);
+        out.push_str(&format!("  EDC:            {} / {:.*}\n", edc, opts.decimals(1), Reading(table.edc_limit, "A")));
+        out.push_str(&format!("  SoC:            {:.*}\n", opts.decimals(1), Reading(opts.power(table.soc_power), "W")));
+        if let Some(total) = table.total_power() {
+            out.push_str(&format!("  Total:          {:.*}\n", opts.decimals(1), Reading(opts.power(total), "W")));
+        }
+
+        for i in core_rows(table.core_power.len()) {
+            let power = table.core_power[i];
+            if power > 0.0 {
+                out.push_str(&format!("  {:<16}{:.*}\n", format!("{}:", opts.core_label(table, i)), opts.decimals(2), Reading(opts.power(power), "W")));
             }
         }
         out.push('\n');
     }
 
     // Frequencies
-    if opts.show_all() || opts.freq_only {
-        out.push_str("Frequencies:\n");
-        out.push_str(&format!("  FCLK:           {:.0} MHz\n", table.fclk));
-        out.push_str(&format!("  MCLK:           {:.0} MHz\n", table.mclk));
-
-        for (i, (freq, eff)) in table.core_freqs.iter()
-            .zip(table.core_freqs_eff.iter())
-            .enumerate()
-        {
-            if *freq > 0.0 {
+    if opts.sections.contains(OutputSections::FREQ) {
+        out.push_str(match table.freq_source {
+            FreqSource::PmTable => "Frequencies:\n",
+            FreqSource::Cpuinfo => "Frequencies (from /proc/cpuinfo):\n",
+            FreqSource::Unavailable => "Frequencies (per-core unavailable):\n",
+        });
+        out.push_str(&format!("  FCLK:           {:.*}\n", opts.decimals(0), Reading(table.fclk, " MHz")));
+        out.push_str(&format!("  MCLK:           {:.*}\n", opts.decimals(0), Reading(table.mclk, " MHz")));
+        if table.max_core_freq() > 0.0 {
+            out.push_str(&format!("  Avg:            {:.*} MHz  Max: {:.*} MHz\n",
+                opts.decimals(0), table.avg_core_freq(), opts.decimals(0), table.max_core_freq()));
+        }
+        let activity = table.package_c0();
+        if !activity.is_finite() {
+            out.push_str("  Activity:       —\n");
+        } else {
+            out.push_str(&format!("  Activity:       {:.*}%\n", opts.decimals(1), activity));
+        }
+        if table.efficiency() > 0.0 {
+            // Calibrated watts, like the Power section
+            out.push_str(&format!("  Efficiency:     {:.*}\n", opts.decimals(1), Reading(table.efficiency() / opts.power_calibration, " MHz/W")));
+        }
+
+        let freq_cores = table.core_freqs.len().min(table.core_freqs_eff.len());
+        for i in core_rows(freq_cores) {
+            let (freq, eff) = (table.core_freqs[i], table.core_freqs_eff[i]);
+            if opts.show_parked && table.is_core_parked(i) {
+                out.push_str(&format!("  {:<16}parked\n", format!("{}:", opts.core_label(table, i))));
+            } else if freq > 0.0 {
                 let c0 = table.core_c0.get(i).unwrap_or(&0.0);
-                out.push_str(&format!("  Core {:2}:        {:.0} MHz (eff: {:.0})  C0: {:.1}%\n",
-                    i, freq, eff, c0));
+                let stretch = Reading(table.core_freq_stretch(i), " MHz");
+                out.push_str(&format!("  {:<16}{:.*} (eff: {:.*})  C0: {:.*}  Stretch: {:.*}\n",
+                    format!("{}:", opts.core_label(table, i)), opts.decimals(0), Reading(freq, " MHz"), opts.decimals(0), Reading(eff, ""), opts.decimals(1), Reading(*c0, "%"), opts.decimals(0), stretch));
             }
         }
+        // Say so rather than leave the rows out as if every core were idle
+        let available = table.available_fields();
+        if !available.core_freqs && table.core_freqs.is_empty() {
+            out.push_str("  Per-core frequency: not supported on this CPU\n");
+        }
+        if !available.core_c0 && table.core_c0.is_empty() && !table.core_temps.is_empty() {
+            out.push_str("  Per-core C0:    not supported on this CPU\n");
+        }
+        out.push('\n');
+    }
+
+    // Per-CCD aggregates, for parts with more than one die
+    if opts.sections.intersects(OutputSections::TEMPS | OutputSections::POWER | OutputSections::FREQ) && table.ccd_count() > 1 {
+        out.push_str("Per-CCD Summary:\n");
+        let unit = opts.temp_unit.symbol();
+        // "—" for a CCD without per-core clocks rather than 0 MHz
+        let freq = |mhz: f32| Reading(if mhz > 0.0 { mhz } else { f32::NAN }, " MHz");
+        for ccd in 0..table.ccd_count() {
+            if !table.core_temps[table.cores_in_ccd(ccd)].iter().any(|t| *t > 0.0) {
+                continue;
+            }
+            out.push_str(&format!("  {:<16}Temp: {:+.*}  Freq: {:.*} (peak {:.*})  Power: {:.*}\n",
+                format!("CCD{}:", ccd),
+                opts.decimals(1), Reading(opts.temp(table.ccd_avg_temp(ccd)), unit),
+                opts.decimals(0), freq(table.ccd_avg_freq(ccd)),
+                opts.decimals(0), freq(table.ccd_max_freq(ccd)),
+                opts.decimals(2), Reading(opts.power(table.ccd_power(ccd)), "W")));
+        }
         out.push('\n');
     }
 
     // Voltages
-    if opts.show_all() {
+    if opts.sections.contains(OutputSections::VOLTAGES) {
         out.push_str("Voltages:\n");
-        out.push_str(&format!("  VCore:          {:.3}V\n", table.core_voltage));
-        out.push_str(&format!("  VSoC:           {:.3}V\n", table.soc_voltage));
+        out.push_str(&format!("  VCore:          {:.*}\n", opts.decimals(3), Reading(table.core_voltage, "V")));
+        out.push_str(&format!("  VSoC:           {:.*}\n", opts.decimals(3), Reading(table.soc_voltage, "V")));
+        for i in core_rows(table.core_voltages.len()) {
+            let voltage = table.core_voltages[i];
+            if voltage > 0.0 {
+                out.push_str(&format!("  {:<16}{:.*}\n", format!("{}:", opts.core_label(table, i)), opts.decimals(3), Reading(voltage, "V")));
+            }
+        }
+    }
+
+    // Per-core fit, on layouts that have it
+    if opts.sections.contains(OutputSections::VOLTAGES | OutputSections::CORES) && table.core_fit.iter().any(|fit| *fit > 0.0) {
+        out.push_str("\nCore Fit:\n");
+        for i in core_rows(table.core_fit.len()) {
+            if table.core_fit[i] > 0.0 {
+                out.push_str(&format!("  {:<16}{:.*}\n", format!("{}:", opts.core_label(table, i)), opts.decimals(1), Reading(table.core_fit[i], "")));
+            }
+        }
+    }
+
+    out
+}
+
+/// A reading in text output: the number followed by `unit`, or "—" when it
+/// isn't finite (e.g. NaN from a garbage offset)
+///
+/// Precision, sign and width flags apply to the number.
+struct Reading<'a>(f32, &'a str);
+
+impl fmt::Display for Reading<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_finite() {
+            fmt::Display::fmt(&self.0, f)?;
+            f.write_str(self.1)
+        } else {
+            // Not `pad`, whose precision would cut the dash off
+            write!(f, "{:>1$}", "—", f.width().unwrap_or(0))
+        }
+    }
+}
+
+/// "Cooling" section for text output, empty without fans or when the
+/// section filters leave out temperatures
+pub fn format_cooling(fans: &[(String, u32)], opts: &OutputOptions) -> String {
+    if fans.is_empty() || !opts.fields.is_empty() || !opts.sections.contains(OutputSections::TEMPS) {
+        return String::new();
+    }
+    let mut out = String::from("\nCooling:\n");
+    for (label, rpm) in fans {
+        out.push_str(&format!("  {:<15} {} RPM\n", format!("{}:", label), rpm));
+    }
+    out
+}
+
+/// Just the `--fields` readings, one labelled line each, with no header
+fn format_fields(table: &PmTable, fields: &[TextField], opts: &OutputOptions) -> String {
+    let mut out = String::new();
+    let unit = opts.temp_unit.symbol();
+    let mut line = |label: String, value: String| out.push_str(&format!("{:<15} {}\n", label + ":", value));
+    let temp = |celsius: f32| opts.paint(format!("{:+.*}", opts.decimals(1), Reading(opts.temp(celsius), unit)), celsius, opts.thresholds.temperature);
+
+    for field in fields {
+        match field {
+            TextField::Tctl => line("Tctl".into(), temp(table.tctl)),
+            TextField::SocTemp => line("SoC temp".into(), temp(table.soc_temp)),
+            TextField::CoreTemps => {
+                for i in opts.core_indices(table.core_temps.len()) {
+                    line(format!("{} temp", opts.core_label(table, i)), temp(table.core_temps[i]));
+                }
+            }
+            TextField::Ppt => line("Package".into(), format!("{} / {:.*} (PPT)",
+                opts.paint_limit(format!("{:.*}", opts.decimals(1), Reading(opts.power(table.ppt_value), "W")), table, Limit::Ppt), opts.decimals(1), Reading(table.ppt_limit, "W"))),
+            TextField::Tdc => line("TDC".into(), format!("{} / {:.*}",
+                opts.paint_limit(format!("{:.*}", opts.decimals(1), Reading(opts.current(table.tdc_value), "A")), table, Limit::Tdc), opts.decimals(1), Reading(table.tdc_limit, "A"))),
+            TextField::Edc => line("EDC".into(), format!("{} / {:.*}",
+                opts.paint_limit(format!("{:.*}", opts.decimals(1), Reading(opts.current(table.edc_value), "A")), table, Limit::Edc), opts.decimals(1), Reading(table.edc_limit, "A"))),
+            TextField::SocPower => line("SoC power".into(), format!("{:.*}", opts.decimals(1), Reading(opts.power(table.soc_power), "W"))),
+            TextField::CorePower => {
+                for i in opts.core_indices(table.core_power.len()) {
+                    line(format!("{} power", opts.core_label(table, i)), format!("{:.*}", opts.decimals(2), Reading(opts.power(table.core_power[i]), "W")));
+                }
+            }
+            TextField::Fclk => line("FCLK".into(), format!("{:.*}", opts.decimals(0), Reading(table.fclk, " MHz"))),
+            TextField::Mclk => line("MCLK".into(), format!("{:.*}", opts.decimals(0), Reading(table.mclk, " MHz"))),
+            TextField::CoreFreqs => {
+                let freq_cores = table.core_freqs.len().min(table.core_freqs_eff.len());
+                for i in opts.core_indices(freq_cores) {
+                    let c0 = table.core_c0.get(i).unwrap_or(&0.0);
+                    line(format!("{} freq", opts.core_label(table, i)), format!("{:.*} (eff: {:.*})  C0: {:.*}",
+                        opts.decimals(0), Reading(table.core_freqs[i], " MHz"), opts.decimals(0), Reading(table.core_freqs_eff[i], ""), opts.decimals(1), Reading(*c0, "%")));
+                }
+            }
+            TextField::Vcore => line("VCore".into(), format!("{:.*}", opts.decimals(3), Reading(table.core_voltage, "V"))),
+            TextField::Vsoc => line("VSoC".into(), format!("{:.*}", opts.decimals(3), Reading(table.soc_voltage, "V"))),
+            TextField::CoreVoltages => {
+                for i in opts.core_indices(table.core_voltages.len()) {
+                    line(format!("{} volt", opts.core_label(table, i)), format!("{:.*}", opts.decimals(3), Reading(table.core_voltages[i], "V")));
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Short "how's my CPU right now" view with only the key numbers
+pub fn format_summary(table: &PmTable, opts: &OutputOptions) -> String {
+    let mut out = String::new();
+    let ppt_value = opts.power(table.ppt_value);
+    let ppt_pct = opts.limit_utilization(table, Limit::Ppt) * 100.0;
+    let (limit, utilization) = opts.binding_limit(table);
+
+    let unit = opts.temp_unit.symbol();
+    out.push_str(&format!("Tctl:           {:.1}{} (hottest core {:.1}{})\n",
+        opts.temp(table.tctl), unit, opts.temp(table.max_core_temp()), unit));
+    out.push_str(&format!("Package:        {:.1}W / {:.1}W ({:.0}%)\n",
+        ppt_value, table.ppt_limit, ppt_pct));
+    out.push_str(&format!("Max core freq:  {:.0} MHz\n", table.max_core_freq()));
+    out.push_str(&format!("Profile:        {}\n", table.power_profile()));
+    out.push_str(&format!("Limiter:        {} ({:.0}%)\n", limit, utilization * 100.0));
+    out.push_str(&format!("Throttling:     {}\n", match table.throttle_status() {
+        ThrottleStatus::Unconstrained => "no".to_string(),
+        ThrottleStatus::Limited(limit) => format!("yes ({})", limit),
+    }));
+    out
+}
+
+/// `fields` on one space-separated line, e.g. "65°C 88W 4.5GHz"; readings
+/// the table doesn't have show as "-"
+pub fn format_brief(table: &PmTable, fields: &[BriefField], opts: &OutputOptions) -> String {
+    let values: Vec<String> = fields
+        .iter()
+        .map(|field| match field {
+            BriefField::Tctl => format!("{:.0}{}", opts.temp(table.tctl), opts.temp_unit.symbol()),
+            BriefField::Power => format!("{:.0}W", opts.power(table.ppt_value)),
+            BriefField::Freq => match table.max_core_freq() {
+                0.0 => "-".to_string(),
+                mhz => format!("{:.1}GHz", mhz / 1000.0),
+            },
+            BriefField::Activity => match table.package_c0() {
+                c0 if c0.is_nan() => "-".to_string(),
+                c0 => format!("{:.0}%", c0),
+            },
+        })
+        .collect();
+    format!("{}\n", values.join(" "))
+}
+
+/// Session maxima tracked in watch mode
+#[derive(Debug, Default)]
+pub struct SessionPeaks {
+    pub tctl: f32,
+    pub package_power: f32,
+}
+
+impl SessionPeaks {
+    pub fn update(&mut self, table: &PmTable) {
+        self.tctl = self.tctl.max(table.tctl);
+        self.package_power = self.package_power.max(table.ppt_value);
+    }
+}
+
+pub fn format_session_peaks(peaks: &SessionPeaks, opts: &OutputOptions) -> String {
+    let mut out = String::new();
+    out.push_str("\nPeak (this session):\n");
+    out.push_str(&format!("  Tctl:           {:+.1}{}\n", opts.temp(peaks.tctl), opts.temp_unit.symbol()));
+    out.push_str(&format!("  Package:        {:.1}W\n", opts.power(peaks.package_power)));
+    out
+}
+
+/// Running min/max/mean of one metric
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RunningStat {
+    pub min: f32,
+    pub max: f32,
+    sum: f64,
+    pub count: u64,
+}
+
+impl RunningStat {
+    pub fn push(&mut self, value: f32) {
+        if self.count == 0 {
+            self.min = value;
+            self.max = value;
+        } else {
+            self.min = self.min.min(value);
+            self.max = self.max.max(value);
+        }
+        self.sum += value as f64;
+        self.count += 1;
+    }
+
+    pub fn mean(&self) -> f32 {
+        if self.count == 0 { 0.0 } else { (self.sum / self.count as f64) as f32 }
+    }
+}
+
+/// Session statistics for `--stats` in watch mode
+#[derive(Debug, Default)]
+pub struct StatsAccumulator {
+    pub samples: u64,
+    pub tctl: RunningStat,
+    pub package_power: RunningStat,
+    pub ppt_value: RunningStat,
+    /// Highest core frequency in each sample
+    pub peak_core_freq: RunningStat,
+    /// PPT integrated over the session
+    pub energy: EnergyMeter,
+}
+
+impl StatsAccumulator {
+    pub fn update(&mut self, table: &PmTable) {
+        self.samples += 1;
+        self.tctl.push(table.tctl);
+        self.package_power.push(table.package_power);
+        self.ppt_value.push(table.ppt_value);
+        self.energy.update(table);
+        // Skip samples where no core reported a frequency
+        let peak = table.max_core_freq();
+        if peak > 0.0 {
+            self.peak_core_freq.push(peak);
+        }
+    }
+}
+
+pub fn format_stats(stats: &StatsAccumulator, opts: &OutputOptions) -> String {
+    let mut out = String::new();
+    out.push_str(&format!("\nSession stats ({} samples):       min      avg      max\n", stats.samples));
+    // Each row is (label, sample count, [min, avg, max] in display units)
+    let row = |label: String, stat: RunningStat, convert: &dyn Fn(f32) -> f32| {
+        (label, stat.count, [convert(stat.min), convert(stat.mean()), convert(stat.max)])
+    };
+    let rows = [
+        row(format!("Tctl ({})", opts.temp_unit.symbol()), stats.tctl, &|c| opts.temp(c)),
+        row("Package (W)".to_string(), stats.package_power, &|w| opts.power(w)),
+        row("PPT (W)".to_string(), stats.ppt_value, &|w| opts.power(w)),
+        row("Peak core (MHz)".to_string(), stats.peak_core_freq, &|f| f),
+    ];
+    for (label, count, [min, avg, max]) in rows {
+        if count == 0 {
+            continue;
+        }
+        out.push_str(&format!("  {:<30}{:>8.1} {:>8.1} {:>8.1}\n", label, min, avg, max));
+    }
+    if let Some(average) = stats.energy.average_watts() {
+        let calibration = opts.power_calibration as f64;
+        out.push_str(&format!(
+            "  Energy (PPT): {:.1} J ({:.4} Wh), average {:.1} W over {:.1}s\n",
+            stats.energy.joules() * calibration,
+            stats.energy.watt_hours() * calibration,
+            average * calibration,
+            stats.energy.elapsed().as_secs_f64()
+        ));
+    }
+    out
+}
+
+/// Before/after table for `--diff`, in the same units and names as JSON
+/// (per-core rows named as for `--query`, e.g. `core_temp.3`)
+pub fn format_diff(diff: &PmTableDiff) -> String {
+    let mut out = String::new();
+    out.push_str(&format!("{:<18}{:>10} {:>10} {:>10} {:>9}\n", "Field", "Before", "After", "Delta", "Change"));
+    let row = |name: &str, value: &ValueDiff| {
+        let percent = value.percent().map_or("-".to_string(), |p| format!("{:+.1}%", p));
+        format!("{:<18}{:>10.2} {:>10.2} {:>+10.2} {:>9}\n", name, value.before, value.after, value.delta(), percent)
+    };
+    for (name, value) in &diff.fields {
+        out.push_str(&row(name, value));
+    }
+    for (name, values) in [
+        ("core_temp", &diff.core_temps),
+        ("core_freq", &diff.core_freqs),
+        ("core_freq_eff", &diff.core_freqs_eff),
+        ("core_power", &diff.core_power),
+        ("core_c0", &diff.core_c0),
+        ("core_voltage", &diff.core_voltages),
+    ] {
+        for (i, value) in values.iter().enumerate() {
+            out.push_str(&row(&format!("{}.{}", name, i), value));
+        }
+    }
+    out
+}
+
+pub fn format_json(table: &PmTable, aliases: &FieldAliases) -> String {
+    let Ok(mut value) = serde_json::to_value(table) else {
+        return "{}".to_string();
+    };
+    apply_aliases(&mut value, aliases);
+    serde_json::to_string_pretty(&value).unwrap_or_else(|_| "{}".to_string())
+}
+
+/// JSON with per-core data nested under `ccds` (see `GroupedPmTable`)
+pub fn format_json_grouped(table: &PmTable, aliases: &FieldAliases) -> String {
+    let Ok(mut value) = serde_json::to_value(table.grouped()) else {
+        return "{}".to_string();
+    };
+    apply_aliases(&mut value, aliases);
+    serde_json::to_string_pretty(&value).unwrap_or_else(|_| "{}".to_string())
+}
+
+/// JSON with the table nested under `pm_table`, next to the SMU firmware
+/// and driver versions
+pub fn format_json_envelope(table: &PmTable, versions: &Versions, aliases: &FieldAliases) -> String {
+    let Ok(mut pm_table) = serde_json::to_value(table) else {
+        return "{}".to_string();
+    };
+    apply_aliases(&mut pm_table, aliases);
+    let mut envelope = serde_json::Map::new();
+    envelope.insert("smu_version".to_string(), Value::from(versions.smu_version.clone()));
+    envelope.insert("driver_version".to_string(), Value::from(versions.driver_version.clone()));
+    envelope.insert("pm_table".to_string(), pm_table);
+    serde_json::to_string_pretty(&envelope).unwrap_or_else(|_| "{}".to_string())
+}
+
+/// Single-line JSON record with a `timestamp` field for `time`, unless
+/// `time_format` is `None`
+pub fn format_json_line(table: &PmTable, time: SystemTime, time_format: TimeFormat, aliases: &FieldAliases) -> String {
+    let Ok(mut value) = serde_json::to_value(table) else {
+        return "{}".to_string();
+    };
+    apply_aliases(&mut value, aliases);
+    if let Value::Object(map) = &mut value
+        && let Some(timestamp) = time_format.json(time)
+    {
+        map.insert("timestamp".to_string(), timestamp);
+    }
+    serde_json::to_string(&value).unwrap_or_else(|_| "{}".to_string())
+}
+
+/// Prometheus exposition text for one sample
+///
+/// Per-core gauges are labeled by core index; cores reporting 0.0 are skipped.
+pub fn format_prometheus(table: &PmTable) -> String {
+    let mut out = String::new();
+
+    out.push_str("# HELP amd_smu_info Processor and PM table identification\n");
+    out.push_str("# TYPE amd_smu_info gauge\n");
+    out.push_str(&format!("amd_smu_info{{codename=\"{}\",pm_table_version=\"{:#x}\"}} 1\n",
+        table.codename_str, table.version));
+
+    let scalars = [
+        ("amd_smu_tctl_celsius", "Tctl junction temperature", table.tctl),
+        ("amd_smu_soc_temp_celsius", "SoC temperature", table.soc_temp),
+        ("amd_smu_thermal_limit_celsius", "Thermal limit", table.thm_limit),
+        ("amd_smu_package_power_watts", "Package power", table.package_power),
+        ("amd_smu_soc_power_watts", "SoC power", table.soc_power),
+        ("amd_smu_ppt_watts", "Package Power Tracking value", table.ppt_value),
+        ("amd_smu_ppt_limit_watts", "Package Power Tracking limit", table.ppt_limit),
+        ("amd_smu_tdc_amps", "Thermal Design Current value", table.tdc_value),
+        ("amd_smu_tdc_limit_amps", "Thermal Design Current limit", table.tdc_limit),
+        ("amd_smu_edc_amps", "Electrical Design Current value", table.edc_value),
+        ("amd_smu_edc_limit_amps", "Electrical Design Current limit", table.edc_limit),
+        ("amd_smu_fclk_mhz", "Fabric clock", table.fclk),
+        ("amd_smu_mclk_mhz", "Memory clock", table.mclk),
+        ("amd_smu_core_voltage_volts", "Core voltage", table.core_voltage),
+        ("amd_smu_soc_voltage_volts", "SoC voltage", table.soc_voltage),
+    ];
+    for (name, help, value) in scalars {
+        out.push_str(&format!("# HELP {} {}\n# TYPE {} gauge\n{} {}\n", name, help, name, name, value));
+    }
+
+    let per_core = [
+        ("amd_smu_core_temp_celsius", "Per-core temperature", &table.core_temps),
+        ("amd_smu_core_frequency_mhz", "Per-core frequency", &table.core_freqs),
+        ("amd_smu_core_effective_frequency_mhz", "Per-core effective frequency", &table.core_freqs_eff),
+        ("amd_smu_core_power_watts", "Per-core power", &table.core_power),
+        ("amd_smu_core_c0_percent", "Per-core C0 residency", &table.core_c0),
+        ("amd_smu_core_voltage_per_core_volts", "Per-core voltage", &table.core_voltages),
+    ];
+    for (name, help, values) in per_core {
+        if values.iter().all(|v| *v == 0.0) {
+            continue;
+        }
+        out.push_str(&format!("# HELP {} {}\n# TYPE {} gauge\n", name, help, name));
+        for (i, value) in values.iter().enumerate() {
+            if *value != 0.0 {
+                out.push_str(&format!("{}{{core=\"{}\"}} {}\n", name, i, value));
+            }
+        }
     }
 
     out
 }
 
-pub fn format_json(table: &PmTable) -> String {
-    serde_json::to_string_pretty(table).unwrap_or_else(|_| "{}".to_string())
+/// InfluxDB line protocol for one sample:
+/// `amd_smu,codename=Vermeer,socket=0 tctl=65.2,...,core0_temp=60.1 <ns>`
+///
+/// Every reading is a float field, in the same units as JSON; per-core
+/// fields are `core<N>_temp`, `_freq`, `_freq_eff`, `_power`, `_c0` and
+/// `_voltage`. Non-finite values are left out, as InfluxDB rejects them.
+/// Without `timestamp_ns` the server stamps the point on arrival.
+pub fn format_influx(table: &PmTable, timestamp_ns: Option<u64>, socket: usize) -> String {
+    let mut tags = String::new();
+    if !table.codename_str.is_empty() {
+        tags.push_str(&format!(",codename={}", escape_influx_tag(&table.codename_str)));
+    }
+    tags.push_str(&format!(",socket={}", socket));
+
+    let mut fields: Vec<(String, f32)> = [
+        ("tctl", table.tctl),
+        ("soc_temp", table.soc_temp),
+        ("package_power", table.package_power),
+        ("soc_power", table.soc_power),
+        ("ppt_value", table.ppt_value),
+        ("ppt_limit", table.ppt_limit),
+        ("tdc_value", table.tdc_value),
+        ("tdc_limit", table.tdc_limit),
+        ("edc_value", table.edc_value),
+        ("edc_limit", table.edc_limit),
+        ("thm_limit", table.thm_limit),
+        ("fclk", table.fclk),
+        ("mclk", table.mclk),
+        ("core_voltage", table.core_voltage),
+        ("soc_voltage", table.soc_voltage),
+    ]
+    .into_iter()
+    .map(|(name, value)| (name.to_string(), value))
+    .collect();
+    for (suffix, values) in [
+        ("temp", &table.core_temps),
+        ("freq", &table.core_freqs),
+        ("freq_eff", &table.core_freqs_eff),
+        ("power", &table.core_power),
+        ("c0", &table.core_c0),
+        ("voltage", &table.core_voltages),
+    ] {
+        fields.extend(values.iter().enumerate().map(|(i, v)| (format!("core{}_{}", i, suffix), *v)));
+    }
+
+    let fields: Vec<String> = fields
+        .into_iter()
+        .filter(|(_, value)| value.is_finite())
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect();
+    match timestamp_ns {
+        Some(timestamp_ns) => format!("amd_smu{} {} {}\n", tags, fields.join(","), timestamp_ns),
+        None => format!("amd_smu{} {}\n", tags, fields.join(",")),
+    }
+}
+
+/// A chip block in the style of lm-sensors' `sensors`, for scripts that
+/// already parse its output:
+///
+/// ```text
+/// amd_smu-virtual-0
+/// Adapter: Virtual device
+/// temp1:        +65.2°C  (high = +90.0°C)
+/// ```
+///
+/// Inputs are numbered the hwmon way: temp1 Tctl, temp2 SoC, then one per
+/// core; in0/in1 core and SoC voltage; power1 package, power2 SoC, power3
+/// PPT; curr1 TDC, curr2 EDC; freq1 FCLK, freq2 MCLK. Always Celsius
+/// and uncalibrated; unreadable values print as N/A, as `sensors` does.
+pub fn format_sensors(table: &PmTable) -> String {
+    let mut out = String::from("amd_smu-virtual-0\nAdapter: Virtual device\n");
+    let mut line = |name: String, value: String, limit: Option<(&str, String)>| {
+        out.push_str(&format!("{:<14}{}", format!("{}:", name), value));
+        if let Some((kind, limit)) = limit {
+            out.push_str(&format!("  ({} = {})", kind, limit.trim_start()));
+        }
+        out.push('\n');
+    };
+    let limit = |kind, value: f32, precision, unit| {
+        (value.is_finite() && value > 0.0).then(|| (kind, sensors_value(value, precision, unit, unit == "°C")))
+    };
+    let temp = |value| sensors_value(value, 1, "°C", true);
+
+    let mut temps = vec![table.tctl, table.soc_temp];
+    temps.extend(&table.core_temps);
+    for (i, &value) in temps.iter().enumerate() {
+        line(format!("temp{}", i + 1), temp(value), if i == 0 { limit("high", table.thm_limit, 1, "°C") } else { None });
+    }
+
+    let volts = [table.core_voltage, table.soc_voltage];
+    for (i, &value) in volts.iter().enumerate() {
+        line(format!("in{}", i), sensors_value(value, 2, " V", true), None);
+    }
+
+    for (i, (value, cap)) in [(table.package_power, None), (table.soc_power, None), (table.ppt_value, Some(table.ppt_limit))]
+        .into_iter()
+        .enumerate()
+    {
+        line(format!("power{}", i + 1), sensors_value(value, 2, " W", false), cap.and_then(|cap| limit("cap", cap, 2, " W")));
+    }
+
+    for (i, (value, max)) in [(table.tdc_value, table.tdc_limit), (table.edc_value, table.edc_limit)].into_iter().enumerate() {
+        line(format!("curr{}", i + 1), sensors_value(value, 2, " A", false), limit("max", max, 2, " A"));
+    }
+
+    let freqs = [table.fclk, table.mclk];
+    for (i, &value) in freqs.iter().enumerate() {
+        line(format!("freq{}", i + 1), sensors_value(value, 0, " MHz", false), None);
+    }
+    out
+}
+
+/// One `sensors` reading, right-aligned to six columns like its `%+6.1f`
+fn sensors_value(value: f32, precision: usize, unit: &str, signed: bool) -> String {
+    if !value.is_finite() {
+        format!("{:>6}", "N/A")
+    } else if signed {
+        format!("{:+6.*}{}", precision, value, unit)
+    } else {
+        format!("{:6.*}{}", precision, value, unit)
+    }
+}
+
+/// Backslash-escape the characters line protocol gives meaning to in tags
+fn escape_influx_tag(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, ',' | '=' | ' ' | '\\') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Scalar CSV columns, in order, after the timestamp
+const CSV_SCALARS: [&str; 11] = [
+    "tctl", "soc_temp", "package_power", "ppt_value", "ppt_limit",
+    "tdc_value", "tdc_limit", "edc_value", "edc_limit", "fclk", "mclk",
+];
+
+/// CSV header matching `format_csv` rows for this table's core counts
+pub fn csv_header(table: &PmTable, time_format: TimeFormat) -> String {
+    let mut columns: Vec<String> = Vec::new();
+    if time_format != TimeFormat::None {
+        columns.push("timestamp".to_string());
+    }
+    columns.extend(CSV_SCALARS.iter().map(|c| c.to_string()));
+    columns.extend((0..table.core_temps.len()).map(|i| format!("core{}_temp", i)));
+    columns.extend((0..table.core_freqs.len()).map(|i| format!("core{}_freq", i)));
+    columns.extend((0..table.core_power.len()).map(|i| format!("core{}_power", i)));
+    columns.join(",") + "\n"
+}
+
+/// One CSV row; columns match `csv_header`
+///
+/// Values are rounded to `precision` decimals, or printed in full when None.
+pub fn format_csv(table: &PmTable, time: SystemTime, time_format: TimeFormat, precision: Option<usize>) -> String {
+    let value = |v: &f32| match precision {
+        Some(decimals) => format!("{:.*}", decimals, v),
+        None => v.to_string(),
+    };
+    let scalars = [
+        table.tctl, table.soc_temp, table.package_power, table.ppt_value, table.ppt_limit,
+        table.tdc_value, table.tdc_limit, table.edc_value, table.edc_limit, table.fclk, table.mclk,
+    ];
+    let mut fields: Vec<String> = time_format.format(time).into_iter().collect();
+    fields.extend(scalars.iter().map(value));
+    fields.extend(table.core_temps.iter().map(value));
+    fields.extend(table.core_freqs.iter().map(value));
+    fields.extend(table.core_power.iter().map(value));
+    fields.join(",") + "\n"
+}
+
+/// When `table` was read, or now for tables that weren't stamped (e.g.
+/// decoded from a file)
+pub fn taken_at(table: &PmTable) -> SystemTime {
+    table.timestamp.unwrap_or_else(SystemTime::now)
+}
+
+/// Milliseconds since the Unix epoch
+pub fn unix_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as u64)
+}
+
+/// Nanoseconds since the Unix epoch
+pub fn unix_nanos(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_nanos() as u64)
+}
+
+/// Rename top-level keys of a serialized table; unmapped keys are kept as-is
+///
+/// Every key is looked up in the original object, so chained aliases
+/// (`a=b`, `b=c`) rename each field once. `check_aliases` rules out two
+/// fields landing on the same key.
+pub fn apply_aliases(value: &mut Value, aliases: &FieldAliases) {
+    let Value::Object(map) = value else {
+        return;
+    };
+    *map = std::mem::take(map)
+        .into_iter()
+        .map(|(key, v)| match aliases.get(&key) {
+            Some(alias) => (alias.clone(), v),
+            None => (key, v),
+        })
+        .collect();
+}
+
+/// Build the alias map from `--json-alias` pairs, rejecting a field given
+/// twice and any two fields that would end up under the same key
+pub fn check_aliases(pairs: Vec<(String, String)>) -> Result<FieldAliases, String> {
+    let mut aliases = FieldAliases::new();
+    for (field, alias) in pairs {
+        if aliases.insert(field.clone(), alias).is_some() {
+            return Err(format!("--json-alias: '{}' is renamed more than once", field));
+        }
+    }
+    // Keys either JSON shape can have, with the optional ones present
+    let sample = PmTable {
+        timestamp: Some(UNIX_EPOCH),
+        core_voltages: vec![0.0],
+        core_fit: vec![0.0],
+        missing_fields: vec!["core_c0"],
+        ..Default::default()
+    };
+    let keys: BTreeSet<String> = [serde_json::to_value(&sample), serde_json::to_value(sample.grouped())]
+        .into_iter()
+        .filter_map(|value| match value {
+            Ok(Value::Object(map)) => Some(map.into_iter().map(|(key, _)| key)),
+            _ => None,
+        })
+        .flatten()
+        .chain(aliases.keys().cloned())
+        .collect();
+    let mut taken = BTreeMap::new();
+    for key in &keys {
+        let emitted = aliases.get(key).unwrap_or(key);
+        if let Some(other) = taken.insert(emitted, key) {
+            return Err(format!("--json-alias: '{}' and '{}' would both be written as '{}'", other, key, emitted));
+        }
+    }
+    Ok(aliases)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use amd_smu_lib::Codename;
+    use std::time::Duration;
+
+    fn test_opts() -> OutputOptions {
+        OutputOptions {
+            sections: OutputSections::all(),
+            current_calibration: 1.0,
+            power_calibration: 1.0,
+            core_order: None,
+            show_parked: false,
+            json_aliases: FieldAliases::new(),
+            temp_unit: TempUnit::Celsius,
+            json_envelope: None,
+            ansi: false,
+            json_grouped: false,
+            socket: 0,
+            fields: Vec::new(),
+            cooling: false,
+            thresholds: ThresholdConfig::default(),
+            ccd_relative: false,
+            brief_fields: Vec::new(),
+            precision: None,
+            header: true,
+            pbo: true,
+            time_format: TimeFormat::Unix,
+        }
+    }
+
+    #[test]
+    fn test_level_color_thresholds() {
+        let thresholds = ThresholdConfig::default();
+        assert_eq!(level_color(69.9, thresholds.temperature), "\x1B[32m");
+        assert_eq!(level_color(70.0, thresholds.temperature), "\x1B[33m");
+        assert_eq!(level_color(85.0, thresholds.temperature), "\x1B[31m");
+        assert_eq!(level_color(89.0, thresholds.ppt), "\x1B[33m");
+    }
+
+    #[test]
+    fn test_fields_select_and_order() {
+        let opts = OutputOptions {
+            sections: OutputSections::from_filters(false, true, false),
+            fields: vec![TextField::Fclk, TextField::Tctl, TextField::CoreTemps],
+            ..test_opts()
+        };
+        let table = PmTable { tctl: 65.2, fclk: 1800.0, core_temps: vec![60.0, 61.5], ..Default::default() };
+        assert_eq!(
+            format_text(&table, "SMU", &opts),
+            "FCLK:           1800 MHz\nTctl:           +65.2°C\nCore  0 temp:   +60.0°C\nCore  1 temp:   +61.5°C\n"
+        );
+        assert_eq!(TextField::from_str("core_temps", false), Ok(TextField::CoreTemps));
+        assert_eq!(format_cooling(&[("nct6798 CPU_FAN".into(), 1210)], &opts), "");
+
+        let opts = OutputOptions { fields: Vec::new(), sections: OutputSections::from_filters(true, false, false), ..opts };
+        assert_eq!(
+            format_cooling(&[("nct6798 CPU_FAN".into(), 1210), ("it87 fan2".into(), 800)], &opts),
+            "\nCooling:\n  nct6798 CPU_FAN: 1210 RPM\n  it87 fan2:      800 RPM\n"
+        );
+        assert_eq!(format_cooling(&[], &opts), "");
+    }
+
+    #[test]
+    fn test_non_finite_readings_render_as_dash() {
+        let opts = test_opts();
+        let table = PmTable {
+            tctl: f32::NAN,
+            thm_limit: 90.0,
+            ppt_value: f32::INFINITY,
+            ppt_limit: 142.0,
+            fclk: f32::NAN,
+            core_voltage: f32::NEG_INFINITY,
+            core_freqs: vec![4500.0],
+            core_freqs_eff: vec![f32::NAN],
+            ..Default::default()
+        };
+        let text = format_text(&table, "SMU", &opts);
+        assert!(!text.contains("NaN") && !text.contains("inf"), "{}", text);
+        assert!(text.contains("  Tctl:           —  (limit: 90.0°C)\n"), "{}", text);
+        assert!(text.contains("  Package:        — / 142.0W (PPT)\n"));
+        assert!(text.contains("  FCLK:           —\n"), "{}", text);
+        assert!(text.contains("  VCore:          —\n"));
+        assert!(text.contains("4500 MHz (eff: —)"));
+        assert_eq!(format!("{:+.1}|{:>6.1}", Reading(65.25, "°C"), Reading(f32::NAN, "W")), "+65.2°C|     —");
+        assert!(!text.contains("not supported"));
+
+        // A layout without per-core clocks or C0 (Granite Ridge) says so
+        let table = PmTable { core_temps: vec![60.0], ..Default::default() };
+        let text = format_text(&table, "SMU", &opts);
+        assert!(text.contains("  Per-core frequency: not supported on this CPU\n"), "{}", text);
+        assert!(text.contains("  Per-core C0:    not supported on this CPU\n"));
+        assert!(text.contains("PM Table v0x0\nActive cores: 0/1\n"), "{}", text);
+    }
+
+    #[test]
+    fn test_ccd_relative_labels() {
+        let table = PmTable { codename: Codename::Vermeer, ..Default::default() };
+        let opts = test_opts();
+        assert_eq!(opts.core_label(&table, 9), "Core  9");
+        let opts = OutputOptions { ccd_relative: true, ..opts };
+        assert_eq!(opts.core_label(&table, 3), "CCD0/Core3");
+        assert_eq!(opts.core_label(&table, 9), "CCD1/Core1");
+    }
+
+    #[test]
+    fn test_per_ccd_summary() {
+        // 5950X-style: CCD0 warmer and busier than CCD1
+        let table = PmTable {
+            codename: Codename::Vermeer,
+            core_temps: (0..16).map(|i| if i < 8 { 70.0 } else { 55.0 }).collect(),
+            core_freqs: (0..16).map(|i| if i < 8 { 4500.0 + 50.0 * i as f32 } else { 3600.0 }).collect(),
+            core_freqs_eff: vec![3600.0; 16],
+            core_power: (0..16).map(|i| if i < 8 { 9.5 } else { 1.25 }).collect(),
+            ..Default::default()
+        };
+        let opts = test_opts();
+        let text = format_text(&table, "SMU", &opts);
+        assert!(text.contains(
+            "Per-CCD Summary:\n  \
+             CCD0:           Temp: +70.0°C  Freq: 4675 MHz (peak 4850 MHz)  Power: 76.00W\n  \
+             CCD1:           Temp: +55.0°C  Freq: 3600 MHz (peak 3600 MHz)  Power: 10.00W\n"
+        ), "{}", text);
+
+        // Left out for a single CCD
+        let single = PmTable { core_temps: vec![60.0; 8], ..table };
+        assert!(!format_text(&single, "SMU", &opts).contains("Per-CCD"));
+    }
+
+    #[test]
+    fn test_precision_overrides_decimals() {
+        let table = PmTable { tctl: 65.25, fclk: 1800.0, core_voltage: 1.35, core_temps: vec![60.0, 72.4], ..Default::default() };
+        let opts = OutputOptions { fields: vec![TextField::Tctl, TextField::Fclk, TextField::Vcore], ..test_opts() };
+        assert_eq!(format_text(&table, "SMU", &opts), "Tctl:           +65.2°C\nFCLK:           1800 MHz\nVCore:          1.350V\n");
+
+        let opts = OutputOptions { precision: Some(2), ..opts };
+        assert_eq!(format_text(&table, "SMU", &opts), "Tctl:           +65.25°C\nFCLK:           1800.00 MHz\nVCore:          1.35V\n");
+        let opts = OutputOptions { fields: Vec::new(), precision: Some(0), ..opts };
+        let text = format_text(&table, "SMU", &opts);
+        assert!(text.contains("  Tctl:           +65°C"), "{}", text);
+        assert!(text.contains("  VCore:          1V\n"), "{}", text);
+        assert!(text.contains("  Core spread:    12°C  (stddev 6°C)\n"), "{}", text);
+
+        let text = format_text(&table, "SMU", &OutputOptions { header: false, ..opts });
+        assert!(text.starts_with("Temperatures:\n"), "{}", text);
+    }
+
+    #[test]
+    fn test_paint_limit_calibrated() {
+        // 80% raw, 96% calibrated: past the default 90% PPT critical level
+        let table = PmTable { ppt_value: 80.0, ppt_limit: 100.0, ..Default::default() };
+        let opts = OutputOptions { ansi: true, ..test_opts() };
+        assert!(opts.paint_limit("80W".into(), &table, Limit::Ppt).starts_with("\x1B[33m"));
+        let opts = OutputOptions { power_calibration: 1.2, ..opts };
+        assert!(opts.paint_limit("96W".into(), &table, Limit::Ppt).starts_with("\x1B[31m"));
+    }
+
+    #[test]
+    fn test_summary_calibrated() {
+        let table = PmTable { ppt_value: 100.0, ppt_limit: 142.0, tdc_value: 80.0, tdc_limit: 95.0, ..Default::default() };
+        assert!(format_summary(&table, &test_opts()).contains("Limiter:        TDC (84%)\n"));
+
+        // PPT reads 120 W against 142 W on both lines
+        let text = format_summary(&table, &OutputOptions { power_calibration: 1.2, ..test_opts() });
+        assert!(text.contains("Package:        120.0W / 142.0W (85%)\n"), "{}", text);
+        assert!(text.contains("Limiter:        PPT (85%)\n"), "{}", text);
+    }
+
+    #[test]
+    fn test_brief_line() {
+        let table = PmTable { tctl: 65.4, ppt_value: 88.2, core_freqs: vec![4480.0, 4520.0], ..Default::default() };
+        let opts = test_opts();
+        let fields = [BriefField::Tctl, BriefField::Power, BriefField::Freq];
+        assert_eq!(format_brief(&table, &fields, &opts), "65°C 88W 4.5GHz\n");
+        assert_eq!(format_brief(&PmTable::default(), &[BriefField::Freq, BriefField::Activity], &opts), "- -\n");
+    }
+
+    #[test]
+    fn test_influx_line() {
+        let table = PmTable {
+            codename_str: "Granite Ridge".into(),
+            tctl: 65.5,
+            fclk: 1800.0,
+            soc_power: f32::NAN,
+            core_temps: vec![60.25],
+            ..Default::default()
+        };
+        let line = format_influx(&table, Some(1_700_000_000_000_000_000), 1);
+        assert!(line.starts_with("amd_smu,codename=Granite\\ Ridge,socket=1 tctl=65.5,"));
+        assert!(line.contains(",fclk=1800,"));
+        assert!(line.contains(",core0_temp=60.25 "));
+        assert!(!line.contains("soc_power"));
+        assert!(!line.contains("peak_tctl"));
+        assert!(line.ends_with(" 1700000000000000000\n"));
+        assert_eq!(line.lines().count(), 1);
+        assert!(format_influx(&table, None, 1).ends_with(",core0_temp=60.25\n"));
+    }
+
+    #[test]
+    fn test_diff_rows() {
+        let before = PmTable { tctl: 60.0, core_temps: vec![50.0], ..Default::default() };
+        let after = PmTable { tctl: 75.0, core_temps: vec![55.0, 70.0], ..Default::default() };
+        let out = format_diff(&before.diff(&after));
+        assert!(out.contains("tctl                   60.00      75.00     +15.00    +25.0%"));
+        assert!(out.contains("fclk                    0.00       0.00      +0.00         -"));
+        assert!(out.contains("core_temp.0"));
+        assert!(!out.contains("core_temp.1"));
+    }
+
+    #[test]
+    fn test_json_aliases() {
+        let table = PmTable { tctl: 65.5, soc_temp: 40.0, ..Default::default() };
+        let aliases = FieldAliases::from([("tctl".to_string(), "cpu_temp".to_string())]);
+
+        let value: Value = serde_json::from_str(&format_json(&table, &aliases)).unwrap();
+        assert_eq!(value["cpu_temp"], 65.5);
+        assert!(value.get("tctl").is_none());
+        assert_eq!(value["soc_temp"], 40.0);
+    }
+
+    #[test]
+    fn test_json_aliases_chained_and_swapped() {
+        let table = PmTable { tctl: 65.5, soc_temp: 40.0, fclk: 1800.0, ..Default::default() };
+        let aliases = check_aliases(vec![
+            ("tctl".to_string(), "soc_temp".to_string()),
+            ("soc_temp".to_string(), "fclk".to_string()),
+            ("fclk".to_string(), "tctl".to_string()),
+        ])
+        .unwrap();
+
+        let value: Value = serde_json::from_str(&format_json(&table, &aliases)).unwrap();
+        assert_eq!(value["soc_temp"], 65.5);
+        assert_eq!(value["fclk"], 40.0);
+        assert_eq!(value["tctl"], 1800.0);
+    }
+
+    #[test]
+    fn test_conflicting_json_aliases_rejected() {
+        let pairs = |list: &[(&str, &str)]| list.iter().map(|(f, a)| (f.to_string(), a.to_string())).collect();
+        // Onto a field that keeps its name
+        assert!(check_aliases(pairs(&[("tctl", "soc_temp")])).is_err());
+        // Two fields onto one key
+        assert!(check_aliases(pairs(&[("tctl", "temp"), ("soc_temp", "temp")])).is_err());
+        // One field twice
+        assert!(check_aliases(pairs(&[("tctl", "a"), ("tctl", "b")])).is_err());
+        assert!(check_aliases(pairs(&[("tctl", "cpu_temp"), ("ppt_value", "power")])).is_ok());
+    }
+
+    #[test]
+    fn test_json_line_is_single_line_with_timestamp() {
+        let table = PmTable { tctl: 65.5, core_temps: vec![60.0, 61.0], ..Default::default() };
+        let time = UNIX_EPOCH + Duration::from_millis(1_700_000_000_123);
+        let line = format_json_line(&table, time, TimeFormat::Unix, &FieldAliases::new());
+
+        assert!(!line.contains('\n'));
+        let value: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["timestamp"], 1_700_000_000_123u64);
+        assert_eq!(value["tctl"], 65.5);
+
+        let line = format_json_line(&table, time, TimeFormat::Iso8601, &FieldAliases::new());
+        let value: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["timestamp"], "2023-11-14T22:13:20.123Z");
+        let line = format_json_line(&table, time, TimeFormat::None, &FieldAliases::new());
+        assert!(serde_json::from_str::<Value>(&line).unwrap().get("timestamp").is_none());
+    }
+
+    #[test]
+    fn test_stats_accumulator() {
+        let mut stats = StatsAccumulator::default();
+        let sample = |tctl: f32, freqs: Vec<f32>| PmTable { tctl, core_freqs: freqs, ..Default::default() };
+
+        stats.update(&sample(60.0, vec![4500.0, 0.0]));
+        assert_eq!(stats.tctl.min, 60.0);
+        assert_eq!(stats.tctl.mean(), 60.0);
+
+        stats.update(&sample(70.0, vec![0.0, 0.0]));
+        stats.update(&sample(50.0, vec![4700.0, 4600.0]));
+
+        assert_eq!(stats.samples, 3);
+        assert_eq!(stats.tctl.min, 50.0);
+        assert_eq!(stats.tctl.max, 70.0);
+        assert!((stats.tctl.mean() - 60.0).abs() < 0.001);
+        // The all-zero sample doesn't drag the frequency stats down
+        assert_eq!(stats.peak_core_freq.count, 2);
+        assert_eq!(stats.peak_core_freq.min, 4500.0);
+        assert_eq!(stats.peak_core_freq.mean(), 4600.0);
+    }
+
+    #[test]
+    fn test_json_envelope_has_versions() {
+        let table = PmTable { tctl: 65.5, ..Default::default() };
+        let versions = Versions {
+            smu_version: Some("SMU v46.54.0".to_string()),
+            driver_version: Some("0.1.7".to_string()),
+        };
+        let aliases = FieldAliases::from([("tctl".to_string(), "cpu_temp".to_string())]);
+        let value: Value = serde_json::from_str(&format_json_envelope(&table, &versions, &aliases)).unwrap();
+
+        assert_eq!(value["smu_version"], "SMU v46.54.0");
+        assert_eq!(value["driver_version"], "0.1.7");
+        assert_eq!(value["pm_table"]["cpu_temp"], 65.5);
+
+        // Offline dumps have no versions to report
+        let value: Value =
+            serde_json::from_str(&format_json_envelope(&table, &Versions::default(), &aliases)).unwrap();
+        assert!(value["smu_version"].is_null());
+    }
+
+    #[test]
+    fn test_prometheus_format() {
+        let table = PmTable {
+            tctl: 65.5,
+            core_temps: vec![60.0, 0.0, 62.5],
+            ..Default::default()
+        };
+        let text = format_prometheus(&table);
+
+        assert!(text.contains("# TYPE amd_smu_tctl_celsius gauge\namd_smu_tctl_celsius 65.5\n"));
+        assert!(text.contains("amd_smu_core_temp_celsius{core=\"0\"} 60\n"));
+        assert!(text.contains("amd_smu_core_temp_celsius{core=\"2\"} 62.5\n"));
+        assert!(!text.contains("core=\"1\""));
+        // All-zero per-core series are left out entirely
+        assert!(!text.contains("amd_smu_core_c0_percent"));
+    }
+
+    #[test]
+    fn test_sensors_format() {
+        let table = PmTable {
+            tctl: 65.2,
+            thm_limit: 90.0,
+            soc_temp: 42.1,
+            core_temps: vec![60.0, f32::NAN],
+            core_voltage: 1.35,
+            soc_voltage: 1.1,
+            package_power: 88.5,
+            soc_power: 12.4,
+            ppt_value: 89.5,
+            ppt_limit: 142.0,
+            tdc_value: 62.3,
+            tdc_limit: 95.0,
+            edc_value: 98.7,
+            fclk: 1800.0,
+            mclk: 1800.0,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_sensors(&table),
+            "amd_smu-virtual-0
+Adapter: Virtual device
+temp1:         +65.2°C  (high = +90.0°C)
+temp2:         +42.1°C
+temp3:         +60.0°C
+temp4:           N/A
+in0:           +1.35 V
+in1:           +1.10 V
+power1:        88.50 W
+power2:        12.40 W
+power3:        89.50 W  (cap = 142.00 W)
+curr1:         62.30 A  (max = 95.00 A)
+curr2:         98.70 A
+freq1:          1800 MHz
+freq2:          1800 MHz
+"
+        );
+    }
+
+    #[test]
+    fn test_csv_columns_match_header() {
+        let table = PmTable {
+            tctl: 65.5,
+            core_temps: vec![60.0, 61.5],
+            core_freqs: vec![4500.0, 4450.0],
+            core_power: vec![8.0, 8.25],
+            ..Default::default()
+        };
+        let time = UNIX_EPOCH + Duration::from_millis(1234);
+        let header = csv_header(&table, TimeFormat::Unix);
+        let row = format_csv(&table, time, TimeFormat::Unix, None);
+
+        assert!(header.starts_with("timestamp,tctl,soc_temp,"));
+        assert!(header.trim_end().ends_with("core0_power,core1_power"));
+        assert_eq!(header.split(',').count(), row.split(',').count());
+        assert!(row.starts_with("1234,65.5,"));
+        assert!(row.trim_end().ends_with("8,8.25"));
+        let row = format_csv(&table, time, TimeFormat::Unix, Some(3));
+        assert!(row.starts_with("1234,65.500,0.000,"));
+        assert!(row.trim_end().ends_with("8.000,8.250"));
+
+        let row = format_csv(&table, time, TimeFormat::Iso8601, None);
+        assert!(row.starts_with("1970-01-01T00:00:01.234Z,65.5,"));
+        let header = csv_header(&table, TimeFormat::None);
+        let row = format_csv(&table, time, TimeFormat::None, None);
+        assert!(header.starts_with("tctl,soc_temp,"));
+        assert!(row.starts_with("65.5,"));
+        assert_eq!(header.split(',').count(), row.split(',').count());
+    }
 }
@@ -0,0 +1,71 @@
+use amd_smu_lib::PmTable;
+
+/// Look up one numeric reading by name for `--query`
+///
+/// Scalars use their JSON field names (`tctl`, `package_power`, `fclk`);
+/// per-core readings take an index, e.g. `core_temp.3`.
+pub fn resolve(table: &PmTable, field: &str) -> Result<f32, String> {
+    if let Some((name, index)) = field.split_once('.') {
+        let values = match name {
+            "core_temp" | "core_temps" => &table.core_temps,
+            "core_freq" | "core_freqs" => &table.core_freqs,
+            "core_freq_eff" | "core_freqs_eff" => &table.core_freqs_eff,
+            "core_power" => &table.core_power,
+            "core_c0" => &table.core_c0,
+            "core_voltage" | "core_voltages" => &table.core_voltages,
+            _ => return Err(format!("unknown per-core field '{}'", name)),
+        };
+        let index: usize = index
+            .parse()
+            .map_err(|_| format!("invalid core index '{}'", index))?;
+        return values.get(index).copied().ok_or_else(|| {
+            format!("core index {} out of range ({} cores)", index, values.len())
+        });
+    }
+
+    let value = match field {
+        "ppt_limit" => table.ppt_limit,
+        "tdc_limit" => table.tdc_limit,
+        "edc_limit" => table.edc_limit,
+        "thm_limit" => table.thm_limit,
+        "ppt_value" => table.ppt_value,
+        "tdc_value" => table.tdc_value,
+        "edc_value" => table.edc_value,
+        "tctl" => table.tctl,
+        "soc_temp" => table.soc_temp,
+        "fclk" => table.fclk,
+        "mclk" => table.mclk,
+        "package_power" => table.package_power,
+        "soc_power" => table.soc_power,
+        "core_voltage" => table.core_voltage,
+        "soc_voltage" => table.soc_voltage,
+        _ => return Err(format!("unknown field '{}'", field)),
+    };
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> PmTable {
+        PmTable { tctl: 65.5, fclk: 1800.0, core_temps: vec![60.0, 61.0, 62.0, 63.0], ..Default::default() }
+    }
+
+    #[test]
+    fn test_resolve_scalars() {
+        assert_eq!(resolve(&table(), "tctl"), Ok(65.5));
+        assert_eq!(resolve(&table(), "fclk"), Ok(1800.0));
+        assert!(resolve(&table(), "peak_tctl").is_err());
+        assert!(resolve(&table(), "bogus").is_err());
+    }
+
+    #[test]
+    fn test_resolve_per_core() {
+        assert_eq!(resolve(&table(), "core_temp.3"), Ok(63.0));
+        assert_eq!(resolve(&table(), "core_temps.0"), Ok(60.0));
+        assert!(resolve(&table(), "core_temp.4").unwrap_err().contains("out of range"));
+        assert!(resolve(&table(), "core_temp.x").is_err());
+        assert!(resolve(&table(), "core_volts.0").is_err());
+    }
+}
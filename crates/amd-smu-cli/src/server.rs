@@ -0,0 +1,191 @@
+use crate::output::{format_json_line, taken_at, FieldAliases, TimeFormat};
+use amd_smu_lib::{with_retry, PmTableSource, READ_RETRIES, RETRY_DELAY};
+use std::fs;
+use std::io::{self, Write};
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Most clients served at once; later connections are closed right away
+pub const MAX_CLIENTS: usize = 32;
+
+/// How often new connections are picked up between samples
+const ACCEPT_POLL: Duration = Duration::from_millis(50);
+
+/// Unix socket that fans JSON lines out to every connected client
+pub struct SocketServer {
+    listener: UnixListener,
+    path: PathBuf,
+    clients: Vec<UnixStream>,
+    /// Last line broadcast, replayed to clients as they connect
+    latest: Option<String>,
+}
+
+impl SocketServer {
+    /// Listen on `path`, replacing a stale socket left by a crashed server
+    pub fn bind(path: &Path) -> io::Result<Self> {
+        if let Ok(meta) = fs::symlink_metadata(path)
+            && meta.file_type().is_socket()
+            && UnixStream::connect(path).is_err()
+        {
+            fs::remove_file(path)?;
+        }
+        let listener = UnixListener::bind(path)?;
+        listener.set_nonblocking(true)?;
+        Ok(Self { listener, path: path.to_path_buf(), clients: Vec::new(), latest: None })
+    }
+
+    /// Accept pending connections, sending each the latest sample
+    pub fn accept_pending(&mut self) {
+        loop {
+            match self.listener.accept() {
+                Ok((mut stream, _)) => {
+                    if self.clients.len() >= MAX_CLIENTS {
+                        notice!("Warning: refusing client, {} already connected", MAX_CLIENTS);
+                        continue;
+                    }
+                    // A client that stops reading gets dropped once its
+                    // buffer fills, rather than stalling everyone else
+                    if stream.set_nonblocking(true).is_err() {
+                        continue;
+                    }
+                    if let Some(line) = &self.latest
+                        && stream.write_all(line.as_bytes()).is_err()
+                    {
+                        continue;
+                    }
+                    self.clients.push(stream);
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) => {
+                    eprintln!("Warning: accept failed: {}", e);
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Send `line` to every client, dropping the ones that went away
+    pub fn broadcast(&mut self, line: String) {
+        self.clients.retain_mut(|client| client.write_all(line.as_bytes()).is_ok());
+        self.latest = Some(line);
+    }
+}
+
+impl Drop for SocketServer {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Read the PM table every `interval` and stream it to socket clients as
+/// JSON lines until `running` is cleared
+pub fn serve_samples(
+    path: &Path,
+    source: &mut impl PmTableSource,
+    interval: Duration,
+    aliases: &FieldAliases,
+    time_format: TimeFormat,
+    running: &AtomicBool,
+) -> io::Result<()> {
+    let mut server = SocketServer::bind(path)?;
+    notice!("Streaming samples on {}", path.display());
+
+    while running.load(Ordering::SeqCst) {
+        let started = Instant::now();
+        match with_retry(READ_RETRIES, RETRY_DELAY, || source.read()) {
+            Ok(table) => {
+                let line = format_json_line(&table, taken_at(&table), time_format, aliases);
+                server.broadcast(format!("{}\n", line));
+            }
+            Err(e) => eprintln!("Warning: error reading PM table: {}", e),
+        }
+
+        // Keep picking up clients until the next sample is due
+        while running.load(Ordering::SeqCst) {
+            server.accept_pending();
+            let remaining = interval.saturating_sub(started.elapsed());
+            if remaining.is_zero() {
+                break;
+            }
+            thread::sleep(remaining.min(ACCEPT_POLL));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+
+    fn read_line(client: &UnixStream) -> String {
+        let mut line = String::new();
+        BufReader::new(client).read_line(&mut line).unwrap();
+        line
+    }
+
+    #[test]
+    fn test_broadcast_and_replay_latest() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("smu.sock");
+        let mut server = SocketServer::bind(&path).unwrap();
+
+        let first = UnixStream::connect(&path).unwrap();
+        server.accept_pending();
+        server.broadcast("{\"tctl\":60.0}\n".to_string());
+        assert_eq!(read_line(&first), "{\"tctl\":60.0}\n");
+
+        // Late joiners get the latest sample straight away
+        let late = UnixStream::connect(&path).unwrap();
+        server.accept_pending();
+        assert_eq!(read_line(&late), "{\"tctl\":60.0}\n");
+        assert_eq!(server.clients.len(), 2);
+    }
+
+    #[test]
+    fn test_disconnected_clients_are_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("smu.sock");
+        let mut server = SocketServer::bind(&path).unwrap();
+
+        let gone = UnixStream::connect(&path).unwrap();
+        let staying = UnixStream::connect(&path).unwrap();
+        server.accept_pending();
+        drop(gone);
+
+        server.broadcast("a\n".to_string());
+        server.broadcast("b\n".to_string());
+        assert_eq!(server.clients.len(), 1);
+        assert_eq!(read_line(&staying), "a\n");
+    }
+
+    #[test]
+    fn test_client_cap() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("smu.sock");
+        let mut server = SocketServer::bind(&path).unwrap();
+
+        let _clients: Vec<UnixStream> = (0..MAX_CLIENTS + 2)
+            .map(|_| UnixStream::connect(&path).unwrap())
+            .collect();
+        server.accept_pending();
+        assert_eq!(server.clients.len(), MAX_CLIENTS);
+    }
+
+    #[test]
+    fn test_stale_socket_replaced_and_removed_on_drop() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("smu.sock");
+        // A listener that's gone leaves its socket file behind
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+        assert!(path.exists());
+
+        let server = SocketServer::bind(&path).unwrap();
+        drop(server);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,53 @@
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How often an idle watch loop checks for a trigger or Ctrl-C
+const POLL: Duration = Duration::from_millis(50);
+
+/// Set by SIGUSR1 and consumed by the next sample
+///
+/// Signals that arrive while a sample is being taken coalesce into one
+/// further sample rather than queueing up.
+pub struct SignalTrigger {
+    pending: Arc<AtomicBool>,
+}
+
+impl SignalTrigger {
+    /// Install the SIGUSR1 handler
+    pub fn install() -> io::Result<Self> {
+        let pending = Arc::new(AtomicBool::new(false));
+        signal_hook::flag::register(signal_hook::consts::SIGUSR1, Arc::clone(&pending))?;
+        Ok(Self { pending })
+    }
+
+    /// Block until a signal has arrived; false if `running` was cleared first
+    pub fn wait(&self, running: &AtomicBool) -> bool {
+        while running.load(Ordering::SeqCst) {
+            if self.pending.swap(false, Ordering::SeqCst) {
+                return true;
+            }
+            std::thread::sleep(POLL);
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signals_coalesce() {
+        let trigger = SignalTrigger::install().unwrap();
+        let running = AtomicBool::new(true);
+        signal_hook::low_level::raise(signal_hook::consts::SIGUSR1).unwrap();
+        signal_hook::low_level::raise(signal_hook::consts::SIGUSR1).unwrap();
+        assert!(trigger.wait(&running));
+        assert!(!trigger.pending.load(Ordering::SeqCst));
+
+        running.store(false, Ordering::SeqCst);
+        assert!(!trigger.wait(&running));
+    }
+}
@@ -0,0 +1,31 @@
+/// CRC-32 (IEEE 802.3) of a byte buffer
+///
+/// Used as a cheap, stable fingerprint of a raw PM table blob, e.g. to tell
+/// whether two reads returned identical data or to identify a capture in a
+/// bug report.
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc32_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn test_crc32_empty() {
+        assert_eq!(crc32(&[]), 0);
+    }
+}
@@ -1,9 +1,64 @@
+use crate::SmuError;
 use std::fmt;
+use std::str::FromStr;
+
+/// A factory power configuration: nominal TDP and the stock limits it
+/// maps to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PowerTier {
+    /// Marketed TDP (W)
+    pub tdp: u32,
+    /// Package Power Tracking limit the SMU enforces for that TDP (W)
+    pub ppt: f32,
+    /// Stock Thermal Design Current limit (A)
+    pub tdc: f32,
+    /// Stock Electrical Design Current limit (A)
+    pub edc: f32,
+    /// Only reachable through the BIOS Eco Mode setting, never a SKU default
+    pub eco: bool,
+}
+
+const fn tier(tdp: u32, ppt: f32, tdc: f32, edc: f32, eco: bool) -> PowerTier {
+    PowerTier { tdp, ppt, tdc, edc, eco }
+}
+
+/// AM4 desktop: 65W and 105W SKUs, plus the 45W Eco Mode setting
+const AM4_TIERS: &[PowerTier] = &[
+    tier(45, 60.0, 45.0, 65.0, true),
+    tier(65, 88.0, 60.0, 90.0, false),
+    tier(105, 142.0, 95.0, 140.0, false),
+];
+
+/// AM5 desktop: 65W to 170W SKUs, plus the 45W Eco Mode setting
+const AM5_TIERS: &[PowerTier] = &[
+    tier(45, 60.0, 45.0, 65.0, true),
+    tier(65, 88.0, 75.0, 150.0, false),
+    tier(105, 142.0, 110.0, 170.0, false),
+    tier(120, 162.0, 120.0, 180.0, false),
+    tier(170, 230.0, 160.0, 225.0, false),
+];
+
+/// PM table versions reported by Zen 2/Zen 3 desktop parts
+const AM4_ZEN2_ZEN3_VERSIONS: &[u32] = &[
+    0x240802, 0x240803, 0x240902, 0x240903, 0x380804, 0x380805, 0x380904, 0x380905,
+];
+
+/// PM table versions reported by Raphael
+const RAPHAEL_VERSIONS: &[u32] = &[
+    0x540100, 0x540101, 0x540102, 0x540103, 0x540104, 0x540105, 0x540108,
+];
+
+/// PM table versions reported by Granite Ridge
+const GRANITE_RIDGE_VERSIONS: &[u32] = &[0x620105, 0x620205];
+
+/// Highest numeric codename id ryzen_smu assigns
+const MAX_ID: u32 = 25;
 
 /// AMD processor codenames supported by ryzen_smu
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 #[repr(u32)]
 pub enum Codename {
+    #[default]
     Unsupported = 0,
     Colfax = 1,
     Renoir = 2,
@@ -70,15 +125,70 @@ impl Codename {
         match self {
             Self::Matisse | Self::Vermeer | Self::Milan | Self::Raphael | Self::GraniteRidge => 8,
             Self::Cezanne | Self::Rembrandt | Self::Phoenix | Self::HawkPoint | Self::StrixPoint => 8,
-            Self::Renoir | Self::Lucienne => 8,
+            Self::Renoir | Self::Lucienne | Self::StormPeak => 8,
             _ => 8, // Default assumption
         }
     }
 
+    /// Known factory power tiers for this family, lowest PPT first
+    ///
+    /// Empty for families we don't have data for (mobile and server parts).
+    pub fn power_tiers(&self) -> &'static [PowerTier] {
+        match self {
+            Self::SummitRidge | Self::PinnacleRidge | Self::Matisse | Self::Vermeer => AM4_TIERS,
+            Self::Raphael | Self::GraniteRidge => AM5_TIERS,
+            _ => &[],
+        }
+    }
+
+    /// PM table versions this family is known to report
+    ///
+    /// Empty for families we have no data for, where any version is
+    /// plausible.
+    pub fn known_pm_table_versions(&self) -> &'static [u32] {
+        match self {
+            Self::Matisse | Self::Vermeer => AM4_ZEN2_ZEN3_VERSIONS,
+            Self::Raphael => RAPHAEL_VERSIONS,
+            Self::GraniteRidge => GRANITE_RIDGE_VERSIONS,
+            _ => &[],
+        }
+    }
+
+    /// Whether `version` is plausible for this family (see
+    /// `known_pm_table_versions`)
+    pub fn expects_pm_table_version(&self, version: u32) -> bool {
+        let known = self.known_pm_table_versions();
+        known.is_empty() || known.contains(&version)
+    }
+
+    /// Codenames whose `known_pm_table_versions` include `version`, by id
+    pub fn reporting_pm_table_version(version: u32) -> Vec<Self> {
+        (1..=MAX_ID)
+            .map(Self::from_id)
+            .filter(|codename| codename.known_pm_table_versions().contains(&version))
+            .collect()
+    }
+
+    /// Zen microarchitecture generation of this family
+    ///
+    /// Zen+ parts report 1 alongside Zen; `Unsupported` reports 0.
+    pub fn zen_generation(&self) -> u8 {
+        match self {
+            Self::SummitRidge | Self::PinnacleRidge | Self::Naples | Self::Threadripper
+            | Self::Colfax | Self::Raven | Self::Raven2 | Self::Picasso | Self::Dali => 1,
+            Self::Matisse | Self::CastlePeak | Self::Renoir | Self::Lucienne | Self::Vangogh => 2,
+            Self::Vermeer | Self::Cezanne | Self::Milan | Self::Chagall | Self::Rembrandt => 3,
+            Self::Raphael | Self::Phoenix | Self::HawkPoint | Self::StormPeak => 4,
+            Self::GraniteRidge | Self::StrixPoint => 5,
+            Self::Unsupported => 0,
+        }
+    }
+
     /// Get max CCDs for this processor family
     pub fn max_ccds(&self) -> usize {
         match self {
-            Self::Milan | Self::Naples | Self::Chagall | Self::StormPeak => 8,
+            Self::StormPeak => 12,
+            Self::Milan | Self::Naples | Self::Chagall => 8,
             Self::Threadripper | Self::CastlePeak => 4,
             Self::Vermeer | Self::Matisse | Self::Raphael | Self::GraniteRidge => 2,
             _ => 1,
@@ -86,9 +196,10 @@ impl Codename {
     }
 }
 
-impl fmt::Display for Codename {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let name = match self {
+impl Codename {
+    /// Human-readable name of this codename
+    pub fn as_str(&self) -> &'static str {
+        match self {
             Self::Unsupported => "Unsupported",
             Self::Colfax => "Colfax",
             Self::Renoir => "Renoir",
@@ -115,7 +226,98 @@ impl fmt::Display for Codename {
             Self::GraniteRidge => "Granite Ridge",
             Self::StrixPoint => "Strix Point",
             Self::StormPeak => "Storm Peak",
-        };
-        write!(f, "{}", name)
+        }
+    }
+}
+
+impl fmt::Display for Codename {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Codename {
+    type Err = SmuError;
+
+    /// Parse a display name ("Granite Ridge") or compact form ("graniteridge"),
+    /// ignoring case, spaces, dashes and underscores
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let wanted = normalize(s);
+        (1..=MAX_ID)
+            .map(Self::from_id)
+            .find(|codename| normalize(codename.as_str()) == wanted)
+            .ok_or_else(|| SmuError::UnknownCodename(s.to_string()))
+    }
+}
+
+/// (CCD, core within the CCD) for a flat core index, given
+/// `Codename::cores_per_ccd`; 0 cores per CCD puts everything on CCD 0
+pub fn core_ccd_position(global: usize, cores_per_ccd: usize) -> (usize, usize) {
+    match cores_per_ccd {
+        0 => (0, global),
+        n => (global / n, global % n),
+    }
+}
+
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '_'))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_str_round_trips_display() {
+        for id in 1..=MAX_ID {
+            let codename = Codename::from_id(id);
+            assert_eq!(codename.to_string().parse::<Codename>().unwrap(), codename);
+        }
+    }
+
+    #[test]
+    fn test_from_str_compact_forms() {
+        assert_eq!("graniteridge".parse::<Codename>().unwrap(), Codename::GraniteRidge);
+        assert_eq!("GRANITE_RIDGE".parse::<Codename>().unwrap(), Codename::GraniteRidge);
+        assert_eq!("raven2".parse::<Codename>().unwrap(), Codename::Raven2);
+        assert_eq!("vangogh".parse::<Codename>().unwrap(), Codename::Vangogh);
+    }
+
+    #[test]
+    fn test_from_str_rejects_unknown() {
+        assert!(matches!("Zambezi".parse::<Codename>(), Err(SmuError::UnknownCodename(_))));
+        assert!("Unsupported".parse::<Codename>().is_err());
+        assert!("".parse::<Codename>().is_err());
+    }
+
+    #[test]
+    fn test_expects_pm_table_version() {
+        assert!(Codename::Vermeer.expects_pm_table_version(0x240903));
+        assert!(Codename::GraniteRidge.expects_pm_table_version(0x620205));
+        assert!(!Codename::Raphael.expects_pm_table_version(0x240903));
+        // No data, no opinion
+        assert!(Codename::Naples.expects_pm_table_version(0x123456));
+    }
+
+    #[test]
+    fn test_core_ccd_position() {
+        assert_eq!(core_ccd_position(0, 8), (0, 0));
+        assert_eq!(core_ccd_position(3, 8), (0, 3));
+        assert_eq!(core_ccd_position(8, 8), (1, 0));
+        assert_eq!(core_ccd_position(15, 8), (1, 7));
+        assert_eq!(core_ccd_position(7, 6), (1, 1));
+        assert_eq!(core_ccd_position(5, 0), (0, 5));
+    }
+
+    #[test]
+    fn test_zen_generation() {
+        assert_eq!(Codename::Matisse.zen_generation(), 2);
+        assert_eq!(Codename::Vermeer.zen_generation(), 3);
+        assert_eq!(Codename::Raphael.zen_generation(), 4);
+        assert_eq!(Codename::GraniteRidge.zen_generation(), 5);
+        assert_eq!(Codename::Unsupported.zen_generation(), 0);
     }
 }
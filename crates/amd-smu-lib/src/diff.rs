@@ -0,0 +1,99 @@
+use crate::PmTable;
+
+/// One reading in two samples
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ValueDiff {
+    pub before: f32,
+    pub after: f32,
+}
+
+impl ValueDiff {
+    pub fn delta(&self) -> f32 {
+        self.after - self.before
+    }
+
+    /// Change relative to `before` in percent; None when `before` is 0
+    pub fn percent(&self) -> Option<f32> {
+        (self.before != 0.0).then(|| self.delta() / self.before.abs() * 100.0)
+    }
+}
+
+/// Field-by-field change from one PM table to another
+///
+/// Per-core lists cover the cores both tables have, so tables with
+/// different core counts compare over their common prefix.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PmTableDiff {
+    /// Scalar readings by JSON field name
+    pub fields: Vec<(&'static str, ValueDiff)>,
+    pub core_temps: Vec<ValueDiff>,
+    pub core_freqs: Vec<ValueDiff>,
+    pub core_freqs_eff: Vec<ValueDiff>,
+    pub core_power: Vec<ValueDiff>,
+    pub core_c0: Vec<ValueDiff>,
+    pub core_voltages: Vec<ValueDiff>,
+}
+
+impl PmTable {
+    /// Changes from `self` (before) to `other` (after)
+    pub fn diff(&self, other: &PmTable) -> PmTableDiff {
+        let scalar = |before: f32, after: f32| ValueDiff { before, after };
+        let fields = vec![
+            ("ppt_limit", scalar(self.ppt_limit, other.ppt_limit)),
+            ("tdc_limit", scalar(self.tdc_limit, other.tdc_limit)),
+            ("edc_limit", scalar(self.edc_limit, other.edc_limit)),
+            ("thm_limit", scalar(self.thm_limit, other.thm_limit)),
+            ("ppt_value", scalar(self.ppt_value, other.ppt_value)),
+            ("tdc_value", scalar(self.tdc_value, other.tdc_value)),
+            ("edc_value", scalar(self.edc_value, other.edc_value)),
+            ("tctl", scalar(self.tctl, other.tctl)),
+            ("soc_temp", scalar(self.soc_temp, other.soc_temp)),
+            ("fclk", scalar(self.fclk, other.fclk)),
+            ("mclk", scalar(self.mclk, other.mclk)),
+            ("package_power", scalar(self.package_power, other.package_power)),
+            ("soc_power", scalar(self.soc_power, other.soc_power)),
+            ("core_voltage", scalar(self.core_voltage, other.core_voltage)),
+            ("soc_voltage", scalar(self.soc_voltage, other.soc_voltage)),
+        ];
+        PmTableDiff {
+            fields,
+            core_temps: diff_cores(&self.core_temps, &other.core_temps),
+            core_freqs: diff_cores(&self.core_freqs, &other.core_freqs),
+            core_freqs_eff: diff_cores(&self.core_freqs_eff, &other.core_freqs_eff),
+            core_power: diff_cores(&self.core_power, &other.core_power),
+            core_c0: diff_cores(&self.core_c0, &other.core_c0),
+            core_voltages: diff_cores(&self.core_voltages, &other.core_voltages),
+        }
+    }
+}
+
+fn diff_cores(before: &[f32], after: &[f32]) -> Vec<ValueDiff> {
+    before
+        .iter()
+        .zip(after)
+        .map(|(&before, &after)| ValueDiff { before, after })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_fields_and_common_cores() {
+        let before = PmTable { tctl: 60.0, fclk: 0.0, core_temps: vec![50.0, 55.0, 60.0], ..Default::default() };
+        let after = PmTable { tctl: 75.0, fclk: 1800.0, core_temps: vec![52.0, 50.0], ..Default::default() };
+        let diff = before.diff(&after);
+
+        let tctl = diff.fields.iter().find(|(name, _)| *name == "tctl").unwrap().1;
+        assert_eq!(tctl.delta(), 15.0);
+        assert_eq!(tctl.percent(), Some(25.0));
+        let fclk = diff.fields.iter().find(|(name, _)| *name == "fclk").unwrap().1;
+        assert_eq!(fclk.percent(), None);
+        assert!(!diff.fields.iter().any(|(name, _)| *name == "peak_tctl"));
+
+        assert_eq!(diff.core_temps.len(), 2);
+        assert_eq!(diff.core_temps[1].delta(), -5.0);
+        assert!(diff.core_freqs.is_empty());
+    }
+}
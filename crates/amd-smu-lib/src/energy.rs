@@ -0,0 +1,115 @@
+use crate::PmTable;
+use std::time::{Duration, SystemTime};
+
+/// Energy drawn over a run, integrated from PPT readings
+///
+/// Each pair of consecutive readings adds their average power times the
+/// time between them (trapezoidal rule), so the result doesn't depend on
+/// an even sampling interval. Readings are timed by `PmTable::timestamp`,
+/// or the time of the update for tables without one. A reading older than
+/// the previous one (clock step) starts a new interval without adding
+/// energy.
+///
+/// ```
+/// use amd_smu_lib::EnergyMeter;
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// let mut meter = EnergyMeter::default();
+/// meter.record(UNIX_EPOCH, 80.0);
+/// meter.record(UNIX_EPOCH + Duration::from_secs(10), 100.0);
+/// assert_eq!(meter.joules(), 900.0);
+/// assert_eq!(meter.average_watts(), Some(90.0));
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct EnergyMeter {
+    joules: f64,
+    elapsed: Duration,
+    /// Previous reading's time and power (W)
+    last: Option<(SystemTime, f32)>,
+}
+
+impl EnergyMeter {
+    /// Add `table`'s PPT reading
+    pub fn update(&mut self, table: &PmTable) {
+        self.record(table.timestamp.unwrap_or_else(SystemTime::now), table.ppt_value);
+    }
+
+    /// Add a power reading of `watts` taken at `at`
+    pub fn record(&mut self, at: SystemTime, watts: f32) {
+        if let Some((last_at, last_watts)) = self.last
+            && let Ok(dt) = at.duration_since(last_at)
+        {
+            self.joules += (last_watts as f64 + watts as f64) / 2.0 * dt.as_secs_f64();
+            self.elapsed += dt;
+        }
+        self.last = Some((at, watts));
+    }
+
+    /// Energy since the first reading (J)
+    pub fn joules(&self) -> f64 {
+        self.joules
+    }
+
+    /// Energy since the first reading (Wh)
+    pub fn watt_hours(&self) -> f64 {
+        self.joules / 3600.0
+    }
+
+    /// Time covered by the readings so far
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Mean power since the first reading; None until two readings apart
+    /// in time
+    pub fn average_watts(&self) -> Option<f64> {
+        (!self.elapsed.is_zero()).then(|| self.joules / self.elapsed.as_secs_f64())
+    }
+
+    /// Start over; the next reading is the new starting point
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    #[test]
+    fn test_energy_with_uneven_intervals() {
+        let at = |ms: u64| UNIX_EPOCH + Duration::from_millis(ms);
+        let mut meter = EnergyMeter::default();
+        meter.record(at(0), 100.0);
+        assert_eq!(meter.average_watts(), None);
+        meter.record(at(1000), 100.0);
+        meter.record(at(4000), 50.0);
+        // 100 J, then 3 s averaging 75 W
+        assert!((meter.joules() - 325.0).abs() < 1e-9);
+        assert_eq!(meter.elapsed(), Duration::from_secs(4));
+        assert!((meter.average_watts().unwrap() - 81.25).abs() < 1e-9);
+        assert!((meter.watt_hours() - 325.0 / 3600.0).abs() < 1e-12);
+
+        // A clock step back adds nothing and restarts from there
+        meter.record(at(2000), 50.0);
+        meter.record(at(3000), 50.0);
+        assert!((meter.joules() - 375.0).abs() < 1e-9);
+
+        meter.reset();
+        assert_eq!(meter, EnergyMeter::default());
+    }
+
+    #[test]
+    fn test_update_uses_table_timestamp() {
+        let mut meter = EnergyMeter::default();
+        for secs in [0, 2] {
+            meter.update(&PmTable {
+                ppt_value: 60.0,
+                timestamp: Some(UNIX_EPOCH + Duration::from_secs(secs)),
+                ..Default::default()
+            });
+        }
+        assert_eq!(meter.joules(), 120.0);
+    }
+}
@@ -1,3 +1,5 @@
+use crate::{Codename, Limit};
+use std::ops::RangeInclusive;
 use std::path::PathBuf;
 use thiserror::Error;
 
@@ -9,17 +11,51 @@ pub enum SmuError {
     #[error("Permission denied reading {0}: run as root or configure udev rules")]
     PermissionDenied(PathBuf),
 
+    #[error("Failed to read {path}: {source}")]
+    ReadFailed { path: PathBuf, source: std::io::Error },
+
     #[error("Unsupported PM table version: {0:#x}")]
     UnsupportedPmTableVersion(u32),
 
     #[error("Unsupported processor codename: {0}")]
     UnsupportedProcessor(u32),
 
+    #[error("Unknown processor codename: {0:?}")]
+    UnknownCodename(String),
+
     #[error("Invalid PM table size: expected at least {expected} bytes, got {actual}")]
     InvalidPmTableSize { expected: usize, actual: usize },
 
+    #[error("Unknown PM table size: {0} is missing, empty or 0")]
+    UnknownPmTableSize(PathBuf),
+
+    #[error("SMU command {cmd:#x} failed with status {status:#x}")]
+    SmuCommandFailed { cmd: u32, status: u32 },
+
+    #[error("Setting the {limit} limit is not supported on {codename}")]
+    UnsupportedTuning { limit: Limit, codename: Codename },
+
+    #[error("Refusing to set limits: {codename} doesn't report PM table version {version:#x}, so the driver's codename may be stale")]
+    TuningVersionMismatch { codename: Codename, version: u32 },
+
+    #[error("{limit} limit {value} is outside the accepted {min} to {max}", min = .range.start(), max = .range.end())]
+    LimitOutOfRange { limit: Limit, value: f32, range: RangeInclusive<f32> },
+
+    #[error("Invalid core count {0}: expected 1 to {max}", max = crate::MAX_CORES)]
+    InvalidCoreCount(usize),
+
+    #[error("Invalid config {path}: {message}")]
+    InvalidConfig { path: PathBuf, message: String },
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 }
 
+impl SmuError {
+    /// Whether retrying the same read might succeed (e.g. the SMU was busy)
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::Io(_) | Self::ReadFailed { .. } | Self::InvalidPmTableSize { .. })
+    }
+}
+
 pub type Result<T> = std::result::Result<T, SmuError>;
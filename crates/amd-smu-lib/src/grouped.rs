@@ -0,0 +1,147 @@
+use crate::{FreqSource, PmTable};
+use serde::Serialize;
+use std::borrow::Cow;
+
+/// PM table with per-core data nested under the CCD it belongs to
+///
+/// Serializes to the same scalar fields as `PmTable`, with the flat
+/// per-core arrays replaced by `ccds: [{ index, cores: [...] }]`. Cores are
+/// grouped by `Codename::cores_per_ccd`.
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupedPmTable {
+    pub version: u32,
+    #[serde(serialize_with = "crate::pmtable::unix_millis::serialize", skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<std::time::SystemTime>,
+    pub codename: Cow<'static, str>,
+    pub ppt_limit: f32,
+    pub tdc_limit: f32,
+    pub edc_limit: f32,
+    pub thm_limit: f32,
+    pub ppt_value: f32,
+    pub tdc_value: f32,
+    pub edc_value: f32,
+    pub tctl: f32,
+    pub soc_temp: f32,
+    pub fclk: f32,
+    pub mclk: f32,
+    pub freq_source: FreqSource,
+    pub package_power: f32,
+    pub soc_power: f32,
+    pub core_voltage: f32,
+    pub soc_voltage: f32,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub missing_fields: Vec<&'static str>,
+    pub ccds: Vec<CcdMetrics>,
+}
+
+/// One CCD's cores
+#[derive(Debug, Clone, Serialize)]
+pub struct CcdMetrics {
+    pub index: usize,
+    pub cores: Vec<CoreMetrics>,
+}
+
+/// Readings for a single core; values the layout lacks are omitted
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct CoreMetrics {
+    /// Logical core index, as in the flat arrays
+    pub id: usize,
+    pub temp: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub freq: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub freq_eff: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub power: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub c0: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub voltage: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fit: Option<f32>,
+}
+
+impl From<&PmTable> for GroupedPmTable {
+    fn from(table: &PmTable) -> Self {
+        let ccds = (0..table.ccd_count())
+            .map(|index| CcdMetrics {
+                index,
+                cores: table
+                    .cores_in_ccd(index)
+                    .map(|id| CoreMetrics {
+                        id,
+                        temp: table.core_temps[id],
+                        freq: table.core_freqs.get(id).copied(),
+                        freq_eff: table.core_freqs_eff.get(id).copied(),
+                        power: table.core_power.get(id).copied(),
+                        c0: table.core_c0.get(id).copied(),
+                        voltage: table.core_voltages.get(id).copied(),
+                        fit: table.core_fit.get(id).copied(),
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Self {
+            version: table.version,
+            timestamp: table.timestamp,
+            codename: table.codename_str.clone(),
+            ppt_limit: table.ppt_limit,
+            tdc_limit: table.tdc_limit,
+            edc_limit: table.edc_limit,
+            thm_limit: table.thm_limit,
+            ppt_value: table.ppt_value,
+            tdc_value: table.tdc_value,
+            edc_value: table.edc_value,
+            tctl: table.tctl,
+            soc_temp: table.soc_temp,
+            fclk: table.fclk,
+            mclk: table.mclk,
+            freq_source: table.freq_source,
+            package_power: table.package_power,
+            soc_power: table.soc_power,
+            core_voltage: table.core_voltage,
+            soc_voltage: table.soc_voltage,
+            missing_fields: table.missing_fields.clone(),
+            ccds,
+        }
+    }
+}
+
+impl PmTable {
+    /// Copy of this table with per-core data grouped by CCD
+    pub fn grouped(&self) -> GroupedPmTable {
+        GroupedPmTable::from(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Codename;
+
+    #[test]
+    fn test_two_ccd_nesting() {
+        let table = PmTable {
+            codename: Codename::Vermeer,
+            core_temps: (0..16).map(|i| 60.0 + i as f32).collect(),
+            core_freqs: vec![4500.0; 16],
+            core_power: vec![5.0; 16],
+            ..Default::default()
+        };
+
+        let value = serde_json::to_value(table.grouped()).unwrap();
+        let ccds = value["ccds"].as_array().unwrap();
+        assert_eq!(ccds.len(), 2);
+        assert_eq!(ccds[1]["index"], 1);
+
+        let cores = ccds[1]["cores"].as_array().unwrap();
+        assert_eq!(cores.len(), 8);
+        assert_eq!(cores[0]["id"], 8);
+        assert_eq!(cores[0]["temp"], 68.0);
+        assert_eq!(cores[0]["freq"], 4500.0);
+        // No effective clocks or C0 in this table
+        assert!(cores[0].get("c0").is_none());
+        assert!(value.get("core_temps").is_none());
+    }
+}
@@ -0,0 +1,126 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+use crate::Result;
+
+const DEFAULT_HWMON_PATH: &str = "/sys/class/hwmon";
+
+/// hwmon drivers whose fans cool something other than the CPU
+const IGNORED_CHIPS: &[&str] = &["amdgpu", "radeon", "nouveau", "nvidia"];
+
+/// Read fan speeds from the default hwmon location
+pub fn fan_speeds() -> Result<Vec<(String, u32)>> {
+    fan_speeds_from(DEFAULT_HWMON_PATH)
+}
+
+/// Labeled fan speeds (RPM) from a sysfs-style `hwmon` class directory
+///
+/// The SMU doesn't see fans, so this looks at every hwmon chip: k10temp,
+/// the board's Super I/O controller (nct67xx, it87, ...) or a vendor
+/// EC/WMI driver, skipping GPU drivers. Each `fanN_input` is reported as
+/// "<chip> <fanN_label>", or "<chip> fanN" without a label, so fans on
+/// several controllers stay apart. Unlabeled fans reading 0 RPM are
+/// treated as empty headers and left out. No hwmon directory, or no fans,
+/// is an empty list rather than an error.
+pub fn fan_speeds_from<P: AsRef<Path>>(path: P) -> Result<Vec<(String, u32)>> {
+    let entries = match fs::read_dir(path.as_ref()) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut chips: Vec<(u32, std::path::PathBuf)> = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name();
+        if let Some(index) = name.to_str().and_then(|n| n.strip_prefix("hwmon")).and_then(|n| n.parse().ok()) {
+            chips.push((index, entry.path()));
+        }
+    }
+    chips.sort();
+
+    let mut fans = Vec::new();
+    for (_, chip) in chips {
+        let name = fs::read_to_string(chip.join("name")).unwrap_or_default();
+        let name = name.trim();
+        if IGNORED_CHIPS.contains(&name) {
+            continue;
+        }
+
+        let mut inputs: Vec<u32> = Vec::new();
+        for entry in fs::read_dir(&chip)? {
+            let file = entry?.file_name();
+            if let Some(index) = file
+                .to_str()
+                .and_then(|f| f.strip_prefix("fan"))
+                .and_then(|f| f.strip_suffix("_input"))
+                .and_then(|f| f.parse().ok())
+            {
+                inputs.push(index);
+            }
+        }
+        inputs.sort();
+
+        for index in inputs {
+            let Some(rpm) = fs::read_to_string(chip.join(format!("fan{}_input", index)))
+                .ok()
+                .and_then(|s| s.trim().parse::<u32>().ok())
+            else {
+                continue;
+            };
+            let label = fs::read_to_string(chip.join(format!("fan{}_label", index)))
+                .ok()
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty());
+            if label.is_none() && rpm == 0 {
+                continue;
+            }
+            let label = label.unwrap_or_else(|| format!("fan{}", index));
+            fans.push((if name.is_empty() { label } else { format!("{} {}", name, label) }, rpm));
+        }
+    }
+    Ok(fans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn add_chip(root: &Path, index: u32, name: &str, fans: &[(u32, Option<&str>, u32)]) {
+        let chip = root.join(format!("hwmon{}", index));
+        fs::create_dir_all(&chip).unwrap();
+        fs::write(chip.join("name"), format!("{}\n", name)).unwrap();
+        for &(fan, label, rpm) in fans {
+            fs::write(chip.join(format!("fan{}_input", fan)), format!("{}\n", rpm)).unwrap();
+            if let Some(label) = label {
+                fs::write(chip.join(format!("fan{}_label", fan)), format!("{}\n", label)).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_fans_across_controllers() {
+        let dir = TempDir::new().unwrap();
+        add_chip(dir.path(), 0, "k10temp", &[]);
+        add_chip(dir.path(), 1, "amdgpu", &[(1, None, 1500)]);
+        add_chip(dir.path(), 2, "nct6798", &[(2, Some("CPU_FAN"), 1210), (1, None, 850), (3, None, 0)]);
+        add_chip(dir.path(), 10, "asus_ec_sensors", &[(1, Some("CPU Optional"), 0)]);
+
+        assert_eq!(
+            fan_speeds_from(dir.path()).unwrap(),
+            vec![
+                ("nct6798 fan1".to_string(), 850),
+                ("nct6798 CPU_FAN".to_string(), 1210),
+                ("asus_ec_sensors CPU Optional".to_string(), 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_no_hwmon_is_empty() {
+        let dir = TempDir::new().unwrap();
+        assert!(fan_speeds_from(dir.path().join("missing")).unwrap().is_empty());
+        assert!(fan_speeds_from(dir.path()).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,29 @@
+use std::time::Duration;
+
+/// Shortest refresh interval the watch loop and the TUI accept, and the
+/// floor their `+` key stops at
+pub const MIN_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Parse a refresh interval as typed on the command line (`--interval`),
+/// e.g. "250ms" or "2s"; anything under `MIN_INTERVAL` is rejected
+pub fn parse_interval(s: &str) -> Result<Duration, String> {
+    match humantime::parse_duration(s) {
+        Ok(interval) if interval >= MIN_INTERVAL => Ok(interval),
+        Ok(_) => Err(format!("interval must be at least {}", humantime::format_duration(MIN_INTERVAL))),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_interval() {
+        assert_eq!(parse_interval("250ms"), Ok(Duration::from_millis(250)));
+        assert_eq!(parse_interval("2s"), Ok(Duration::from_secs(2)));
+        assert_eq!(parse_interval("100ms"), Ok(MIN_INTERVAL));
+        assert!(parse_interval("50ms").unwrap_err().contains("at least 100ms"));
+        assert!(parse_interval("soon").is_err());
+    }
+}
@@ -1,13 +1,73 @@
+mod checksum;
 mod codename;
+mod diff;
+mod energy;
 mod error;
+mod grouped;
+mod hwmon;
+mod interval;
+mod mailbox;
+mod metrics;
 mod pmtable;
+mod profile;
+mod sections;
+mod sensors;
+mod smoothing;
 mod smu;
+mod source;
+mod thresholds;
+mod topology;
+mod tuning;
+mod units;
+mod warning;
+mod watchdog;
 
-pub use codename::Codename;
+pub use checksum::crc32;
+pub use codename::{core_ccd_position, Codename, PowerTier};
+pub use diff::{PmTableDiff, ValueDiff};
+pub use energy::EnergyMeter;
 pub use error::{Result, SmuError};
-pub use pmtable::{PmTable, MAX_CORES};
-pub use smu::SmuReader;
+pub use grouped::{CcdMetrics, CoreMetrics, GroupedPmTable};
+pub use hwmon::{fan_speeds, fan_speeds_from};
+pub use interval::{parse_interval, MIN_INTERVAL};
+pub use mailbox::{Mailbox, SMU_ARG_COUNT, SMU_STATUS_OK};
+pub use pmtable::{
+    parse_pm_table_version, pm_table_offsets, supported_pm_table_versions, supported_pm_tables, FieldAvailability, FreqSource, Limit, PmTable,
+    PmTableOffsets, ThrottleStatus, ACTIVE_C0_PERCENT, MAX_CORES, PARKED_FREQ_MHZ, THROTTLE_THRESHOLD,
+};
+pub use profile::{PboStatus, PowerProfile, PBO_MARGIN, TIER_TOLERANCE_W};
+pub use sections::OutputSections;
+pub use sensors::{Frequencies, PowerMetrics, Temperatures};
+pub use smoothing::{parse_smoothing, Smoother};
+pub use smu::{with_retry, SmuReader, PM_TABLE_REFRESH_FILE, READ_RETRIES, RETRY_DELAY, SYSFS_PATH_ENV};
+pub use source::{FileSource, MockSource, PmTableSource};
+pub use thresholds::{Level, Threshold, ThresholdConfig};
+pub use topology::{CoreLocation, CoreTopology};
+pub use tuning::{limit_command, settable_range, RiskAcknowledged};
+pub use units::{to_fahrenheit, TempUnit};
+pub use warning::Warning;
+pub use watchdog::{ReconnectingReader, VersionTracker, RECONNECT_MAX_BACKOFF, RECONNECT_MIN_BACKOFF};
 
+/// Library version, e.g. "0.1.0"
 pub fn version() -> &'static str {
     env!("CARGO_PKG_VERSION")
 }
+
+/// Version with the git commit and build date, e.g.
+/// "0.1.0 (abcdef1, 2024-06-01)", for bug reports
+///
+/// The commit is "unknown" when built outside a git checkout.
+pub fn full_version() -> &'static str {
+    concat!(env!("CARGO_PKG_VERSION"), " (", env!("AMD_SMU_GIT_HASH"), ", ", env!("AMD_SMU_BUILD_DATE"), ")")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_full_version_starts_with_semver() {
+        assert!(full_version().starts_with(&format!("{} (", version())));
+        assert!(full_version().ends_with(')'));
+    }
+}
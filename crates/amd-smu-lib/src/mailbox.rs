@@ -0,0 +1,50 @@
+use std::fmt;
+
+/// SMU mailbox a command is sent through
+///
+/// Which mailboxes exist, and which commands each accepts, depends on the
+/// processor family; ryzen_smu only creates the sysfs entries it supports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mailbox {
+    /// Remote SMU mailbox, used for most tuning commands
+    Rsmu,
+    /// MP1 mailbox
+    Mp1,
+    /// Host System Management Port (server parts)
+    Hsmp,
+}
+
+impl Mailbox {
+    /// Every mailbox ryzen_smu may expose
+    pub const ALL: [Mailbox; 3] = [Self::Rsmu, Self::Mp1, Self::Hsmp];
+
+    /// Name as AMD documents it, e.g. "RSMU"
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Rsmu => "RSMU",
+            Self::Mp1 => "MP1",
+            Self::Hsmp => "HSMP",
+        }
+    }
+
+    /// ryzen_smu sysfs file that takes commands for this mailbox
+    pub fn sysfs_name(&self) -> &'static str {
+        match self {
+            Self::Rsmu => "rsmu_cmd",
+            Self::Mp1 => "mp1_smu_cmd",
+            Self::Hsmp => "hsmp_smu_cmd",
+        }
+    }
+}
+
+impl fmt::Display for Mailbox {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Number of 32-bit argument slots in `smu_args`
+pub const SMU_ARG_COUNT: usize = 6;
+
+/// Status the driver reads back from a command file after a successful command
+pub const SMU_STATUS_OK: u32 = 0x01;
@@ -0,0 +1,48 @@
+use crate::PmTable;
+use std::collections::BTreeMap;
+
+impl PmTable {
+    /// Every reading as one flat, sorted map, for consumers that don't
+    /// want to know the struct's fields
+    ///
+    /// Scalars use their JSON field names (`tctl`, `ppt_value`, ...).
+    /// Per-core readings are `core.<N>.temp`, `.freq`, `.freq_eff`, `.power`, `.c0`, `.voltage`
+    /// and `.fit`, one key per core the layout provides.
+    pub fn as_metric_map(&self) -> BTreeMap<String, f32> {
+        let mut map: BTreeMap<String, f32> = [
+            ("ppt_limit", self.ppt_limit),
+            ("tdc_limit", self.tdc_limit),
+            ("edc_limit", self.edc_limit),
+            ("thm_limit", self.thm_limit),
+            ("ppt_value", self.ppt_value),
+            ("tdc_value", self.tdc_value),
+            ("edc_value", self.edc_value),
+            ("tctl", self.tctl),
+            ("soc_temp", self.soc_temp),
+            ("fclk", self.fclk),
+            ("mclk", self.mclk),
+            ("package_power", self.package_power),
+            ("soc_power", self.soc_power),
+            ("core_voltage", self.core_voltage),
+            ("soc_voltage", self.soc_voltage),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect();
+
+        for (name, values) in [
+            ("temp", &self.core_temps),
+            ("freq", &self.core_freqs),
+            ("freq_eff", &self.core_freqs_eff),
+            ("power", &self.core_power),
+            ("c0", &self.core_c0),
+            ("voltage", &self.core_voltages),
+            ("fit", &self.core_fit),
+        ] {
+            for (core, &value) in values.iter().enumerate() {
+                map.insert(format!("core.{}.{}", core, name), value);
+            }
+        }
+        map
+    }
+}
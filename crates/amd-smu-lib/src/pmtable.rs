@@ -1,23 +1,62 @@
-use byteorder::{LittleEndian, ReadBytesExt};
-use std::io::Cursor;
-use crate::{Result, SmuError};
-use crate::Codename;
-use serde::Serialize;
+use crate::{Result, SmuError, Warning};
+use crate::{core_ccd_position, Codename};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Cow;
+use std::fmt;
+use std::ops::{Range, RangeInclusive};
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::time::SystemTime;
 
-/// Maximum number of cores supported
-pub const MAX_CORES: usize = 16;
+/// Most per-core entries a layout may hold (96-core Threadripper PRO)
+///
+/// Each offset table carries its own `max_cores`, which is what bounds
+/// per-core parsing; this is the ceiling for all of them, including
+/// `--offsets` files for parts without a built-in layout.
+pub const MAX_CORES: usize = 96;
+
+/// Where per-core clocks and the core count come from when the PM table
+/// doesn't say
+pub(crate) const CPUINFO_PATH: &str = "/proc/cpuinfo";
+
+// Plausible ranges for PM table readings; anything outside them comes from
+// an offset landing in uninitialized memory rather than a real sensor
+const TEMP_RANGE: RangeInclusive<f32> = -50.0..=150.0;
+const POWER_RANGE: RangeInclusive<f32> = 0.0..=1000.0;
+const CURRENT_RANGE: RangeInclusive<f32> = 0.0..=1000.0;
+const FREQ_RANGE: RangeInclusive<f32> = 0.0..=8000.0;
+const VOLTAGE_RANGE: RangeInclusive<f32> = 0.0..=2.0;
+const PERCENT_RANGE: RangeInclusive<f32> = 0.0..=100.0;
+// Wider than TEMP_RANGE: Granite Ridge reports a 200°C thermal limit
+const THM_LIMIT_RANGE: RangeInclusive<f32> = 0.0..=255.0;
+// Per-core power sum as a share of the package's core power. Cores draw
+// a varying part of the rail (the rest is L3 and leakage), so only sums far
+// outside it point at wrong offsets; below CORE_POWER_CHECK_MIN watts the
+// rail is mostly uncore and isn't checked
+const CORE_POWER_SUM_RATIO: RangeInclusive<f32> = 0.05..=2.0;
+const CORE_POWER_CHECK_MIN: f32 = 10.0;
 
 /// PM Table data parsed from the kernel module
-#[derive(Debug, Clone, Serialize)]
+///
+/// Serializes to JSON and loads back, so tables can be recorded and
+/// replayed without hardware. `codename` is rebuilt from its name.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+// The derives become inherent functions, wrapped by the trait impls below
+#[serde(remote = "Self")]
 pub struct PmTable {
     /// PM table format version
     pub version: u32,
+    /// When `SmuReader` read this table; None for tables parsed directly.
+    /// Unix milliseconds in JSON
+    #[serde(default, with = "unix_millis", skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<SystemTime>,
     /// Processor codename
     #[serde(skip)]
     pub codename: Codename,
     /// Codename as string for JSON
     #[serde(rename = "codename")]
-    pub codename_str: String,
+    pub codename_str: Cow<'static, str>,
 
     // Limits
     /// Package Power Tracking limit (W)
@@ -50,6 +89,9 @@ pub struct PmTable {
     pub core_freqs: Vec<f32>,
     /// Per-core effective frequencies
     pub core_freqs_eff: Vec<f32>,
+    /// Where the per-core frequencies came from
+    #[serde(default)]
+    pub freq_source: FreqSource,
     /// Fabric clock
     pub fclk: f32,
     /// Memory clock
@@ -66,18 +108,42 @@ pub struct PmTable {
     // Voltages (V) and residency (%)
     /// Core voltage
     pub core_voltage: f32,
+    /// Per-core voltage; empty if the layout doesn't have it
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub core_voltages: Vec<f32>,
     /// SoC voltage
     pub soc_voltage: f32,
     /// Per-core C0 residency (%)
     pub core_c0: Vec<f32>,
+    /// Per-core fit (silicon quality rating firmware uses for boost and
+    /// Curve Optimizer margins); empty if the layout doesn't have it
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub core_fit: Vec<f32>,
+
+    /// Which per-core readings the layout has; see `available_fields`
+    #[serde(skip)]
+    pub availability: FieldAvailability,
+    /// Whether `package_power` already includes the SoC, per the layout;
+    /// see `total_power`
+    #[serde(skip)]
+    pub cpu_power_includes_soc: Option<bool>,
+
+    /// Fields a lenient parse couldn't read from a truncated table
+    #[serde(default, skip_serializing_if = "Vec::is_empty", deserialize_with = "deserialize_field_names")]
+    pub missing_fields: Vec<&'static str>,
+
+    /// Non-fatal problems noticed while reading this table
+    #[serde(skip)]
+    pub warnings: Vec<String>,
 }
 
 impl Default for PmTable {
     fn default() -> Self {
         Self {
             version: 0,
+            timestamp: None,
             codename: Codename::Unsupported,
-            codename_str: String::new(),
+            codename_str: Cow::Borrowed(""),
             ppt_limit: 0.0,
             tdc_limit: 0.0,
             edc_limit: 0.0,
@@ -90,22 +156,160 @@ impl Default for PmTable {
             core_temps: Vec::new(),
             core_freqs: Vec::new(),
             core_freqs_eff: Vec::new(),
+            freq_source: FreqSource::PmTable,
             fclk: 0.0,
             mclk: 0.0,
             core_power: Vec::new(),
             package_power: 0.0,
             soc_power: 0.0,
             core_voltage: 0.0,
+            core_voltages: Vec::new(),
             soc_voltage: 0.0,
             core_c0: Vec::new(),
+            core_fit: Vec::new(),
+            availability: FieldAvailability::default(),
+            cpu_power_includes_soc: None,
+            missing_fields: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+}
+
+pub use offsets::PmTableOffsets;
+
+/// Byte offsets this library parses PM table `version` with, or None if
+/// the version has no layout
+pub fn pm_table_offsets(version: u32) -> Option<PmTableOffsets> {
+    offsets::get_offsets(version)
+}
+
+impl PmTableOffsets {
+    /// Read a layout from a JSON (`.json`) or TOML (anything else) file
+    ///
+    /// For hardware this library has no layout for yet. Every field must be
+    /// present except `power_scale` and `current_scale`, which default to
+    /// 1.0; readings the table doesn't have take the 0xFFFF marker. Fails
+    /// with `InvalidConfig` on a missing or unknown field, a `max_cores`
+    /// outside `1..=MAX_CORES` or a scale that isn't positive.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let invalid = |message: String| SmuError::InvalidConfig { path: path.to_path_buf(), message };
+        let text = fs::read_to_string(path).map_err(|e| invalid(e.to_string()))?;
+        let offsets: Self = if path.extension().is_some_and(|ext| ext == "json") {
+            serde_json::from_str(&text).map_err(|e| invalid(e.to_string()))?
+        } else {
+            toml::from_str(&text).map_err(|e| invalid(e.message().to_string()))?
+        };
+        if !(1..=MAX_CORES).contains(&offsets.max_cores) {
+            return Err(invalid(format!("max_cores {} is outside 1 to {}", offsets.max_cores, MAX_CORES)));
+        }
+        for (name, scale) in [("power_scale", offsets.power_scale), ("current_scale", offsets.current_scale)] {
+            if !(scale.is_finite() && scale > 0.0) {
+                return Err(invalid(format!("{} {} is not a positive number", name, scale)));
+            }
+        }
+        Ok(offsets)
+    }
+}
+
+/// PM table versions this library can parse, in ascending order
+pub fn supported_pm_table_versions() -> &'static [u32] {
+    offsets::SUPPORTED_VERSIONS
+}
+
+/// Supported PM table versions, each with the processors known to report
+/// it (see `Codename::known_pm_table_versions`)
+pub fn supported_pm_tables() -> Vec<(u32, Vec<Codename>)> {
+    supported_pm_table_versions()
+        .iter()
+        .map(|&version| (version, Codename::reporting_pm_table_version(version)))
+        .collect()
+}
+
+/// Parse a PM table version written as text, as some driver builds expose
+/// it and as users type it
+///
+/// Accepts hex with a `0x`/`0X` prefix (leading zeros are fine, so
+/// `0x00620205` and `0x620205` are the same version) or plain decimal,
+/// ignoring surrounding whitespace.
+pub fn parse_pm_table_version(s: &str) -> Option<u32> {
+    let s = s.trim();
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+}
+
+impl Serialize for PmTable {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        PmTable::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for PmTable {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let mut table = PmTable::deserialize(deserializer)?;
+        table.codename = table.codename_str.parse().unwrap_or_default();
+        if table.codename != Codename::Unsupported {
+            table.codename_str = Cow::Borrowed(table.codename.as_str());
+        }
+        // Recordings don't carry it; the version's layout is the best guess
+        table.availability = FieldAvailability::for_version(table.version);
+        table.cpu_power_includes_soc = offsets::get_offsets(table.version).and_then(|off| off.cpu_power_includes_soc);
+        Ok(table)
+    }
+}
+
+/// `Option<SystemTime>` as Unix milliseconds
+pub(crate) mod unix_millis {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    pub fn serialize<S: Serializer>(time: &Option<SystemTime>, serializer: S) -> Result<S::Ok, S::Error> {
+        match time {
+            Some(time) => {
+                let millis = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as u64);
+                serializer.serialize_some(&millis)
+            }
+            None => serializer.serialize_none(),
         }
     }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<SystemTime>, D::Error> {
+        Ok(Option::<u64>::deserialize(deserializer)?.map(|millis| UNIX_EPOCH + Duration::from_millis(millis)))
+    }
+}
+
+/// Names that can appear in `missing_fields`
+const FIELD_NAMES: &[&str] = &[
+    "ppt_limit", "ppt_value", "tdc_limit", "tdc_value", "thm_limit", "tctl", "edc_limit",
+    "edc_value", "package_power", "soc_power", "core_voltage", "soc_temp", "soc_voltage", "fclk",
+    "mclk", "core_power", "core_temps", "core_freqs", "core_freqs_eff", "core_c0", "core_voltages",
+    "core_fit",
+];
+
+/// Map field names back onto their static strings, dropping unknown ones
+fn deserialize_field_names<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Vec<&'static str>, D::Error> {
+    let names = Vec::<String>::deserialize(deserializer)?;
+    Ok(names
+        .iter()
+        .filter_map(|name| FIELD_NAMES.iter().find(|known| *known == name).copied())
+        .collect())
 }
 
 /// PM table offset definitions for different processor generations
 mod offsets {
+    use super::FieldAvailability;
+    use serde::Deserialize;
+
     /// Offset structure for PM table fields
-    #[derive(Debug, Clone, Copy)]
+    ///
+    /// Deserializable so a layout can come from a file (see
+    /// `PmTableOffsets::load`); every field is required.
+    #[derive(Debug, Clone, Copy, Deserialize)]
+    #[serde(deny_unknown_fields)]
     pub struct PmTableOffsets {
         pub ppt_limit: usize,
         pub ppt_value: usize,
@@ -117,6 +321,10 @@ mod offsets {
         pub edc_value: usize,
         pub cpu_power: usize,     // Package/CPU power
         pub soc_power: usize,
+        /// Whether `cpu_power` is socket power with the SoC included; None
+        /// until a capture settles it (and when an offsets file leaves it out)
+        #[serde(default)]
+        pub cpu_power_includes_soc: Option<bool>,
         pub cpu_voltage: usize,
         pub soc_voltage: usize,
         pub fclk: usize,
@@ -127,9 +335,26 @@ mod offsets {
         pub core_freq_base: usize,
         pub core_freqeff_base: usize,
         pub core_c0_base: usize,
+        pub core_voltage_base: usize,
+        pub core_fit_base: usize,  // Per-core fit
         pub max_cores: usize,
+        /// Board telemetry calibration: multiplies `ppt_value`,
+        /// `package_power`, `soc_power` and `core_power`
+        #[serde(default = "unit_scale")]
+        pub power_scale: f32,
+        /// Multiplies `tdc_value` and `edc_value`; limits, temperatures,
+        /// clocks and voltages are never scaled
+        #[serde(default = "unit_scale")]
+        pub current_scale: f32,
     }
 
+    fn unit_scale() -> f32 {
+        1.0
+    }
+
+    /// Per-core capacity of the desktop layouts (two 8-core CCDs)
+    const DESKTOP_CORES: usize = 16;
+
     /// PM table offsets for version 0x240903 (Matisse/Vermeer - Zen 2/3)
     pub const OFFSETS_0X240903: PmTableOffsets = PmTableOffsets {
         ppt_limit: 0x000,
@@ -142,6 +367,7 @@ mod offsets {
         edc_value: 0x024,
         cpu_power: 0x060,
         soc_power: 0x064,
+        cpu_power_includes_soc: Some(false), // VDDCR_CPU, the core rail
         cpu_voltage: 0x0A0,
         soc_voltage: 0x0B4,
         fclk: 0x0C0,
@@ -152,10 +378,14 @@ mod offsets {
         core_freq_base: 0x2EC,
         core_freqeff_base: 0x30C,
         core_c0_base: 0x32C,
-        max_cores: 16,
+        core_voltage_base: 0xFFFF, // Not located yet
+        core_fit_base: 0xFFFF,    // Not located yet
+        max_cores: DESKTOP_CORES,
+        power_scale: 1.0,
+        current_scale: 1.0,
     };
 
-    /// PM table offsets for version 0x00620205 (Granite Ridge - Zen 5)
+    /// PM table offsets for version 0x620205 (Granite Ridge - Zen 5)
     /// Reverse-engineered from actual PM table data on 9950X3D
     /// Note: Per-core frequencies not available in PM table, use /proc/cpuinfo instead
     pub const OFFSETS_0X620205: PmTableOffsets = PmTableOffsets {
@@ -169,6 +399,7 @@ mod offsets {
         edc_value: 0x100,         // Current EDC
         cpu_power: 0x024,         // Same as ppt_value (package power)
         soc_power: 0x054,         // SoC power ~18W
+        cpu_power_includes_soc: Some(true), // PPT covers the whole socket
         cpu_voltage: 0x048,       // ~1.36V
         soc_voltage: 0x04C,       // ~1.22V
         fclk: 0x11C,              // 2000 MHz
@@ -179,14 +410,72 @@ mod offsets {
         core_freq_base: 0xFFFF,   // Not available in PM table - use 0xFFFF as marker
         core_freqeff_base: 0xFFFF, // Not available in PM table
         core_c0_base: 0xFFFF,     // Not available in PM table
-        max_cores: 16,
+        core_voltage_base: 0xFFFF, // Not located yet
+        core_fit_base: 0xFFFF,    // Not located yet
+        max_cores: DESKTOP_CORES,
+        power_scale: 1.0,
+        current_scale: 1.0,
     };
 
+    /// 0xFFFF in a field means the layout doesn't have that reading
+    const MARKER: usize = 0xFFFF;
+
+    impl PmTableOffsets {
+        /// Every reading the layout locates, by its `PmTable::as_metric_map`
+        /// key, in table order; per-core arrays appear as their first core
+        /// (`core.0.temp`, ...)
+        pub fn named(&self) -> Vec<(&'static str, usize)> {
+            let mut named = vec![
+                ("ppt_limit", self.ppt_limit),
+                ("ppt_value", self.ppt_value),
+                ("tdc_limit", self.tdc_limit),
+                ("tdc_value", self.tdc_value),
+                ("thm_limit", self.thm_limit),
+                ("tctl", self.thm_value),
+                ("edc_limit", self.edc_limit),
+                ("edc_value", self.edc_value),
+                ("package_power", self.cpu_power),
+                ("soc_power", self.soc_power),
+                ("core_voltage", self.cpu_voltage),
+                ("soc_voltage", self.soc_voltage),
+                ("fclk", self.fclk),
+                ("mclk", self.mclk),
+                ("soc_temp", self.soc_temp),
+                ("core.0.power", self.core_power_base),
+                ("core.0.temp", self.core_temp_base),
+                ("core.0.freq", self.core_freq_base),
+                ("core.0.freq_eff", self.core_freqeff_base),
+                ("core.0.c0", self.core_c0_base),
+                ("core.0.voltage", self.core_voltage_base),
+                ("core.0.fit", self.core_fit_base),
+            ];
+            named.retain(|&(_, offset)| offset != MARKER);
+            named.sort_by_key(|&(_, offset)| offset);
+            named
+        }
+
+        /// Which per-core readings this layout locates
+        pub fn availability(&self) -> FieldAvailability {
+            FieldAvailability {
+                core_temps: self.core_temp_base != MARKER,
+                core_freqs: self.core_freq_base != MARKER,
+                core_freqs_eff: self.core_freqeff_base != MARKER,
+                core_power: self.core_power_base != MARKER,
+                core_c0: self.core_c0_base != MARKER,
+                core_voltages: self.core_voltage_base != MARKER,
+                core_fit: self.core_fit_base != MARKER,
+            }
+        }
+    }
+
+    /// Versions with a layout below, in ascending order
+    pub const SUPPORTED_VERSIONS: &[u32] = &[0x240903, 0x620205];
+
     /// Get the appropriate offsets for a given PM table version
     pub fn get_offsets(version: u32) -> Option<PmTableOffsets> {
         match version {
             0x240903 => Some(OFFSETS_0X240903),
-            0x00620205 => Some(OFFSETS_0X620205),
+            0x620205 => Some(OFFSETS_0X620205),
             _ => None,
         }
     }
@@ -195,10 +484,53 @@ mod offsets {
 impl PmTable {
     /// Parse PM table from raw bytes
     pub fn parse(data: &[u8], version: u32, codename: Codename, core_count: usize) -> Result<Self> {
-        // Get offsets for this PM table version
-        let off = offsets::get_offsets(version).ok_or_else(|| {
-            SmuError::UnsupportedPmTableVersion(version)
-        })?;
+        let off = layout(version)?;
+        Self::parse_inner(data, &off, version, codename, core_count, false, Some(Path::new(CPUINFO_PATH)))
+            .map(with_warning_text)
+    }
+
+    /// Parse with `offsets` instead of the layout for `version`
+    ///
+    /// For tables this library has no layout for, with offsets from e.g.
+    /// `PmTableOffsets::load`. `version` is only recorded in the result.
+    pub fn parse_with_offsets(
+        data: &[u8],
+        offsets: &PmTableOffsets,
+        version: u32,
+        codename: Codename,
+        core_count: usize,
+    ) -> Result<Self> {
+        Self::parse_inner(data, offsets, version, codename, core_count, false, Some(Path::new(CPUINFO_PATH)))
+            .map(with_warning_text)
+    }
+
+    /// Parse whatever fields fit in a possibly truncated PM table
+    ///
+    /// Fields past the end of `data` read as 0.0 and are listed in
+    /// `missing_fields` instead of failing the whole parse. An unsupported
+    /// version is still an error.
+    pub fn parse_lenient(data: &[u8], version: u32, codename: Codename, core_count: usize) -> Result<Self> {
+        let off = layout(version)?;
+        Self::parse_inner(data, &off, version, codename, core_count, true, Some(Path::new(CPUINFO_PATH)))
+            .map(with_warning_text)
+    }
+
+    /// Parse with `off`, returning the warnings separately rather than in
+    /// `warnings`
+    ///
+    /// Layouts without per-core clocks take them from `cpuinfo`; None
+    /// leaves them unavailable, for tables captured on another machine.
+    pub(crate) fn parse_inner(
+        data: &[u8],
+        off: &PmTableOffsets,
+        version: u32,
+        codename: Codename,
+        core_count: usize,
+        lenient: bool,
+        cpuinfo: Option<&Path>,
+    ) -> Result<(Self, Vec<Warning>)> {
+        // Per-core data is bounded by the layout, not by a global constant
+        let actual_cores = core_count.min(off.max_cores);
 
         // Minimum size check based on the largest per-core offset (excluding 0xFFFF markers)
         let max_per_core_base = [
@@ -207,12 +539,14 @@ impl PmTable {
             off.core_temp_base,
             off.core_freq_base,
             off.core_freqeff_base,
+            off.core_voltage_base,
+            off.core_fit_base,
         ].into_iter()
             .filter(|&x| x < 0xFFFF)  // Exclude marker values
             .max()
             .unwrap_or(0);
-        let min_size = max_per_core_base + (core_count * 4);
-        if data.len() < min_size {
+        let min_size = max_per_core_base + (actual_cores * 4);
+        if !lenient && data.len() < min_size {
             return Err(SmuError::InvalidPmTableSize {
                 expected: min_size,
                 actual: data.len(),
@@ -222,35 +556,77 @@ impl PmTable {
         let mut table = PmTable {
             version,
             codename,
-            codename_str: codename.to_string(),
+            codename_str: Cow::Borrowed(codename.as_str()),
+            core_temps: Vec::with_capacity(actual_cores),
+            core_freqs: Vec::with_capacity(actual_cores),
+            core_freqs_eff: Vec::with_capacity(actual_cores),
+            core_power: Vec::with_capacity(actual_cores),
+            core_c0: Vec::with_capacity(actual_cores),
+            core_voltages: Vec::with_capacity(actual_cores),
+            core_fit: Vec::with_capacity(actual_cores),
+            availability: off.availability(),
+            cpu_power_includes_soc: off.cpu_power_includes_soc,
             ..Default::default()
         };
 
+        // Strict parsing fails on the first field out of range; lenient
+        // parsing records it and carries on
+        let mut warnings = Vec::new();
+        if core_count > off.max_cores {
+            warnings.push(Warning::CoresBeyondLayout { requested: core_count, max_cores: off.max_cores });
+        }
+        let mut missing = Vec::new();
+        let mut scalar = |name: &'static str, offset: usize| -> Result<f32> {
+            match read_f32(data, offset) {
+                Err(SmuError::InvalidPmTableSize { .. }) if lenient => {
+                    missing.push(name);
+                    Ok(0.0)
+                }
+                result => result,
+            }
+        };
+
         // Parse limits
-        table.ppt_limit = read_f32(data, off.ppt_limit)?;
-        table.ppt_value = read_f32(data, off.ppt_value)?;
-        table.tdc_limit = read_f32(data, off.tdc_limit)?;
-        table.tdc_value = read_f32(data, off.tdc_value)?;
-        table.thm_limit = read_f32(data, off.thm_limit)?;
-        table.tctl = read_f32(data, off.thm_value)?;
-        table.edc_limit = read_f32(data, off.edc_limit)?;
-        table.edc_value = read_f32(data, off.edc_value)?;
+        table.ppt_limit = scalar("ppt_limit", off.ppt_limit)?;
+        table.ppt_value = scalar("ppt_value", off.ppt_value)?;
+        table.tdc_limit = scalar("tdc_limit", off.tdc_limit)?;
+        table.tdc_value = scalar("tdc_value", off.tdc_value)?;
+        table.thm_limit = scalar("thm_limit", off.thm_limit)?;
+        table.tctl = scalar("tctl", off.thm_value)?;
+        table.edc_limit = scalar("edc_limit", off.edc_limit)?;
+        table.edc_value = scalar("edc_value", off.edc_value)?;
 
         // Parse power
-        table.package_power = read_f32(data, off.cpu_power)?;
-        table.soc_power = read_f32(data, off.soc_power)?;
+        table.package_power = scalar("package_power", off.cpu_power)?;
+        table.soc_power = scalar("soc_power", off.soc_power)?;
 
         // Parse voltages and temps
-        table.core_voltage = read_f32(data, off.cpu_voltage)?;
-        table.soc_temp = read_f32(data, off.soc_temp)?;
-        table.soc_voltage = read_f32(data, off.soc_voltage)?;
+        table.core_voltage = scalar("core_voltage", off.cpu_voltage)?;
+        table.soc_temp = scalar("soc_temp", off.soc_temp)?;
+        table.soc_voltage = scalar("soc_voltage", off.soc_voltage)?;
 
         // Parse clocks
-        table.fclk = read_f32(data, off.fclk)?;
-        table.mclk = read_f32(data, off.mclk)?;
+        table.fclk = scalar("fclk", off.fclk)?;
+        table.mclk = scalar("mclk", off.mclk)?;
+
+        // Per-core arrays that don't fit are only reachable in lenient mode
+        let per_core = [
+            ("core_power", off.core_power_base),
+            ("core_temps", off.core_temp_base),
+            ("core_freqs", off.core_freq_base),
+            ("core_freqs_eff", off.core_freqeff_base),
+            ("core_c0", off.core_c0_base),
+            ("core_voltages", off.core_voltage_base),
+            ("core_fit", off.core_fit_base),
+        ];
+        for (name, base) in per_core {
+            if base < 0xFFFF && base + actual_cores * 4 > data.len() {
+                missing.push(name);
+            }
+        }
+        table.missing_fields = missing;
 
         // Parse per-core data (limit to actual core count and available data)
-        let actual_cores = core_count.min(off.max_cores);
         for i in 0..actual_cores {
             // Safely read per-core data, using 0.0 if offset is 0xFFFF (not available) or out of bounds
             let power_off = off.core_power_base + i * 4;
@@ -271,57 +647,604 @@ impl PmTable {
                 let c0_off = off.core_c0_base + i * 4;
                 table.core_c0.push(read_f32_safe_with_marker(data, c0_off));
             }
+
+            if off.core_voltage_base != 0xFFFF {
+                let voltage_off = off.core_voltage_base + i * 4;
+                table.core_voltages.push(read_f32_safe_with_marker(data, voltage_off));
+            }
+
+            if off.core_fit_base != 0xFFFF {
+                table.core_fit.push(read_f32_safe_with_marker(data, off.core_fit_base + i * 4));
+            }
         }
 
         // If frequencies are not in PM table, try to read from /proc/cpuinfo
         if off.core_freq_base == 0xFFFF {
-            if let Ok(freqs) = read_cpuinfo_frequencies(actual_cores) {
-                table.core_freqs = freqs.clone();
-                table.core_freqs_eff = freqs;
+            table.freq_source = FreqSource::Unavailable;
+            match cpuinfo.map(|path| (path, fs::read_to_string(path))) {
+                Some((_, Ok(cpuinfo))) => {
+                    // Padded to the core count, so only readings count
+                    let freqs = parse_cpuinfo_frequencies(&cpuinfo, actual_cores);
+                    if freqs.iter().any(|f| *f > 0.0) {
+                        table.freq_source = FreqSource::Cpuinfo;
+                        table.core_freqs = freqs.clone();
+                        table.core_freqs_eff = freqs;
+                    }
+                }
+                Some((path, Err(e))) => warnings.push(Warning::CoreClocksUnavailable {
+                    path: path.to_path_buf(),
+                    error: e.to_string(),
+                }),
+                None => {}
             }
         }
 
-        Ok(table)
+        table.scale_telemetry(off.power_scale, off.current_scale);
+        table.validate_into(&mut warnings);
+        Ok((table, warnings))
+    }
+
+    /// Apply a board's telemetry calibration to the power and current
+    /// readings (see `PmTableOffsets::power_scale`)
+    fn scale_telemetry(&mut self, power: f32, current: f32) {
+        let watts = [&mut self.ppt_value, &mut self.package_power, &mut self.soc_power]
+            .into_iter()
+            .chain(self.core_power.iter_mut());
+        for value in watts {
+            *value *= power;
+        }
+        self.tdc_value *= current;
+        self.edc_value *= current;
+    }
+
+    /// Reset physically impossible readings to 0.0, with a warning each
+    ///
+    /// Runs as part of parsing. 0.0 is the same "unavailable" value used
+    /// for fields the layout doesn't have, so consumers need no extra case.
+    pub fn validate(&mut self) {
+        let mut warnings = Vec::new();
+        self.validate_into(&mut warnings);
+        self.warnings.extend(warnings.iter().map(ToString::to_string));
+    }
+
+    fn validate_into(&mut self, warnings: &mut Vec<Warning>) {
+        check_value(warnings, "ppt_limit", &mut self.ppt_limit, POWER_RANGE);
+        check_value(warnings, "tdc_limit", &mut self.tdc_limit, CURRENT_RANGE);
+        check_value(warnings, "edc_limit", &mut self.edc_limit, CURRENT_RANGE);
+        check_value(warnings, "thm_limit", &mut self.thm_limit, THM_LIMIT_RANGE);
+        check_value(warnings, "ppt_value", &mut self.ppt_value, POWER_RANGE);
+        check_value(warnings, "tdc_value", &mut self.tdc_value, CURRENT_RANGE);
+        check_value(warnings, "edc_value", &mut self.edc_value, CURRENT_RANGE);
+        check_value(warnings, "tctl", &mut self.tctl, TEMP_RANGE);
+        check_value(warnings, "soc_temp", &mut self.soc_temp, TEMP_RANGE);
+        check_value(warnings, "fclk", &mut self.fclk, FREQ_RANGE);
+        check_value(warnings, "mclk", &mut self.mclk, FREQ_RANGE);
+        check_value(warnings, "package_power", &mut self.package_power, POWER_RANGE);
+        check_value(warnings, "soc_power", &mut self.soc_power, POWER_RANGE);
+        check_value(warnings, "core_voltage", &mut self.core_voltage, VOLTAGE_RANGE);
+        check_value(warnings, "soc_voltage", &mut self.soc_voltage, VOLTAGE_RANGE);
+        check_values(warnings, "core_temps", &mut self.core_temps, TEMP_RANGE);
+        check_values(warnings, "core_freqs", &mut self.core_freqs, FREQ_RANGE);
+        check_values(warnings, "core_freqs_eff", &mut self.core_freqs_eff, FREQ_RANGE);
+        check_values(warnings, "core_power", &mut self.core_power, POWER_RANGE);
+        check_values(warnings, "core_c0", &mut self.core_c0, PERCENT_RANGE);
+        check_values(warnings, "core_voltages", &mut self.core_voltages, VOLTAGE_RANGE);
+
+        let sum = self.core_power_sum();
+        let package = self.package_core_power();
+        if sum > 0.0 && package >= CORE_POWER_CHECK_MIN && !CORE_POWER_SUM_RATIO.contains(&(sum / package)) {
+            warnings.push(Warning::CorePowerMismatch { sum, package });
+        }
+    }
+
+    /// Parse a PM table blob previously dumped to a file
+    ///
+    /// Lets captures from other machines be decoded without the kernel
+    /// module loaded. The capture may not come from this machine, so
+    /// layouts without per-core clocks leave them unavailable rather than
+    /// taking this machine's /proc/cpuinfo.
+    pub fn parse_from_file<P: AsRef<Path>>(
+        path: P,
+        version: u32,
+        codename: Codename,
+        core_count: usize,
+    ) -> Result<Self> {
+        let data = fs::read(path)?;
+        Self::parse_inner(&data, &layout(version)?, version, codename, core_count, false, None).map(with_warning_text)
+    }
+
+    /// Parse a PM table from any reader, e.g. a decompressor or a socket
+    ///
+    /// Offsets are random access, so the reader is drained into memory
+    /// first; the result is the same as `parse` on those bytes.
+    pub fn parse_reader<R: Read>(mut reader: R, version: u32, codename: Codename, core_count: usize) -> Result<Self> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        Self::parse(&data, version, codename, core_count)
+    }
+}
+
+/// A limit the SMU enforces on the package
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    /// Package Power Tracking
+    Ppt,
+    /// Thermal Design Current
+    Tdc,
+    /// Electrical Design Current
+    Edc,
+    /// Junction temperature limit
+    Thermal,
+}
+
+impl Limit {
+    pub const ALL: [Limit; 4] = [Limit::Ppt, Limit::Tdc, Limit::Edc, Limit::Thermal];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ppt => "PPT",
+            Self::Tdc => "TDC",
+            Self::Edc => "EDC",
+            Self::Thermal => "Thermal",
+        }
+    }
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Utilization at or above which a limit is considered to be holding the CPU back
+pub const THROTTLE_THRESHOLD: f32 = 0.95;
+
+/// Where a table's per-core frequencies came from
+///
+/// The PM table's clocks are the SMU's effective clocks; the cpuinfo
+/// fallback is the kernel's instantaneous view, which reads differently
+/// under load and idle.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FreqSource {
+    /// The PM table; also assumed for recordings made before this was tracked
+    #[default]
+    PmTable,
+    /// /proc/cpuinfo, for layouts without per-core clocks
+    Cpuinfo,
+    /// Neither the layout nor cpuinfo had them
+    Unavailable,
+}
+
+/// Which per-core readings a table's layout has
+///
+/// An empty per-core list can mean no cores were read or a layout without
+/// that reading; only the latter is false here. Per-core clocks filled in
+/// from /proc/cpuinfo still count as unavailable (see `FreqSource`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FieldAvailability {
+    pub core_temps: bool,
+    pub core_freqs: bool,
+    pub core_freqs_eff: bool,
+    pub core_power: bool,
+    pub core_c0: bool,
+    pub core_voltages: bool,
+    pub core_fit: bool,
+}
+
+impl FieldAvailability {
+    /// What the layout for `version` has; nothing for unknown versions
+    pub fn for_version(version: u32) -> Self {
+        offsets::get_offsets(version).map(|off| off.availability()).unwrap_or_default()
+    }
+}
+
+/// Which limit, if any, is currently holding the CPU back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThrottleStatus {
+    /// Every limit is below `THROTTLE_THRESHOLD`
+    Unconstrained,
+    /// The closest limit is at or above `THROTTLE_THRESHOLD`
+    Limited(Limit),
+}
+
+impl ThrottleStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Unconstrained => "None",
+            Self::Limited(limit) => limit.as_str(),
+        }
+    }
+}
+
+impl fmt::Display for ThrottleStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Effective frequency (MHz) below which a present core is considered parked
+pub const PARKED_FREQ_MHZ: f32 = 50.0;
+/// C0 residency (%) above which a core counts as active
+pub const ACTIVE_C0_PERCENT: f32 = 5.0;
+
+impl PmTable {
+    /// Hottest per-core temperature, ignoring unpopulated (0.0) cores
+    pub fn max_core_temp(&self) -> f32 {
+        max_nonzero(&self.core_temps)
+    }
+
+    /// Hottest minus coolest populated core (°C), 0.0 with fewer than two
+    ///
+    /// A wide spread under load points at uneven cooling, e.g. a poorly
+    /// mounted cooler or dried-out paste.
+    pub fn core_temp_spread(&self) -> f32 {
+        let temps = populated(&self.core_temps);
+        if temps.len() < 2 {
+            return 0.0;
+        }
+        let (min, max) = temps.iter().fold((f32::MAX, f32::MIN), |(lo, hi), t| (lo.min(*t), hi.max(*t)));
+        max - min
+    }
+
+    /// Standard deviation of the populated cores' temperatures (°C), 0.0
+    /// with fewer than two
+    pub fn core_temp_stddev(&self) -> f32 {
+        let temps = populated(&self.core_temps);
+        if temps.len() < 2 {
+            return 0.0;
+        }
+        let mean = temps.iter().sum::<f32>() / temps.len() as f32;
+        (temps.iter().map(|t| (t - mean).powi(2)).sum::<f32>() / temps.len() as f32).sqrt()
+    }
+
+    /// Package plus SoC power (W), counting the SoC once
+    ///
+    /// On 0x240903 package power is the core rail alone and the SoC is
+    /// added; on 0x620205 it is the PPT reading, which already covers the
+    /// whole socket, so it is returned as is. Goes by the layout the table
+    /// was parsed with, so `--offsets` files and forced layouts count. None
+    /// when that layout doesn't say which, or when the SoC reading that
+    /// would be added is unavailable.
+    pub fn total_power(&self) -> Option<f32> {
+        match self.cpu_power_includes_soc? {
+            true => Some(self.package_power),
+            false => (self.soc_power > 0.0).then_some(self.package_power + self.soc_power),
+        }
+    }
+
+    /// Which per-core readings this table's layout has, as recorded when
+    /// it was parsed (under a forced layout, that layout's)
+    pub fn available_fields(&self) -> FieldAvailability {
+        self.availability
+    }
+
+    /// Package power less the SoC where the layout counts it in (W)
+    ///
+    /// What `core_power_sum` is checked against; the package reading as is
+    /// when the layout doesn't say.
+    pub fn package_core_power(&self) -> f32 {
+        match self.cpu_power_includes_soc {
+            Some(true) => self.package_power - self.soc_power,
+            _ => self.package_power,
+        }
+    }
+
+    /// Summed per-core power (W)
+    ///
+    /// Roughly `package_core_power`; parsing warns when it is far off,
+    /// which usually means wrong per-core offsets.
+    pub fn core_power_sum(&self) -> f32 {
+        // An empty f32 sum is -0.0
+        populated(&self.core_power).iter().fold(0.0, |sum, p| sum + p)
+    }
+
+    /// Highest per-core frequency, ignoring unavailable (0.0) cores
+    pub fn max_core_freq(&self) -> f32 {
+        max_nonzero(&self.core_freqs)
+    }
+
+    /// Mean per-core frequency, ignoring unavailable (0.0) cores
+    pub fn avg_core_freq(&self) -> f32 {
+        mean_nonzero(&self.core_freqs)
+    }
+
+    /// Highest per-core effective frequency, ignoring unavailable (0.0) cores
+    pub fn max_core_freq_eff(&self) -> f32 {
+        max_nonzero(&self.core_freqs_eff)
+    }
+
+    /// Mean per-core effective frequency, ignoring unavailable (0.0) cores
+    pub fn avg_core_freq_eff(&self) -> f32 {
+        mean_nonzero(&self.core_freqs_eff)
+    }
+
+    /// How far a core's effective clock falls short of its requested clock
+    /// (MHz), i.e. how much sleep states and stalls are holding it back
+    ///
+    /// Negative when the effective clock reads above the requested one.
+    /// NaN when the core or either clock is missing or 0.
+    pub fn core_freq_stretch(&self, core: usize) -> f32 {
+        match (self.core_freqs.get(core), self.core_freqs_eff.get(core)) {
+            (Some(&freq), Some(&eff)) if freq > 0.0 && eff > 0.0 => freq - eff,
+            _ => f32::NAN,
+        }
+    }
+
+    /// Package activity: mean C0 residency (%) of cores that report one
+    ///
+    /// NaN when the layout has no C0 data or no core reports any.
+    pub fn package_c0(&self) -> f32 {
+        match mean_nonzero(&self.core_c0) {
+            0.0 => f32::NAN,
+            c0 => c0,
+        }
+    }
+
+    /// Whether every limit and reading is zero, as when the driver hands
+    /// back a zeroed buffer (usually missing permissions)
+    pub fn is_blank(&self) -> bool {
+        [
+            self.ppt_limit, self.tdc_limit, self.edc_limit, self.thm_limit,
+            self.ppt_value, self.tdc_value, self.edc_value, self.tctl,
+            self.package_power, self.fclk,
+        ]
+        .iter()
+        .chain(&self.core_temps)
+        .all(|v| *v == 0.0)
+    }
+
+    /// Average core clock per package watt (MHz/W)
+    ///
+    /// 0.0 when package power or core clocks aren't available.
+    pub fn efficiency(&self) -> f32 {
+        ratio(self.avg_core_freq(), self.package_power)
+    }
+
+    /// Clock per watt of a single core (MHz/W), 0.0 when unavailable
+    pub fn core_efficiency(&self, core: usize) -> f32 {
+        match (self.core_freqs.get(core), self.core_power.get(core)) {
+            (Some(&freq), Some(&power)) => ratio(freq, power),
+            _ => 0.0,
+        }
+    }
+
+    /// Current value of a limit as a fraction of its configured ceiling
+    ///
+    /// Returns 0.0 when the limit itself wasn't parsed.
+    pub fn limit_utilization(&self, limit: Limit) -> f32 {
+        let (value, ceiling) = match limit {
+            Limit::Ppt => (self.ppt_value, self.ppt_limit),
+            Limit::Tdc => (self.tdc_value, self.tdc_limit),
+            Limit::Edc => (self.edc_value, self.edc_limit),
+            Limit::Thermal => (self.tctl, self.thm_limit),
+        };
+        if ceiling > 0.0 { value / ceiling } else { 0.0 }
+    }
+
+    /// Degrees left before Tctl reaches the thermal limit
+    ///
+    /// Negative once over the limit; NaN when the limit wasn't parsed.
+    pub fn thermal_headroom(&self) -> f32 {
+        headroom(self.thm_limit, self.tctl)
+    }
+
+    /// Degrees left before a core reaches the thermal limit, NaN when the
+    /// limit or the core's temperature is unavailable
+    pub fn core_thermal_headroom(&self, core: usize) -> f32 {
+        match self.core_temps.get(core) {
+            Some(&temp) if temp > 0.0 => headroom(self.thm_limit, temp),
+            _ => f32::NAN,
+        }
+    }
+
+    /// The limit closest to its ceiling, with its utilization
+    pub fn binding_limit(&self) -> (Limit, f32) {
+        Limit::ALL
+            .into_iter()
+            .map(|limit| (limit, self.limit_utilization(limit)))
+            .fold((Limit::Ppt, f32::MIN), |best, cur| if cur.1 > best.1 { cur } else { best })
+    }
+
+    /// Whether a core is present but parked
+    ///
+    /// A parked core still reports a temperature but its effective frequency
+    /// drops to ~0. Cores with no temperature (fused off / not populated) and
+    /// layouts without per-core frequency data are never reported as parked.
+    pub fn is_core_parked(&self, core: usize) -> bool {
+        let present = self.core_temps.get(core).is_some_and(|t| *t > 0.0);
+        match self.core_freqs_eff.get(core) {
+            Some(eff) => present && *eff < PARKED_FREQ_MHZ,
+            None => false,
+        }
+    }
+
+    /// Cores with a temperature reading, i.e. present and not fused off
+    pub fn present_core_count(&self) -> usize {
+        self.core_temps.iter().filter(|t| **t > 0.0).count()
+    }
+
+    /// Present cores doing work: C0 residency above `c0_threshold` (%)
+    ///
+    /// Layouts without per-core C0 fall back to frequency, counting cores at
+    /// or above `PARKED_FREQ_MHZ` (effective clock where there is one).
+    pub fn active_core_count(&self, c0_threshold: f32) -> usize {
+        let has_c0 = self.availability.core_c0 || self.core_c0.iter().any(|c| *c > 0.0);
+        (0..self.core_temps.len())
+            .filter(|&core| self.core_temps[core] > 0.0)
+            .filter(|&core| {
+                if has_c0 {
+                    return self.core_c0.get(core).is_some_and(|c| *c > c0_threshold);
+                }
+                let freq = self.core_freqs_eff.get(core).filter(|f| **f > 0.0).or(self.core_freqs.get(core));
+                freq.is_some_and(|f| *f >= PARKED_FREQ_MHZ)
+            })
+            .count()
+    }
+
+    /// The limit holding the CPU back right now, if any
+    pub fn throttle_status(&self) -> ThrottleStatus {
+        match self.binding_limit() {
+            (limit, utilization) if utilization >= THROTTLE_THRESHOLD => ThrottleStatus::Limited(limit),
+            _ => ThrottleStatus::Unconstrained,
+        }
+    }
+
+    /// Whether any limit is at or above `THROTTLE_THRESHOLD`
+    pub fn is_throttling(&self) -> bool {
+        self.throttle_status() != ThrottleStatus::Unconstrained
+    }
+
+    /// Number of CCDs spanned by the per-core data
+    pub fn ccd_count(&self) -> usize {
+        self.core_temps.len().div_ceil(self.codename.cores_per_ccd())
+    }
+
+    /// Indices of the cores on a CCD (empty past the last CCD)
+    pub fn cores_in_ccd(&self, ccd: usize) -> Range<usize> {
+        let per_ccd = self.codename.cores_per_ccd();
+        let total = self.core_temps.len();
+        (ccd * per_ccd).min(total)..((ccd + 1) * per_ccd).min(total)
+    }
+
+    /// "CCD1/Core3": a core numbered within its CCD rather than globally
+    pub fn ccd_core_label(&self, core: usize) -> String {
+        let (ccd, local) = core_ccd_position(core, self.codename.cores_per_ccd());
+        format!("CCD{}/Core{}", ccd, local)
+    }
+
+    /// Average temperature of a CCD's populated cores, 0.0 if none report
+    pub fn ccd_avg_temp(&self, ccd: usize) -> f32 {
+        let temps: Vec<f32> = self.core_temps[self.cores_in_ccd(ccd)]
+            .iter()
+            .copied()
+            .filter(|t| *t > 0.0)
+            .collect();
+        if temps.is_empty() { 0.0 } else { temps.iter().sum::<f32>() / temps.len() as f32 }
+    }
+
+    /// Summed core power of a CCD
+    pub fn ccd_power(&self, ccd: usize) -> f32 {
+        let range = self.cores_in_ccd(ccd);
+        let end = range.end.min(self.core_power.len());
+        self.core_power.get(range.start..end).map_or(0.0, |p| p.iter().sum())
+    }
+
+    /// Mean frequency of a CCD's cores, ignoring unavailable (0.0) cores
+    pub fn ccd_avg_freq(&self, ccd: usize) -> f32 {
+        mean_nonzero(self.ccd_freqs(ccd))
     }
+
+    /// Highest frequency among a CCD's cores, 0.0 if none report
+    pub fn ccd_max_freq(&self, ccd: usize) -> f32 {
+        max_nonzero(self.ccd_freqs(ccd))
+    }
+
+    fn ccd_freqs(&self, ccd: usize) -> &[f32] {
+        let range = self.cores_in_ccd(ccd);
+        let end = range.end.min(self.core_freqs.len());
+        self.core_freqs.get(range.start..end).unwrap_or(&[])
+    }
+}
+
+pub(crate) fn headroom(limit: f32, temp: f32) -> f32 {
+    if limit > 0.0 { limit - temp } else { f32::NAN }
+}
+
+/// Record `warnings` as text in the table, the way `parse` returns them
+pub(crate) fn with_warning_text((mut table, warnings): (PmTable, Vec<Warning>)) -> PmTable {
+    table.warnings.extend(warnings.iter().map(ToString::to_string));
+    table
+}
+
+/// Reset a reading outside `range` (or NaN) to 0.0 and record why
+fn check_value(warnings: &mut Vec<Warning>, field: &'static str, value: &mut f32, range: RangeInclusive<f32>) {
+    if !range.contains(value) {
+        warnings.push(Warning::ClampedValue { field, value: *value, range });
+        *value = 0.0;
+    }
+}
+
+/// Like `check_value`, with one warning for the whole per-core array
+fn check_values(warnings: &mut Vec<Warning>, field: &'static str, values: &mut [f32], range: RangeInclusive<f32>) {
+    let bad: Vec<usize> = (0..values.len()).filter(|&i| !range.contains(&values[i])).collect();
+    if bad.is_empty() {
+        return;
+    }
+    for &i in &bad {
+        values[i] = 0.0;
+    }
+    warnings.push(Warning::ClampedCores { field, cores: bad, range });
+}
+
+pub(crate) fn max_nonzero(values: &[f32]) -> f32 {
+    values.iter().copied().filter(|v| *v > 0.0).fold(0.0, f32::max)
+}
+
+/// `value / per`, or 0.0 when either side isn't a positive reading
+fn ratio(value: f32, per: f32) -> f32 {
+    if value > 0.0 && per > 0.0 { value / per } else { 0.0 }
+}
+
+/// Offsets for `version`, or `UnsupportedPmTableVersion`
+pub(crate) fn layout(version: u32) -> Result<PmTableOffsets> {
+    offsets::get_offsets(version).ok_or(SmuError::UnsupportedPmTableVersion(version))
+}
+
+/// Positive, finite readings; unpopulated cores read 0.0
+fn populated(values: &[f32]) -> Vec<f32> {
+    values.iter().copied().filter(|v| v.is_finite() && *v > 0.0).collect()
+}
+
+pub(crate) fn mean_nonzero(values: &[f32]) -> f32 {
+    let (sum, count) = values.iter().filter(|v| **v > 0.0).fold((0.0, 0), |(s, n), v| (s + v, n + 1));
+    if count == 0 { 0.0 } else { sum / count as f32 }
+}
+
+/// The little-endian f32 at `offset`, or None if it runs past the buffer
+///
+/// Every field read goes through here: one bounds check, no alignment
+/// requirement, and the same result on any host byte order.
+#[inline]
+fn read_le_f32(data: &[u8], offset: usize) -> Option<f32> {
+    let bytes = data.get(offset..offset.checked_add(4)?)?;
+    Some(f32::from_le_bytes(bytes.try_into().ok()?))
 }
 
 /// Read a little-endian f32 from buffer at offset
 fn read_f32(data: &[u8], offset: usize) -> Result<f32> {
-    if offset + 4 > data.len() {
-        return Err(SmuError::InvalidPmTableSize {
-            expected: offset + 4,
-            actual: data.len(),
-        });
-    }
-    let mut cursor = Cursor::new(&data[offset..offset + 4]);
-    Ok(cursor.read_f32::<LittleEndian>()?)
+    read_le_f32(data, offset).ok_or(SmuError::InvalidPmTableSize {
+        expected: offset.saturating_add(4),
+        actual: data.len(),
+    })
 }
 
 /// Read a little-endian f32, returning 0.0 if offset is marker (0xFFFF) or out of bounds
 fn read_f32_safe_with_marker(data: &[u8], offset: usize) -> f32 {
-    if offset >= 0xFFFF || offset + 4 > data.len() {
-        return 0.0;
-    }
-    let mut cursor = Cursor::new(&data[offset..offset + 4]);
-    cursor.read_f32::<LittleEndian>().unwrap_or(0.0)
+    read_f32_optional(data, offset).unwrap_or(0.0)
 }
 
-/// Read CPU frequencies from /proc/cpuinfo
-fn read_cpuinfo_frequencies(core_count: usize) -> std::io::Result<Vec<f32>> {
-    use std::fs;
+/// Read a little-endian f32, returning None if offset is marker (0xFFFF) or out of bounds
+fn read_f32_optional(data: &[u8], offset: usize) -> Option<f32> {
+    if offset >= 0xFFFF {
+        return None;
+    }
+    read_le_f32(data, offset)
+}
 
-    let cpuinfo = fs::read_to_string("/proc/cpuinfo")?;
+/// First `core_count` "cpu MHz" values in /proc/cpuinfo text, padded with
+/// zeros
+fn parse_cpuinfo_frequencies(cpuinfo: &str, core_count: usize) -> Vec<f32> {
     let mut freqs = Vec::with_capacity(core_count);
 
     for line in cpuinfo.lines() {
-        if line.starts_with("cpu MHz") {
-            if let Some(value_str) = line.split(':').nth(1) {
-                if let Ok(freq) = value_str.trim().parse::<f32>() {
-                    freqs.push(freq);
-                    if freqs.len() >= core_count {
-                        break;
-                    }
-                }
+        if line.starts_with("cpu MHz")
+            && let Some(value_str) = line.split(':').nth(1)
+            && let Ok(freq) = value_str.trim().parse::<f32>()
+        {
+            freqs.push(freq);
+            if freqs.len() >= core_count {
+                break;
             }
         }
     }
@@ -331,7 +1254,7 @@ fn read_cpuinfo_frequencies(core_count: usize) -> std::io::Result<Vec<f32>> {
         freqs.push(0.0);
     }
 
-    Ok(freqs)
+    freqs
 }
 
 #[cfg(test)]
@@ -347,6 +1270,8 @@ mod tests {
             off.core_temp_base,
             off.core_freq_base,
             off.core_freqeff_base,
+            off.core_voltage_base,
+            off.core_fit_base,
         ].into_iter()
             .filter(|&x| x < 0xFFFF)
             .max()
@@ -394,6 +1319,12 @@ mod tests {
             if off.core_c0_base < 0xFFFF {
                 write_f32(&mut data, off.core_c0_base + i * 4, 90.0 + i as f32);
             }
+            if off.core_voltage_base < 0xFFFF {
+                write_f32(&mut data, off.core_voltage_base + i * 4, 1.2 + i as f32 * 0.01);
+            }
+            if off.core_fit_base < 0xFFFF {
+                write_f32(&mut data, off.core_fit_base + i * 4, 20.0 + i as f32);
+            }
         }
 
         data
@@ -445,6 +1376,204 @@ mod tests {
         assert!((table.soc_voltage - 1.10).abs() < 0.01);
     }
 
+    #[test]
+    fn test_parse_core_voltages() {
+        // Only an offsets file locates them on the desktop layouts so far
+        let off = PmTableOffsets { core_voltage_base: 0x580, ..offsets::OFFSETS_0X620205 };
+        let mut data = create_test_pm_table(16, 0x620205);
+        data.resize(0x580 + 16 * 4, 0);
+        for i in 0..16 {
+            data[0x580 + i * 4..0x584 + i * 4].copy_from_slice(&(1.2 + i as f32 * 0.01).to_le_bytes());
+        }
+        let table = PmTable::parse_with_offsets(&data, &off, 0x620205, Codename::GraniteRidge, 16).unwrap();
+        assert_eq!(table.core_voltages.len(), 16);
+        assert!((table.core_voltages[3] - 1.23).abs() < 0.001);
+
+        let data = create_test_pm_table(16, 0x620205);
+        let table = PmTable::parse(&data, 0x620205, Codename::GraniteRidge, 16).unwrap();
+        assert!(table.core_voltages.is_empty());
+        assert!(serde_json::to_value(&table).unwrap().get("core_voltages").is_none());
+    }
+
+    #[test]
+    fn test_optional_core_arrays_do_not_alias_other_arrays() {
+        // A 16-core read of each layout must not spill one array into another
+        let span = |base: usize| base..base + 16 * 4;
+        for &version in offsets::SUPPORTED_VERSIONS {
+            let off = offsets::get_offsets(version).unwrap();
+            let bases = [
+                ("core_power_base", off.core_power_base),
+                ("core_temp_base", off.core_temp_base),
+                ("core_freq_base", off.core_freq_base),
+                ("core_freqeff_base", off.core_freqeff_base),
+                ("core_c0_base", off.core_c0_base),
+                ("core_voltage_base", off.core_voltage_base),
+                ("core_fit_base", off.core_fit_base),
+            ];
+            for (name, base) in [("core_voltage_base", off.core_voltage_base), ("core_fit_base", off.core_fit_base)] {
+                if base == 0xFFFF {
+                    continue;
+                }
+                let optional = span(base);
+                for (other_name, other_base) in bases {
+                    if other_name != name && other_base != 0xFFFF {
+                        let other = span(other_base);
+                        assert!(
+                            optional.end <= other.start || other.end <= optional.start,
+                            "{:#x}: {} {:#x} overlaps {} {:#x}",
+                            version, name, base, other_name, other_base
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_freq_source() {
+        let data = create_test_pm_table(8, 0x240903);
+        let table = PmTable::parse(&data, 0x240903, Codename::Vermeer, 8).unwrap();
+        assert_eq!(table.freq_source, FreqSource::PmTable);
+
+        // Granite Ridge has no per-core clocks in the PM table
+        let data = create_test_pm_table(16, 0x620205);
+        let dir = tempfile::TempDir::new().unwrap();
+        let cpuinfo = dir.path().join("cpuinfo");
+        let parse = |cpuinfo: &Path| {
+            let off = offsets::OFFSETS_0X620205;
+            PmTable::parse_inner(&data, &off, 0x620205, Codename::GraniteRidge, 16, false, Some(cpuinfo)).unwrap().0
+        };
+
+        fs::write(&cpuinfo, "processor\t: 0\ncpu MHz\t\t: 4400.000\n\nprocessor\t: 1\ncpu MHz\t\t: 3900.000\n").unwrap();
+        let table = parse(&cpuinfo);
+        assert_eq!(table.freq_source, FreqSource::Cpuinfo);
+        assert_eq!(serde_json::to_value(&table).unwrap()["freq_source"], serde_json::to_value(FreqSource::Cpuinfo).unwrap());
+        assert_eq!(table.core_freqs[..2], [4400.0, 3900.0]);
+
+        // No clock lines (containers, other arches) isn't a row of zeros
+        fs::write(&cpuinfo, "processor\t: 0\nmodel name\t: AMD Ryzen 9 9950X\n").unwrap();
+        let table = parse(&cpuinfo);
+        assert_eq!(table.freq_source, FreqSource::Unavailable);
+        assert!(table.core_freqs.is_empty());
+
+        let table = parse(&dir.path().join("missing"));
+        assert_eq!(table.freq_source, FreqSource::Unavailable);
+    }
+
+    #[test]
+    fn test_parse_core_fit() {
+        let off = PmTableOffsets { core_fit_base: 0x580, ..offsets::OFFSETS_0X620205 };
+        let mut data = create_test_pm_table(8, 0x620205);
+        data.resize(0x580 + 8 * 4, 0);
+        for i in 0..8 {
+            data[0x580 + i * 4..0x584 + i * 4].copy_from_slice(&(20.0 + i as f32).to_le_bytes());
+        }
+        let table = PmTable::parse_with_offsets(&data, &off, 0x620205, Codename::GraniteRidge, 8).unwrap();
+        assert_eq!(table.core_fit, vec![20.0, 21.0, 22.0, 23.0, 24.0, 25.0, 26.0, 27.0]);
+
+        // Marker layouts leave it empty and out of JSON
+        let data = create_test_pm_table(16, 0x620205);
+        let table = PmTable::parse(&data, 0x620205, Codename::GraniteRidge, 16).unwrap();
+        assert!(table.core_fit.is_empty());
+        assert!(!table.missing_fields.contains(&"core_fit"));
+        assert!(serde_json::to_value(&table).unwrap().get("core_fit").is_none());
+    }
+
+    #[test]
+    fn test_parse_reader_matches_slice() {
+        let data = create_test_pm_table(8, 0x240903);
+        let table = PmTable::parse(&data, 0x240903, Codename::Vermeer, 8).unwrap();
+        let streamed = PmTable::parse_reader(std::io::Cursor::new(&data), 0x240903, Codename::Vermeer, 8).unwrap();
+        assert_eq!(streamed, table);
+
+        let short = PmTable::parse_reader(&data[..0x100], 0x240903, Codename::Vermeer, 8);
+        assert!(matches!(short, Err(SmuError::InvalidPmTableSize { .. })));
+    }
+
+    /// The 0x240903 layout as a user would write it
+    const VERMEER_OFFSETS_TOML: &str = "\
+        ppt_limit = 0x000\nppt_value = 0x004\ntdc_limit = 0x008\ntdc_value = 0x00C\n\
+        thm_limit = 0x010\nthm_value = 0x014\nedc_limit = 0x020\nedc_value = 0x024\n\
+        cpu_power = 0x060\nsoc_power = 0x064\ncpu_power_includes_soc = false\n\
+        cpu_voltage = 0x0A0\nsoc_voltage = 0x0B4\nfclk = 0x0C0\nmclk = 0x0CC\nsoc_temp = 0x1CC\n\
+        core_power_base = 0x24C\ncore_temp_base = 0x28C\ncore_freq_base = 0x2EC\n\
+        core_freqeff_base = 0x30C\ncore_c0_base = 0x32C\ncore_voltage_base = 0xFFFF\ncore_fit_base = 0xFFFF\n\
+        max_cores = 16\n";
+
+    #[test]
+    fn test_custom_offsets_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("offsets.toml");
+        fs::write(&path, VERMEER_OFFSETS_TOML).unwrap();
+        let offsets = PmTableOffsets::load(&path).unwrap();
+        assert_eq!(offsets.named(), offsets::OFFSETS_0X240903.named());
+
+        // A version without a layout parses the same as the built-in one
+        let data = create_test_pm_table(8, 0x240903);
+        let builtin = PmTable::parse(&data, 0x240903, Codename::Vermeer, 8).unwrap();
+        let custom = PmTable::parse_with_offsets(&data, &offsets, 0x240999, Codename::Vermeer, 8).unwrap();
+        assert_eq!(custom.version, 0x240999);
+        assert_eq!(PmTable { version: 0x240903, ..custom }, builtin);
+        assert!(matches!(PmTable::parse(&data, 0x240999, Codename::Vermeer, 8), Err(SmuError::UnsupportedPmTableVersion(_))));
+
+        // JSON too, and every field is required
+        let json = dir.path().join("offsets.json");
+        fs::write(&json, r#"{"ppt_limit": 0, "ppt_value": 4}"#).unwrap();
+        let err = PmTableOffsets::load(&json).unwrap_err().to_string();
+        assert!(err.contains("offsets.json") && err.contains("missing field `tdc_limit`"), "{}", err);
+
+        fs::write(&path, VERMEER_OFFSETS_TOML.replace("thm_value", "tctl")).unwrap();
+        assert!(PmTableOffsets::load(&path).unwrap_err().to_string().contains("unknown field `tctl`"));
+        fs::write(&path, VERMEER_OFFSETS_TOML.replace("max_cores = 16", "max_cores = 0")).unwrap();
+        assert!(PmTableOffsets::load(&path).unwrap_err().to_string().contains("max_cores 0"));
+
+        // The scales are optional
+        assert_eq!((offsets.power_scale, offsets.current_scale), (1.0, 1.0));
+        fs::write(&path, format!("{}power_scale = 1.25\n", VERMEER_OFFSETS_TOML)).unwrap();
+        assert_eq!(PmTableOffsets::load(&path).unwrap().power_scale, 1.25);
+        fs::write(&path, format!("{}current_scale = 0.0\n", VERMEER_OFFSETS_TOML)).unwrap();
+        assert!(PmTableOffsets::load(&path).unwrap_err().to_string().contains("current_scale 0"));
+    }
+
+    #[test]
+    fn test_telemetry_scale() {
+        let data = create_test_pm_table(8, 0x240903);
+        let plain = PmTable::parse(&data, 0x240903, Codename::Vermeer, 8).unwrap();
+        let offsets = PmTableOffsets { power_scale: 1.25, current_scale: 0.5, ..pm_table_offsets(0x240903).unwrap() };
+        let scaled = PmTable::parse_with_offsets(&data, &offsets, 0x240903, Codename::Vermeer, 8).unwrap();
+
+        assert_eq!(scaled.ppt_value, plain.ppt_value * 1.25);
+        assert_eq!(scaled.package_power, plain.package_power * 1.25);
+        assert_eq!(scaled.soc_power, plain.soc_power * 1.25);
+        assert_eq!(scaled.core_power[3], plain.core_power[3] * 1.25);
+        assert_eq!(scaled.tdc_value, plain.tdc_value * 0.5);
+        assert_eq!(scaled.edc_value, plain.edc_value * 0.5);
+        // Limits and everything that isn't power or current stay as read
+        assert_eq!(scaled.ppt_limit, plain.ppt_limit);
+        assert_eq!(scaled.tdc_limit, plain.tdc_limit);
+        assert_eq!(scaled.tctl, plain.tctl);
+        assert_eq!(scaled.core_temps, plain.core_temps);
+        assert_eq!(scaled.core_voltage, plain.core_voltage);
+        assert_eq!(scaled.core_freqs, plain.core_freqs);
+    }
+
+    #[test]
+    fn test_available_fields() {
+        let data = create_test_pm_table(8, 0x620205);
+        let table = PmTable::parse(&data, 0x620205, Codename::GraniteRidge, 8).unwrap();
+        let available = table.available_fields();
+        assert!(!available.core_freqs && !available.core_freqs_eff && !available.core_c0);
+        assert!(available.core_temps && available.core_power);
+
+        let data = create_test_pm_table(8, 0x240903);
+        let table = PmTable::parse(&data, 0x240903, Codename::Vermeer, 8).unwrap();
+        assert!(table.available_fields().core_freqs && table.available_fields().core_c0);
+        // Recordings get the layout of their version back
+        let loaded: PmTable = serde_json::from_str(&serde_json::to_string(&table).unwrap()).unwrap();
+        assert_eq!(loaded.available_fields(), table.available_fields());
+        assert_eq!(FieldAvailability::for_version(0x123456), FieldAvailability::default());
+    }
+
     #[test]
     fn test_invalid_size() {
         let data = vec![0u8; 100]; // Too small
@@ -452,6 +1581,172 @@ mod tests {
         assert!(matches!(result, Err(SmuError::InvalidPmTableSize { .. })));
     }
 
+    #[test]
+    fn test_parse_lenient_truncated() {
+        let data = create_test_pm_table(8, 0x240903);
+        // Cuts off the per-core block and everything from the clocks onwards
+        let truncated = &data[..0xC0];
+
+        assert!(PmTable::parse(truncated, 0x240903, Codename::Vermeer, 8).is_err());
+        let table = PmTable::parse_lenient(truncated, 0x240903, Codename::Vermeer, 8).unwrap();
+
+        assert!((table.ppt_limit - 142.0).abs() < 0.01);
+        assert!((table.tctl - 65.2).abs() < 0.01);
+        assert!(table.missing_fields.contains(&"fclk"));
+        assert!(table.missing_fields.contains(&"core_temps"));
+        assert!(!table.missing_fields.contains(&"tctl"));
+        assert_eq!(table.fclk, 0.0);
+        assert_eq!(table.core_temps, vec![0.0; 8]);
+    }
+
+    #[test]
+    fn test_parse_lenient_complete_table() {
+        let data = create_test_pm_table(8, 0x240903);
+        let table = PmTable::parse_lenient(&data, 0x240903, Codename::Vermeer, 8).unwrap();
+        assert!(table.missing_fields.is_empty());
+    }
+
+    #[test]
+    fn test_garbage_values_are_reset() {
+        let off = offsets::get_offsets(0x240903).unwrap();
+        let mut data = create_test_pm_table(8, 0x240903);
+        let mut write_bits = |offset: usize, bits: u32| {
+            data[offset..offset + 4].copy_from_slice(&bits.to_le_bytes());
+        };
+        write_bits(off.thm_value, 0x7F7F_FFFF);               // 3.4e38
+        write_bits(off.ppt_value, (-12.0f32).to_bits());
+        write_bits(off.fclk, 0x7FC0_0000);                    // NaN
+        write_bits(off.cpu_voltage, 0x7F80_0000);             // +inf
+        write_bits(off.core_temp_base + 3 * 4, 0xFF7F_FFFF);  // -3.4e38
+        write_bits(off.core_temp_base + 5 * 4, 0x7FC0_0000);
+
+        let table = PmTable::parse(&data, 0x240903, Codename::Vermeer, 8).unwrap();
+
+        assert_eq!(table.tctl, 0.0);
+        assert_eq!(table.ppt_value, 0.0);
+        assert_eq!(table.fclk, 0.0);
+        assert_eq!(table.core_voltage, 0.0);
+        assert_eq!(table.core_temps[3], 0.0);
+        assert_eq!(table.core_temps[5], 0.0);
+        assert!((table.core_temps[4] - 62.0).abs() < 0.01);
+        assert!((table.mclk - 1800.0).abs() < 0.01);
+
+        // One warning per scalar, one for the core array
+        assert_eq!(table.warnings.len(), 5);
+        assert!(table.warnings.iter().any(|w| w.starts_with("tctl")));
+        assert!(table.warnings.iter().any(|w| w.contains("core(s) 3, 5")));
+    }
+
+    #[test]
+    fn test_core_power_sum_check() {
+        let table = |package_power: f32, core_power: Vec<f32>| {
+            let mut table = PmTable { cpu_power_includes_soc: Some(false), package_power, soc_power: 12.0, core_power, ..Default::default() };
+            let mut warnings = Vec::new();
+            table.validate_into(&mut warnings);
+            (table, warnings)
+        };
+
+        let (consistent, warnings) = table(60.0, vec![10.0, 12.0, 14.0, 8.0]);
+        assert_eq!(consistent.core_power_sum(), 44.0);
+        assert!(warnings.is_empty());
+        // Up to twice the package figure is tolerated, beyond is not
+        assert!(table(60.0, vec![60.0, 60.0]).1.is_empty());
+        assert_eq!(table(60.0, vec![60.0, 61.0]).1, vec![Warning::CorePowerMismatch { sum: 121.0, package: 60.0 }]);
+        assert_eq!(table(60.0, vec![1.0, 1.0]).1, vec![Warning::CorePowerMismatch { sum: 2.0, package: 60.0 }]);
+        // Idle packages and tables without per-core power aren't checked
+        assert!(table(8.0, vec![0.1, 0.1]).1.is_empty());
+        assert!(table(60.0, vec![]).1.is_empty());
+
+        // Socket-wide package power includes the SoC, which is left out
+        let (mut socket, mut warnings) = (PmTable { cpu_power_includes_soc: Some(true), ..table(60.0, vec![]).0 }, Vec::new());
+        socket.core_power = vec![50.0, 50.0];
+        socket.validate_into(&mut warnings);
+        assert_eq!(warnings, vec![Warning::CorePowerMismatch { sum: 100.0, package: 48.0 }]);
+
+        // An offsets file saying so on a core-rail version gets the same
+        let mut data = create_test_pm_table(8, 0x240903);
+        let off = PmTableOffsets { cpu_power_includes_soc: Some(true), ..offsets::OFFSETS_0X240903 };
+        for (offset, value) in [(off.cpu_power, 30.0f32), (off.soc_power, 20.0)] {
+            data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+        }
+        for core in 0..8 {
+            let offset = off.core_power_base + core * 4;
+            data[offset..offset + 4].copy_from_slice(&3.5f32.to_le_bytes());
+        }
+        let table = PmTable::parse_with_offsets(&data, &off, 0x240903, Codename::Vermeer, 8).unwrap();
+        assert_eq!(table.package_core_power(), 10.0);
+        assert!(table.warnings.iter().any(|w| w.starts_with("per-core power sums to 28.0 W against 10.0 W")), "{:?}", table.warnings);
+    }
+
+    #[test]
+    fn test_valid_table_has_no_warnings() {
+        let data = create_test_pm_table(8, 0x240903);
+        let table = PmTable::parse(&data, 0x240903, Codename::Vermeer, 8).unwrap();
+        assert!(table.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_parse_pm_table_version() {
+        assert_eq!(parse_pm_table_version("0x00620205"), Some(0x620205));
+        assert_eq!(parse_pm_table_version("0X620205"), Some(0x620205));
+        assert_eq!(parse_pm_table_version(" 0x240903\n"), Some(0x240903));
+        assert_eq!(parse_pm_table_version("2361603"), Some(0x240903));
+        assert_eq!(parse_pm_table_version("0x"), None);
+        assert_eq!(parse_pm_table_version("v240903"), None);
+        assert!(offsets::get_offsets(parse_pm_table_version("0x00620205").unwrap()).is_some());
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let data = create_test_pm_table(8, 0x240903);
+        let mut table = PmTable::parse(&data, 0x240903, Codename::Vermeer, 8).unwrap();
+        table.missing_fields = vec!["core_c0"];
+
+        let json = serde_json::to_string(&table).unwrap();
+        let loaded: PmTable = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, table);
+        assert_eq!(loaded.codename, Codename::Vermeer);
+        assert!(matches!(loaded.codename_str, Cow::Borrowed("Vermeer")));
+    }
+
+    #[test]
+    fn test_supported_versions_have_layouts() {
+        let versions = supported_pm_table_versions();
+        assert!(versions.contains(&0x240903));
+        assert!(versions.contains(&0x620205));
+        assert!(versions.iter().all(|&v| offsets::get_offsets(v).is_some()));
+        assert!(versions.windows(2).all(|w| w[0] < w[1]));
+
+        let tables = supported_pm_tables();
+        assert_eq!(tables[0], (0x240903, vec![Codename::Matisse, Codename::Vermeer]));
+        assert!(tables.iter().any(|(v, names)| *v == 0x620205 && names == &[Codename::GraniteRidge]));
+    }
+
+    #[test]
+    fn test_named_offsets_match_metric_keys() {
+        for &version in supported_pm_table_versions() {
+            let data = create_test_pm_table(2, version);
+            let metrics = PmTable::parse(&data, version, Codename::Unsupported, 2).unwrap().as_metric_map();
+            let named = pm_table_offsets(version).unwrap().named();
+            assert!(named.iter().all(|(name, _)| metrics.contains_key(*name)), "{:#x}: {:?}", version, named);
+            assert!(named.windows(2).all(|w| w[0].1 <= w[1].1));
+        }
+
+        // Unlocated readings are left out
+        let named = pm_table_offsets(0x620205).unwrap().named();
+        assert!(named.contains(&("tctl", 0x00C)));
+        assert!(!named.iter().any(|(name, _)| *name == "core.0.freq"));
+        assert!(pm_table_offsets(0x123456).is_none());
+    }
+
+    #[test]
+    fn test_parse_cpuinfo_frequencies() {
+        let cpuinfo = "processor\t: 0\ncpu MHz\t\t: 4950.123\n\nprocessor\t: 1\ncpu MHz\t\t: 3600.000\n";
+        assert_eq!(parse_cpuinfo_frequencies(cpuinfo, 3), vec![4950.123, 3600.0, 0.0]);
+        assert_eq!(parse_cpuinfo_frequencies(cpuinfo, 1), vec![4950.123]);
+        assert_eq!(parse_cpuinfo_frequencies("", 2), vec![0.0, 0.0]);
+    }
+
     #[test]
     fn test_unsupported_version() {
         let data = vec![0u8; 1000];
@@ -470,14 +1765,349 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_core_count_beyond_layout_is_clamped() {
+        // cpuinfo may report more cores than the layout holds; parsing should
+        // clamp to the layout's max_cores instead of failing the size check
+        let data = create_test_pm_table(16, 0x240903);
+        let table = PmTable::parse(&data, 0x240903, Codename::Vermeer, 32).unwrap();
+        assert_eq!(table.core_temps.len(), 16);
+        assert_eq!(table.core_power.len(), 16);
+        assert!((table.core_temps[15] - 67.5).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_max_core_helpers_skip_zeros() {
+        let table = PmTable {
+            core_temps: vec![61.0, 0.0, 74.5, 70.0],
+            core_freqs: vec![0.0, 4650.0, 4400.0, 0.0],
+            ..Default::default()
+        };
+        assert!((table.max_core_temp() - 74.5).abs() < 0.01);
+        assert!((table.max_core_freq() - 4650.0).abs() < 0.01);
+        assert_eq!(PmTable::default().max_core_freq(), 0.0);
+    }
+
+    #[test]
+    fn test_core_temp_spread_and_stddev() {
+        // Unpopulated (0.0) and garbage (NaN) cores are left out
+        let table = PmTable { core_temps: vec![60.0, 64.0, 0.0, 68.0, f32::NAN, 72.0], ..Default::default() };
+        assert!((table.core_temp_spread() - 12.0).abs() < 0.001);
+        // Mean 66, squared deviations 36 + 4 + 4 + 36 over 4 cores
+        assert!((table.core_temp_stddev() - 20.0f32.sqrt()).abs() < 0.001);
+
+        let single = PmTable { core_temps: vec![65.0, 0.0], ..Default::default() };
+        assert_eq!(single.core_temp_spread(), 0.0);
+        assert_eq!(single.core_temp_stddev(), 0.0);
+    }
+
+    #[test]
+    fn test_total_power() {
+        let core_rail = PmTable { cpu_power_includes_soc: Some(false), package_power: 70.0, soc_power: 18.0, ..Default::default() };
+        assert_eq!(core_rail.total_power(), Some(88.0));
+        // Nothing to add without a SoC reading
+        assert_eq!(PmTable { soc_power: 0.0, ..core_rail.clone() }.total_power(), None);
+
+        let socket = PmTable { cpu_power_includes_soc: Some(true), package_power: 88.0, soc_power: 18.0, ..Default::default() };
+        assert_eq!(socket.total_power(), Some(88.0));
+
+        // Without a layout saying which it's anyone's guess
+        assert_eq!(PmTable { cpu_power_includes_soc: None, ..core_rail }.total_power(), None);
+
+        // Recorded from the layout used: built-in, or an offsets file on a
+        // built-in version number
+        let data = create_test_pm_table(8, 0x240903);
+        let table = PmTable::parse(&data, 0x240903, Codename::Vermeer, 8).unwrap();
+        assert_eq!(table.cpu_power_includes_soc, Some(false));
+        let off = PmTableOffsets { cpu_power_includes_soc: Some(true), ..offsets::OFFSETS_0X240903 };
+        let table = PmTable::parse_with_offsets(&data, &off, 0x240903, Codename::Vermeer, 8).unwrap();
+        assert_eq!(table.total_power(), Some(table.package_power));
+        let data = create_test_pm_table(16, 0x620205);
+        let table = PmTable::parse(&data, 0x620205, Codename::GraniteRidge, 16).unwrap();
+        assert_eq!(table.cpu_power_includes_soc, Some(true));
+    }
+
+    #[test]
+    fn test_efficiency() {
+        let table = PmTable {
+            core_freqs: vec![4000.0, 5000.0, 0.0],
+            core_power: vec![8.0, 0.0, 2.0],
+            package_power: 90.0,
+            ..Default::default()
+        };
+        // Average over the two reporting cores: 4500 MHz / 90 W
+        assert!((table.efficiency() - 50.0).abs() < 0.01);
+        assert!((table.core_efficiency(0) - 500.0).abs() < 0.01);
+        assert_eq!(table.core_efficiency(1), 0.0);
+        assert_eq!(table.core_efficiency(2), 0.0);
+        assert_eq!(table.core_efficiency(7), 0.0);
+        assert_eq!(PmTable::default().efficiency(), 0.0);
+    }
+
+    #[test]
+    fn test_avg_and_max_core_freq() {
+        let data = create_test_pm_table(8, 0x240903);
+        let mut table = PmTable::parse(&data, 0x240903, Codename::Vermeer, 8).unwrap();
+
+        // 4500..4850 and 4400..4750 in steps of 50
+        assert!((table.avg_core_freq() - 4675.0).abs() < 0.01);
+        assert!((table.max_core_freq() - 4850.0).abs() < 0.01);
+        assert!((table.avg_core_freq_eff() - 4575.0).abs() < 0.01);
+        assert!((table.max_core_freq_eff() - 4750.0).abs() < 0.01);
+
+        // Unavailable cores don't drag the average down
+        table.core_freqs[0] = 0.0;
+        table.core_freqs[1] = 0.0;
+        assert!((table.avg_core_freq() - 4725.0).abs() < 0.01);
+        assert_eq!(PmTable::default().avg_core_freq(), 0.0);
+    }
+
+    #[test]
+    fn test_binding_limit() {
+        let data = create_test_pm_table(8, 0x240903);
+        let table = PmTable::parse(&data, 0x240903, Codename::Vermeer, 8).unwrap();
+        // Thermal (65.2/90 = 72%) edges out EDC (98.7/140 = 70%)
+        let (limit, util) = table.binding_limit();
+        assert_eq!(limit, Limit::Thermal);
+        assert!((util - 65.2 / 90.0).abs() < 0.001);
+        assert!(!table.is_throttling());
+
+        let throttled = PmTable { ppt_value: 141.0, ..table };
+        assert_eq!(throttled.binding_limit().0, Limit::Ppt);
+        assert!(throttled.is_throttling());
+    }
+
+    #[test]
+    fn test_throttle_status() {
+        let base = PmTable {
+            ppt_value: 50.0,
+            ppt_limit: 100.0,
+            tdc_value: 50.0,
+            tdc_limit: 100.0,
+            edc_value: 50.0,
+            edc_limit: 100.0,
+            tctl: 50.0,
+            thm_limit: 100.0,
+            ..Default::default()
+        };
+        assert_eq!(base.throttle_status(), ThrottleStatus::Unconstrained);
+        assert_eq!(base.throttle_status().to_string(), "None");
+
+        let cases = [
+            (PmTable { ppt_value: 98.0, ..base.clone() }, Limit::Ppt),
+            (PmTable { tdc_value: 96.0, ..base.clone() }, Limit::Tdc),
+            (PmTable { edc_value: 100.0, ..base.clone() }, Limit::Edc),
+            (PmTable { tctl: 95.0, ..base.clone() }, Limit::Thermal),
+        ];
+        for (table, limit) in cases {
+            assert_eq!(table.throttle_status(), ThrottleStatus::Limited(limit));
+        }
+
+        // Just under the threshold doesn't count
+        let close = PmTable { ppt_value: 94.0, ..base };
+        assert_eq!(close.throttle_status(), ThrottleStatus::Unconstrained);
+    }
+
+    #[test]
+    fn test_is_core_parked() {
+        let table = PmTable {
+            core_temps: vec![60.0, 55.0, 0.0],
+            core_freqs_eff: vec![4400.0, 3.5, 0.0],
+            ..Default::default()
+        };
+        assert!(!table.is_core_parked(0));
+        assert!(table.is_core_parked(1));
+        // No temperature: not populated rather than parked
+        assert!(!table.is_core_parked(2));
+        assert!(!table.is_core_parked(3));
+
+        // Layout without per-core frequency data
+        let no_freq = PmTable { core_temps: vec![60.0], ..Default::default() };
+        assert!(!no_freq.is_core_parked(0));
+    }
+
+    #[test]
+    fn test_ccd_helpers() {
+        // 5950X-style part: two CCDs of 8, one core on CCD1 not reporting
+        let mut core_temps: Vec<f32> = (0..16).map(|i| if i < 8 { 60.0 } else { 70.0 }).collect();
+        core_temps[15] = 0.0;
+        let table = PmTable {
+            codename: Codename::Vermeer,
+            core_temps,
+            core_power: vec![2.0; 16],
+            core_freqs: (0..16).map(|i| 4000.0 + 50.0 * i as f32).collect(),
+            ..Default::default()
+        };
+
+        assert_eq!(table.ccd_count(), 2);
+        assert_eq!(table.cores_in_ccd(0), 0..8);
+        assert_eq!(table.cores_in_ccd(1), 8..16);
+        assert!(table.cores_in_ccd(2).is_empty());
+        assert!((table.ccd_avg_temp(0) - 60.0).abs() < 0.01);
+        assert!((table.ccd_avg_temp(1) - 70.0).abs() < 0.01);
+        assert!((table.ccd_power(1) - 16.0).abs() < 0.01);
+        assert_eq!(table.ccd_power(2), 0.0);
+        assert_eq!(table.ccd_avg_freq(0), 4175.0);
+        assert_eq!(table.ccd_max_freq(1), 4750.0);
+        assert_eq!(table.ccd_max_freq(2), 0.0);
+    }
+
+    #[test]
+    fn test_active_core_count() {
+        let table = PmTable {
+            core_temps: vec![60.0, 55.0, 58.0, 54.0, 0.0],
+            core_c0: vec![92.0, 0.4, 30.0, 4.0, 0.0],
+            core_freqs_eff: vec![4400.0, 3.5, 3900.0, 120.0, 0.0],
+            ..Default::default()
+        };
+        assert_eq!(table.present_core_count(), 4);
+        assert_eq!(table.active_core_count(ACTIVE_C0_PERCENT), 2);
+        assert_eq!(table.active_core_count(0.0), 4);
+
+        // Without C0, parked cores are told apart by their clock
+        let no_c0 = PmTable { core_c0: vec![0.0; 5], ..table.clone() };
+        assert_eq!(no_c0.active_core_count(ACTIVE_C0_PERCENT), 3);
+        let cpuinfo_only = PmTable { core_freqs_eff: Vec::new(), core_freqs: vec![4400.0, 0.0, 3900.0, 0.0, 0.0], ..no_c0 };
+        assert_eq!(cpuinfo_only.active_core_count(ACTIVE_C0_PERCENT), 2);
+        assert_eq!(PmTable::default().active_core_count(ACTIVE_C0_PERCENT), 0);
+    }
+
+    #[test]
+    fn test_package_c0() {
+        let table = PmTable {
+            core_c0: vec![90.0, 0.0, 40.5, 12.0, 0.0, 65.5, 3.0, 0.0],
+            ..Default::default()
+        };
+        assert_eq!(table.package_c0(), 42.2);
+
+        let data = create_test_pm_table(8, 0x240903);
+        let parsed = PmTable::parse(&data, 0x240903, Codename::Vermeer, 8).unwrap();
+        let expected = parsed.core_c0.iter().sum::<f32>() / 8.0;
+        assert!((parsed.package_c0() - expected).abs() < 0.001);
+
+        // Layouts without C0 leave the per-core list zeroed
+        let data = create_test_pm_table(8, 0x620205);
+        let no_c0 = PmTable::parse(&data, 0x620205, Codename::GraniteRidge, 8).unwrap();
+        assert!(no_c0.package_c0().is_nan());
+        assert!(PmTable::default().package_c0().is_nan());
+    }
+
+    #[test]
+    fn test_core_freq_stretch() {
+        let table = PmTable {
+            core_freqs: vec![4500.0, 4200.0, 3600.0, 0.0],
+            core_freqs_eff: vec![4400.0, 1050.5, 3650.0, 800.0],
+            ..Default::default()
+        };
+        assert_eq!(table.core_freq_stretch(0), 100.0);
+        assert_eq!(table.core_freq_stretch(1), 3149.5);
+        assert_eq!(table.core_freq_stretch(2), -50.0);
+        assert!(table.core_freq_stretch(3).is_nan());
+        assert!(table.core_freq_stretch(4).is_nan());
+        assert!(PmTable::default().core_freq_stretch(0).is_nan());
+    }
+
+    #[test]
+    fn test_is_blank() {
+        assert!(PmTable::default().is_blank());
+        let data = create_test_pm_table(8, 0x240903);
+        assert!(!PmTable::parse(&data, 0x240903, Codename::Vermeer, 8).unwrap().is_blank());
+    }
+
+    #[test]
+    fn test_thermal_headroom() {
+        let table = PmTable { tctl: 65.2, thm_limit: 90.0, core_temps: vec![60.0, 0.0], ..Default::default() };
+        assert!((table.thermal_headroom() - 24.8).abs() < 0.001);
+        assert_eq!(table.core_thermal_headroom(0), 30.0);
+        assert!(table.core_thermal_headroom(1).is_nan());
+        assert!(table.core_thermal_headroom(2).is_nan());
+
+        let at_limit = PmTable { tctl: 90.0, thm_limit: 90.0, ..Default::default() };
+        assert_eq!(at_limit.thermal_headroom(), 0.0);
+
+        let over_limit = PmTable { tctl: 95.5, thm_limit: 90.0, ..Default::default() };
+        assert_eq!(over_limit.thermal_headroom(), -5.5);
+
+        let unparsed = PmTable { tctl: 65.0, core_temps: vec![60.0], ..Default::default() };
+        assert!(unparsed.thermal_headroom().is_nan());
+        assert!(unparsed.core_thermal_headroom(0).is_nan());
+    }
+
+    #[test]
+    fn test_limit_utilization_without_limit() {
+        let table = PmTable { ppt_value: 50.0, ..Default::default() };
+        assert_eq!(table.limit_utilization(Limit::Ppt), 0.0);
+    }
+
+    #[test]
+    fn test_read_f32_optional() {
+        let data = 88.5f32.to_le_bytes();
+        assert_eq!(read_f32_optional(&data, 0), Some(88.5));
+        assert_eq!(read_f32_optional(&data, 0xFFFF), None);
+        assert_eq!(read_f32_optional(&data, 2), None);
+    }
+
+    #[test]
+    fn test_read_f32_bounds() {
+        let mut data = vec![0u8; 3];
+        data.extend_from_slice(&(-1.25f32).to_le_bytes());
+        // Unaligned offsets read the same bytes any host would
+        assert_eq!(read_f32(&data, 3).unwrap(), -1.25);
+        assert_eq!(read_f32_safe_with_marker(&data, 3), -1.25);
+        assert!(matches!(
+            read_f32(&data, 4),
+            Err(SmuError::InvalidPmTableSize { expected: 8, actual: 7 })
+        ));
+        assert!(read_f32(&data, usize::MAX).is_err());
+        assert_eq!(read_f32_safe_with_marker(&data, 0xFFFF), 0.0);
+    }
+
+    #[test]
+    fn test_codename_str_is_static() {
+        let data = create_test_pm_table(8, 0x240903);
+        let table = PmTable::parse(&data, 0x240903, Codename::Vermeer, 8).unwrap();
+        assert!(matches!(table.codename_str, Cow::Borrowed("Vermeer")));
+    }
+
     #[test]
     fn test_granite_ridge_offsets() {
-        let data = create_test_pm_table(16, 0x00620205);
-        let table = PmTable::parse(&data, 0x00620205, Codename::GraniteRidge, 16).unwrap();
+        let data = create_test_pm_table(16, 0x620205);
+        let table = PmTable::parse(&data, 0x620205, Codename::GraniteRidge, 16).unwrap();
 
         assert!((table.ppt_limit - 142.0).abs() < 0.01);
         assert!((table.tctl - 65.2).abs() < 0.01);
         assert!((table.soc_temp - 42.1).abs() < 0.01);
         assert_eq!(table.core_temps.len(), 16);
     }
+
+    #[test]
+    fn test_wide_custom_layout() {
+        // No built-in layout is wider than 16 cores; an offsets file can be
+        let off = PmTableOffsets {
+            core_power_base: 0x400,
+            core_temp_base: 0x580,
+            core_freq_base: 0x700,
+            core_freqeff_base: 0x880,
+            core_c0_base: 0xA00,
+            max_cores: MAX_CORES,
+            ..offsets::OFFSETS_0X240903
+        };
+        let mut data = create_test_pm_table(8, 0x240903);
+        data.resize(0xA00 + MAX_CORES * 4, 0);
+        for i in 0..MAX_CORES {
+            data[0x580 + i * 4..0x584 + i * 4].copy_from_slice(&(60.0 + i as f32 * 0.25).to_le_bytes());
+        }
+
+        let table = PmTable::parse_with_offsets(&data, &off, 0x240999, Codename::StormPeak, 64).unwrap();
+        assert_eq!(table.core_temps.len(), 64);
+        assert!((table.core_temps[63] - 75.75).abs() < 0.01);
+        // Eight 8-core CCDs
+        assert_eq!(table.ccd_count(), 8);
+        assert_eq!(table.cores_in_ccd(7), 56..64);
+        assert_eq!(table.ccd_core_label(63), "CCD7/Core7");
+
+        // The layout holds 96 cores; nothing more
+        let table = PmTable::parse_with_offsets(&data, &off, 0x240999, Codename::StormPeak, 128).unwrap();
+        assert_eq!(table.core_temps.len(), 96);
+    }
 }
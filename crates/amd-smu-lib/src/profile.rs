@@ -0,0 +1,190 @@
+use std::fmt;
+use crate::PmTable;
+
+/// How close (W) a PPT limit must be to a known tier to count as that tier
+pub const TIER_TOLERANCE_W: f32 = 2.0;
+
+/// How far (fraction of the stock value) a limit must exceed stock before
+/// PBO is suspected; boards round and nudge the stock values a little
+pub const PBO_MARGIN: f32 = 0.05;
+
+/// Effective power configuration inferred from the active PPT limit
+///
+/// The inference compares `ppt_limit` with the codename's known factory
+/// tiers (see `Codename::power_tiers`):
+///
+/// - within `TIER_TOLERANCE_W` of an Eco-only tier: `EcoMode`
+/// - within `TIER_TOLERANCE_W` of a SKU tier: `Stock`
+/// - above the highest tier: `PboEnhanced`
+/// - anything else: `Manual`
+///
+/// Limitations: the SKU isn't known, so a 105W part in 65W Eco Mode is
+/// indistinguishable from a 65W part at stock and is reported as `Stock`.
+/// Only PPT is considered; PBO setups that keep PPT at stock but raise
+/// TDC/EDC read as `Stock`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerProfile {
+    /// BIOS Eco Mode below any SKU default
+    EcoMode { tdp: u32 },
+    /// Matches a factory SKU configuration
+    Stock { tdp: u32 },
+    /// Limits raised beyond any factory configuration
+    PboEnhanced,
+    /// A limit that matches no known tier
+    Manual,
+    /// No tier data for this codename, or the limit wasn't parsed
+    Unknown,
+}
+
+impl fmt::Display for PowerProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EcoMode { tdp } => write!(f, "Eco ({}W)", tdp),
+            Self::Stock { tdp } => write!(f, "Stock ({}W)", tdp),
+            Self::PboEnhanced => f.write_str("PBO"),
+            Self::Manual => f.write_str("Manual"),
+            Self::Unknown => f.write_str("Unknown"),
+        }
+    }
+}
+
+/// Guess at whether Precision Boost Overdrive is raising the limits
+///
+/// A heuristic over the reported PPT/TDC/EDC limits, not a reading of the
+/// BIOS setting: PBO with stock limits (or PBO limits set back to stock)
+/// looks like `LikelyOff`, and a manual limit below stock isn't PBO
+/// either way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PboStatus {
+    /// A limit is more than `PBO_MARGIN` above the stock value
+    LikelyEnabled,
+    /// The limits match a factory tier
+    LikelyOff,
+    /// No tier data for this codename, a limit wasn't parsed, or PPT
+    /// matches no tier to compare TDC/EDC with
+    Unknown,
+}
+
+impl fmt::Display for PboStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::LikelyEnabled => "likely enabled",
+            Self::LikelyOff => "likely off",
+            Self::Unknown => "unknown",
+        })
+    }
+}
+
+impl PmTable {
+    /// Compare the limits with the codename's stock values; see `PboStatus`
+    ///
+    /// PPT above the highest factory tier means PBO; otherwise the tier PPT
+    /// matches supplies the stock TDC and EDC, so PBO setups that leave PPT
+    /// alone but raise the current limits are caught too.
+    pub fn pbo_status(&self) -> PboStatus {
+        let tiers = self.codename.power_tiers();
+        let Some(highest) = tiers.last() else {
+            return PboStatus::Unknown;
+        };
+        if self.ppt_limit <= 0.0 || !self.ppt_limit.is_finite() {
+            return PboStatus::Unknown;
+        }
+        let above = |limit: f32, stock: f32| limit > stock * (1.0 + PBO_MARGIN);
+        if above(self.ppt_limit, highest.ppt) {
+            return PboStatus::LikelyEnabled;
+        }
+        match tiers.iter().find(|t| (self.ppt_limit - t.ppt).abs() <= TIER_TOLERANCE_W) {
+            Some(tier) if above(self.tdc_limit, tier.tdc) || above(self.edc_limit, tier.edc) => {
+                PboStatus::LikelyEnabled
+            }
+            Some(_) => PboStatus::LikelyOff,
+            None => PboStatus::Unknown,
+        }
+    }
+
+    /// Classify the active power configuration; see `PowerProfile`
+    pub fn power_profile(&self) -> PowerProfile {
+        let tiers = self.codename.power_tiers();
+        let Some(highest) = tiers.last() else {
+            return PowerProfile::Unknown;
+        };
+        if self.ppt_limit <= 0.0 {
+            return PowerProfile::Unknown;
+        }
+
+        if let Some(tier) = tiers
+            .iter()
+            .find(|t| (self.ppt_limit - t.ppt).abs() <= TIER_TOLERANCE_W)
+        {
+            return if tier.eco {
+                PowerProfile::EcoMode { tdp: tier.tdp }
+            } else {
+                PowerProfile::Stock { tdp: tier.tdp }
+            };
+        }
+
+        if self.ppt_limit > highest.ppt {
+            PowerProfile::PboEnhanced
+        } else {
+            PowerProfile::Manual
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Codename;
+
+    fn table(codename: Codename, ppt_limit: f32) -> PmTable {
+        PmTable { codename, ppt_limit, ..Default::default() }
+    }
+
+    #[test]
+    fn test_stock_tiers() {
+        assert_eq!(table(Codename::Vermeer, 142.0).power_profile(), PowerProfile::Stock { tdp: 105 });
+        assert_eq!(table(Codename::Vermeer, 88.0).power_profile(), PowerProfile::Stock { tdp: 65 });
+        assert_eq!(table(Codename::GraniteRidge, 230.5).power_profile(), PowerProfile::Stock { tdp: 170 });
+    }
+
+    #[test]
+    fn test_eco_mode() {
+        let profile = table(Codename::Raphael, 60.0).power_profile();
+        assert_eq!(profile, PowerProfile::EcoMode { tdp: 45 });
+        assert_eq!(profile.to_string(), "Eco (45W)");
+    }
+
+    #[test]
+    fn test_pbo_and_manual() {
+        assert_eq!(table(Codename::Vermeer, 300.0).power_profile(), PowerProfile::PboEnhanced);
+        assert_eq!(table(Codename::Vermeer, 120.0).power_profile(), PowerProfile::Manual);
+    }
+
+    #[test]
+    fn test_pbo_against_stock_limits() {
+        let limits = |codename, ppt_limit, tdc_limit, edc_limit| PmTable {
+            codename,
+            ppt_limit,
+            tdc_limit,
+            edc_limit,
+            ..Default::default()
+        };
+        // Stock 5800X, and the same part with PBO's raised limits
+        assert_eq!(limits(Codename::Vermeer, 142.0, 95.0, 140.0).pbo_status(), PboStatus::LikelyOff);
+        assert_eq!(limits(Codename::Vermeer, 200.0, 140.0, 190.0).pbo_status(), PboStatus::LikelyEnabled);
+        // PPT left at stock, currents raised
+        assert_eq!(limits(Codename::Vermeer, 142.0, 95.0, 180.0).pbo_status(), PboStatus::LikelyEnabled);
+        // Small board deviations stay within the margin
+        assert_eq!(limits(Codename::GraniteRidge, 230.0, 165.0, 225.0).pbo_status(), PboStatus::LikelyOff);
+
+        assert_eq!(limits(Codename::Vermeer, 120.0, 95.0, 140.0).pbo_status(), PboStatus::Unknown);
+        assert_eq!(limits(Codename::Phoenix, 54.0, 50.0, 70.0).pbo_status(), PboStatus::Unknown);
+        assert_eq!(PboStatus::LikelyEnabled.to_string(), "likely enabled");
+    }
+
+    #[test]
+    fn test_unknown() {
+        assert_eq!(table(Codename::Phoenix, 54.0).power_profile(), PowerProfile::Unknown);
+        assert_eq!(table(Codename::Vermeer, 0.0).power_profile(), PowerProfile::Unknown);
+    }
+}
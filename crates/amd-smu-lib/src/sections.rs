@@ -0,0 +1,50 @@
+use bitflags::bitflags;
+
+bitflags! {
+    /// Groups of readings a front end shows, shared by the CLI's section
+    /// filters and the TUI's panel toggles
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct OutputSections: u8 {
+        /// Tctl, SoC and core temperatures, thermal headroom and fans
+        const TEMPS = 1 << 0;
+        /// PPT/TDC/EDC, package and SoC power
+        const POWER = 1 << 1;
+        /// Fabric, memory and core clocks, activity
+        const FREQ = 1 << 2;
+        /// Core and SoC voltages
+        const VOLTAGES = 1 << 3;
+        /// Per-core rows within the other sections
+        const CORES = 1 << 4;
+    }
+}
+
+impl Default for OutputSections {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+impl OutputSections {
+    /// Sections for the `--temps`/`--power`/`--freq` filters: the chosen
+    /// ones with their per-core rows, or everything when none is chosen
+    pub fn from_filters(temps: bool, power: bool, freq: bool) -> Self {
+        let mut sections = Self::empty();
+        sections.set(Self::TEMPS, temps);
+        sections.set(Self::POWER, power);
+        sections.set(Self::FREQ, freq);
+        if sections.is_empty() { Self::all() } else { sections | Self::CORES }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_filters() {
+        assert_eq!(OutputSections::from_filters(false, false, false), OutputSections::all());
+        let sections = OutputSections::from_filters(true, false, true);
+        assert_eq!(sections, OutputSections::TEMPS | OutputSections::FREQ | OutputSections::CORES);
+        assert!(!sections.contains(OutputSections::VOLTAGES));
+    }
+}
@@ -0,0 +1,187 @@
+use crate::pmtable::{headroom, max_nonzero, mean_nonzero};
+use crate::PmTable;
+
+/// Temperature readings (°C)
+///
+/// ```
+/// use amd_smu_lib::PmTable;
+///
+/// let table = PmTable { tctl: 72.0, thm_limit: 90.0, core_temps: vec![65.0, 71.5], ..Default::default() };
+/// let temps = table.temperatures();
+/// assert_eq!(temps.headroom(), 18.0);
+/// assert_eq!(temps.hottest_core(), Some((1, 71.5)));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Temperatures<'a> {
+    pub tctl: f32,
+    pub soc: f32,
+    pub limit: f32,
+    pub cores: &'a [f32],
+}
+
+impl Temperatures<'_> {
+    /// Degrees left before the thermal limit, NaN if the limit is unknown
+    pub fn headroom(&self) -> f32 {
+        headroom(self.limit, self.tctl)
+    }
+
+    /// Index and temperature of the hottest core, ignoring 0.0 placeholders
+    pub fn hottest_core(&self) -> Option<(usize, f32)> {
+        self.cores
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|(_, t)| *t > 0.0)
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+    }
+}
+
+/// Power (W) and current (A) readings with their limits
+///
+/// ```
+/// use amd_smu_lib::PmTable;
+///
+/// let table = PmTable { ppt_value: 71.0, ppt_limit: 142.0, ..Default::default() };
+/// assert_eq!(table.power().ppt_utilization(), 0.5);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct PowerMetrics<'a> {
+    pub package: f32,
+    pub soc: f32,
+    pub ppt_value: f32,
+    pub ppt_limit: f32,
+    pub tdc_value: f32,
+    pub tdc_limit: f32,
+    pub edc_value: f32,
+    pub edc_limit: f32,
+    pub cores: &'a [f32],
+}
+
+impl PowerMetrics<'_> {
+    /// PPT as a fraction of its limit, 0.0 if the limit is unknown
+    pub fn ppt_utilization(&self) -> f32 {
+        ratio(self.ppt_value, self.ppt_limit)
+    }
+
+    /// TDC as a fraction of its limit, 0.0 if the limit is unknown
+    pub fn tdc_utilization(&self) -> f32 {
+        ratio(self.tdc_value, self.tdc_limit)
+    }
+
+    /// EDC as a fraction of its limit, 0.0 if the limit is unknown
+    pub fn edc_utilization(&self) -> f32 {
+        ratio(self.edc_value, self.edc_limit)
+    }
+
+    /// Sum of per-core power
+    pub fn cores_total(&self) -> f32 {
+        self.cores.iter().sum()
+    }
+}
+
+/// Clock readings (MHz) and C0 residency (%)
+///
+/// ```
+/// use amd_smu_lib::PmTable;
+///
+/// let table = PmTable { core_freqs: vec![4500.0, 0.0, 4650.0], ..Default::default() };
+/// assert_eq!(table.frequencies().max_core(), 4650.0);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Frequencies<'a> {
+    pub fclk: f32,
+    pub mclk: f32,
+    pub cores: &'a [f32],
+    pub cores_effective: &'a [f32],
+    pub cores_c0: &'a [f32],
+}
+
+impl Frequencies<'_> {
+    /// Highest core clock, ignoring 0.0 placeholders
+    pub fn max_core(&self) -> f32 {
+        max_nonzero(self.cores)
+    }
+
+    /// Mean effective clock over cores that report one
+    pub fn average_effective(&self) -> f32 {
+        mean_nonzero(self.cores_effective)
+    }
+}
+
+// Grouped views borrowing from the table; the flat fields stay available
+impl PmTable {
+    /// Temperature readings
+    pub fn temperatures(&self) -> Temperatures<'_> {
+        Temperatures {
+            tctl: self.tctl,
+            soc: self.soc_temp,
+            limit: self.thm_limit,
+            cores: &self.core_temps,
+        }
+    }
+
+    /// Power and current readings with their limits
+    pub fn power(&self) -> PowerMetrics<'_> {
+        PowerMetrics {
+            package: self.package_power,
+            soc: self.soc_power,
+            ppt_value: self.ppt_value,
+            ppt_limit: self.ppt_limit,
+            tdc_value: self.tdc_value,
+            tdc_limit: self.tdc_limit,
+            edc_value: self.edc_value,
+            edc_limit: self.edc_limit,
+            cores: &self.core_power,
+        }
+    }
+
+    /// Clock readings and C0 residency
+    pub fn frequencies(&self) -> Frequencies<'_> {
+        Frequencies {
+            fclk: self.fclk,
+            mclk: self.mclk,
+            cores: &self.core_freqs,
+            cores_effective: &self.core_freqs_eff,
+            cores_c0: &self.core_c0,
+        }
+    }
+}
+
+fn ratio(value: f32, limit: f32) -> f32 {
+    if limit > 0.0 { value / limit } else { 0.0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_views_borrow_table_data() {
+        let table = PmTable {
+            tctl: 70.0,
+            thm_limit: 90.0,
+            core_temps: vec![60.0, 0.0, 68.0],
+            core_power: vec![5.0, 0.0, 7.5],
+            tdc_value: 50.0,
+            tdc_limit: 0.0,
+            core_freqs_eff: vec![4000.0, 0.0, 4400.0],
+            ..Default::default()
+        };
+
+        let temps = table.temperatures();
+        assert_eq!(temps.hottest_core(), Some((2, 68.0)));
+        assert!(std::ptr::eq(temps.cores, table.core_temps.as_slice()));
+
+        let power = table.power();
+        assert_eq!(power.cores_total(), 12.5);
+        assert_eq!(power.tdc_utilization(), 0.0);
+
+        assert_eq!(table.frequencies().average_effective(), 4200.0);
+    }
+
+    #[test]
+    fn test_hottest_core_ignores_placeholders() {
+        let table = PmTable { core_temps: vec![0.0, 0.0], ..Default::default() };
+        assert_eq!(table.temperatures().hottest_core(), None);
+    }
+}
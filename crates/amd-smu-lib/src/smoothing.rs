@@ -0,0 +1,150 @@
+use crate::PmTable;
+
+/// Exponential moving average over the readings people look at
+///
+/// Temperatures, power/current and core clocks are blended as
+/// `alpha * new + (1 - alpha) * previous`, so `alpha` is the weight of the
+/// newest sample: 1.0 shows every reading as-is, smaller values smooth
+/// more. Limits and other static fields are passed through untouched, and
+/// a 0.0 ("unavailable") reading is never blended.
+///
+/// ```
+/// use amd_smu_lib::{PmTable, Smoother};
+///
+/// let mut smoother = Smoother::new(0.5);
+/// smoother.apply(&PmTable { ppt_value: 80.0, ..Default::default() });
+/// let smoothed = smoother.apply(&PmTable { ppt_value: 100.0, ..Default::default() });
+/// assert_eq!(smoothed.ppt_value, 90.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Smoother {
+    alpha: f32,
+    previous: Option<PmTable>,
+}
+
+impl Smoother {
+    /// Smoother with the newest sample weighted by `alpha`, clamped to 0.0..=1.0
+    pub fn new(alpha: f32) -> Self {
+        Self { alpha: alpha.clamp(0.0, 1.0), previous: None }
+    }
+
+    /// Smoother for a user-given factor: None for 0, which means no
+    /// smoothing, otherwise the newest sample's weight as in `new`
+    pub fn for_factor(alpha: f32) -> Option<Self> {
+        (alpha > 0.0).then(|| Self::new(alpha))
+    }
+
+    pub fn alpha(&self) -> f32 {
+        self.alpha
+    }
+
+    /// Blend `table` into the running average and return the smoothed copy
+    pub fn apply(&mut self, table: &PmTable) -> PmTable {
+        let mut smoothed = table.clone();
+        if let Some(prev) = &self.previous {
+            let a = self.alpha;
+            for (value, old) in [
+                (&mut smoothed.tctl, prev.tctl),
+                (&mut smoothed.soc_temp, prev.soc_temp),
+                (&mut smoothed.ppt_value, prev.ppt_value),
+                (&mut smoothed.tdc_value, prev.tdc_value),
+                (&mut smoothed.edc_value, prev.edc_value),
+                (&mut smoothed.package_power, prev.package_power),
+                (&mut smoothed.soc_power, prev.soc_power),
+            ] {
+                *value = blend(a, *value, old);
+            }
+            blend_all(a, &mut smoothed.core_temps, &prev.core_temps);
+            blend_all(a, &mut smoothed.core_power, &prev.core_power);
+            blend_all(a, &mut smoothed.core_freqs, &prev.core_freqs);
+            blend_all(a, &mut smoothed.core_freqs_eff, &prev.core_freqs_eff);
+        }
+        self.previous = Some(smoothed.clone());
+        smoothed
+    }
+
+    /// Forget the running average; the next sample is shown as-is
+    pub fn reset(&mut self) {
+        self.previous = None;
+    }
+}
+
+/// Parse a smoothing factor as typed on the command line (`--smooth`),
+/// 0 to 1; see `Smoother::for_factor`
+pub fn parse_smoothing(s: &str) -> Result<f32, String> {
+    match s.trim().parse::<f32>() {
+        Ok(alpha) if (0.0..=1.0).contains(&alpha) => Ok(alpha),
+        _ => Err(format!("smoothing factor must be between 0 (off) and 1, got '{}'", s)),
+    }
+}
+
+fn blend(alpha: f32, new: f32, old: f32) -> f32 {
+    if new == 0.0 || old == 0.0 {
+        new
+    } else {
+        alpha * new + (1.0 - alpha) * old
+    }
+}
+
+/// Blend per-core values; a changed core count starts over
+fn blend_all(alpha: f32, values: &mut [f32], previous: &[f32]) {
+    if values.len() != previous.len() {
+        return;
+    }
+    for (value, old) in values.iter_mut().zip(previous) {
+        *value = blend(alpha, *value, *old);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(tctl: f32, cores: Vec<f32>) -> PmTable {
+        PmTable { tctl, core_freqs: cores, ppt_limit: 142.0, ..Default::default() }
+    }
+
+    #[test]
+    fn test_alpha_one_passes_readings_through() {
+        let mut smoother = Smoother::new(1.0);
+        smoother.apply(&sample(60.0, vec![4000.0]));
+        let table = smoother.apply(&sample(80.0, vec![5000.0]));
+        assert_eq!(table.tctl, 80.0);
+        assert_eq!(table.core_freqs, vec![5000.0]);
+    }
+
+    #[test]
+    fn test_ema_converges() {
+        let mut smoother = Smoother::new(0.5);
+        assert_eq!(smoother.apply(&sample(60.0, vec![4000.0, 0.0])).tctl, 60.0);
+
+        let table = smoother.apply(&sample(80.0, vec![5000.0, 3000.0]));
+        assert_eq!(table.tctl, 70.0);
+        assert_eq!(table.core_freqs, vec![4500.0, 3000.0]);
+        assert_eq!(table.ppt_limit, 142.0);
+
+        assert_eq!(smoother.apply(&sample(80.0, vec![5000.0, 3000.0])).tctl, 75.0);
+    }
+
+    #[test]
+    fn test_parse_smoothing() {
+        assert_eq!(parse_smoothing("0"), Ok(0.0));
+        assert_eq!(parse_smoothing("0.3"), Ok(0.3));
+        assert_eq!(parse_smoothing("1"), Ok(1.0));
+        assert!(Smoother::for_factor(0.0).is_none());
+        assert_eq!(Smoother::for_factor(0.3).map(|s| s.alpha()), Some(0.3));
+        assert!(parse_smoothing("1.5").is_err());
+        assert!(parse_smoothing("-0.1").is_err());
+        assert!(parse_smoothing("NaN").is_err());
+    }
+
+    #[test]
+    fn test_reset_and_core_count_change() {
+        let mut smoother = Smoother::new(0.5);
+        smoother.apply(&sample(60.0, vec![4000.0]));
+        assert_eq!(smoother.apply(&sample(80.0, vec![5000.0, 5000.0])).core_freqs, vec![5000.0, 5000.0]);
+
+        smoother.reset();
+        assert_eq!(smoother.apply(&sample(90.0, vec![])).tctl, 90.0);
+    }
+}
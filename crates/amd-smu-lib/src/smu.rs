@@ -1,12 +1,69 @@
+use std::collections::HashSet;
 use std::fs;
+use std::ops::ControlFlow;
 use std::path::{Path, PathBuf};
-use crate::{Codename, PmTable, Result, SmuError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime};
+use crate::pmtable::{layout, CPUINFO_PATH};
+use crate::{
+    limit_command, parse_pm_table_version, settable_range, supported_pm_table_versions, Codename, Limit, Mailbox, PmTable,
+    PmTableOffsets, Result, RiskAcknowledged, SmuError, Warning, MAX_CORES, SMU_ARG_COUNT, SMU_STATUS_OK,
+};
 
 const DEFAULT_SYSFS_PATH: &str = "/sys/kernel/ryzen_smu_drv";
 
+/// Retries for a PM table read in continuous sampling, before giving up
+pub const READ_RETRIES: u32 = 3;
+/// Pause between PM table read retries
+pub const RETRY_DELAY: Duration = Duration::from_millis(50);
+
+/// Control file some ryzen_smu builds need written before `pm_table` returns
+/// fresh data
+pub const PM_TABLE_REFRESH_FILE: &str = "pm_table_refresh";
+
+/// Environment variable overriding the default sysfs path
+pub const SYSFS_PATH_ENV: &str = "AMD_SMU_SYSFS";
+
+/// PM table facts that stay fixed for the life of the driver
+#[derive(Debug, Clone, Copy)]
+struct Metadata {
+    version: u32,
+    codename: Codename,
+    /// The driver has no `codename` file; `codename` is `Unsupported`
+    codename_missing: bool,
+    /// Size the driver reports for the blob
+    reported_size: usize,
+    core_count: usize,
+    /// Couldn't count cores from cpuinfo; `core_count` is the codename default
+    core_count_guessed: bool,
+}
+
 /// Reader for AMD SMU data via the ryzen_smu kernel module
 pub struct SmuReader {
     sysfs_path: PathBuf,
+    /// Loaded on the first PM table read; see `refresh_metadata`. Shared
+    /// with the handles async reads move onto the blocking pool
+    metadata: Arc<Mutex<Option<Metadata>>>,
+    /// Used instead of the driver's codename; see `with_codename`
+    codename_override: Option<Codename>,
+    /// Poke `PM_TABLE_REFRESH_FILE` before each blob read
+    refresh_before_read: bool,
+    /// Counted to size the per-core arrays; see `with_cpuinfo_path`
+    cpuinfo_path: PathBuf,
+    /// Used instead of counting cores; see `with_core_count`
+    core_count_override: Option<usize>,
+    /// Header bytes skipped before parsing; see `with_table_data_offset`
+    table_data_offset: usize,
+    /// Layout version used instead of the reported one; see
+    /// `with_forced_layout`
+    layout_override: Option<u32>,
+    /// Offsets used instead of any version's layout; see `with_offsets`
+    custom_offsets: Option<PmTableOffsets>,
+    /// Telemetry calibration used instead of the layout's; see
+    /// `with_power_scale`
+    power_scale: Option<f32>,
+    current_scale: Option<f32>,
 }
 
 impl SmuReader {
@@ -23,12 +80,223 @@ impl SmuReader {
             return Err(SmuError::ModuleNotLoaded(sysfs_path));
         }
 
-        Ok(Self { sysfs_path })
+        // Builds without a refresh trigger don't have the file at all
+        let refresh_before_read = fs::OpenOptions::new()
+            .write(true)
+            .open(sysfs_path.join(PM_TABLE_REFRESH_FILE))
+            .is_ok();
+
+        Ok(Self {
+            sysfs_path,
+            metadata: Arc::default(),
+            codename_override: None,
+            refresh_before_read,
+            cpuinfo_path: PathBuf::from(CPUINFO_PATH),
+            core_count_override: None,
+            table_data_offset: 0,
+            layout_override: None,
+            custom_offsets: None,
+            power_scale: None,
+            current_scale: None,
+        })
+    }
+
+    /// Create a reader for `path` if given, else `$AMD_SMU_SYSFS` if set,
+    /// else the default sysfs path
+    pub fn with_path_or_env<P: AsRef<Path>>(path: Option<P>) -> Result<Self> {
+        match path {
+            Some(path) => Self::with_path(path),
+            None => match std::env::var_os(SYSFS_PATH_ENV) {
+                Some(env_path) if !env_path.is_empty() => Self::with_path(env_path),
+                _ => Self::new(),
+            },
+        }
+    }
+
+    /// Find the ryzen_smu instance of every socket, via `$AMD_SMU_SYSFS`
+    /// or the default sysfs path (see `discover_from`)
+    pub fn discover_all() -> Result<Vec<Self>> {
+        match std::env::var_os(SYSFS_PATH_ENV) {
+            Some(env_path) if !env_path.is_empty() => Self::discover_from(env_path),
+            _ => Self::discover_from(DEFAULT_SYSFS_PATH),
+        }
+    }
+
+    /// Find the ryzen_smu instances next to `base`, one per socket
+    ///
+    /// Multi-socket systems get one driver directory per socket: `base`
+    /// itself (socket 0) and siblings with a numeric suffix, e.g.
+    /// `ryzen_smu_drv1`. Readers are ordered by that number. Fails with
+    /// `ModuleNotLoaded` if no instance exists.
+    pub fn discover_from<P: AsRef<Path>>(base: P) -> Result<Vec<Self>> {
+        let base = base.as_ref();
+        let not_loaded = || SmuError::ModuleNotLoaded(base.to_path_buf());
+        let (Some(parent), Some(prefix)) = (base.parent(), base.file_name().and_then(|n| n.to_str())) else {
+            return Err(not_loaded());
+        };
+
+        let mut found = Vec::new();
+        for entry in fs::read_dir(parent).map_err(|_| not_loaded())? {
+            let path = entry?.path();
+            let Some(suffix) = path.file_name().and_then(|n| n.to_str()).and_then(|n| n.strip_prefix(prefix)) else {
+                continue;
+            };
+            let socket = if suffix.is_empty() {
+                0
+            } else {
+                match suffix.parse::<usize>() {
+                    Ok(n) => n,
+                    Err(_) => continue,
+                }
+            };
+            if path.is_dir() {
+                found.push((socket, path));
+            }
+        }
+
+        if found.is_empty() {
+            return Err(not_loaded());
+        }
+        found.sort();
+        found.into_iter().map(|(_, path)| Self::with_path(path)).collect()
+    }
+
+    /// Parse PM tables as `codename` instead of what the driver reports
+    ///
+    /// An escape hatch for drivers that report the wrong codename (or 0)
+    /// on otherwise supported processors. `codename()` still returns the
+    /// driver's value.
+    pub fn with_codename(mut self, codename: Codename) -> Self {
+        self.codename_override = Some(codename);
+        self.metadata = Arc::default();
+        self
+    }
+
+    /// Fresh reader for the same path and codename override, with nothing
+    /// cached, e.g. after the driver was reloaded
+    pub fn reopen(&self) -> Result<Self> {
+        let reader = Self::with_path(&self.sysfs_path)?;
+        Ok(Self {
+            codename_override: self.codename_override,
+            refresh_before_read: self.refresh_before_read,
+            cpuinfo_path: self.cpuinfo_path.clone(),
+            core_count_override: self.core_count_override,
+            table_data_offset: self.table_data_offset,
+            layout_override: self.layout_override,
+            custom_offsets: self.custom_offsets,
+            power_scale: self.power_scale,
+            current_scale: self.current_scale,
+            ..reader
+        })
+    }
+
+    /// Write `PM_TABLE_REFRESH_FILE` before every PM table read, or don't
+    ///
+    /// On by default when the file exists and is writable. Some driver
+    /// builds only update `pm_table` when asked to and otherwise keep
+    /// returning a stale snapshot. A failed write is ignored and the read
+    /// goes ahead.
+    pub fn with_refresh_before_read(mut self, enabled: bool) -> Self {
+        self.refresh_before_read = enabled;
+        self
+    }
+
+    /// Count cores, and read per-core clocks on layouts without them, from
+    /// this file instead of /proc/cpuinfo
+    ///
+    /// If it can't be read, or lists no core ids, the core count falls back
+    /// to the codename's maximum and every table carries a warning saying
+    /// so; `read_pm_table_with_cores` avoids the guess.
+    pub fn with_cpuinfo_path<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.cpuinfo_path = path.as_ref().to_path_buf();
+        self.metadata = Arc::default();
+        self
+    }
+
+    /// Size per-core readings for exactly `count` cores on every read,
+    /// instead of counting them in cpuinfo
+    ///
+    /// For systems where the count comes out wrong, giving too many or too
+    /// few per-core rows. Fails with `InvalidCoreCount` outside
+    /// `1..=MAX_CORES`.
+    pub fn with_core_count(mut self, count: usize) -> Result<Self> {
+        self.core_count_override = Some(check_core_count(count)?);
+        self.metadata = Arc::default();
+        Ok(self)
+    }
+
+    /// Parse every PM table with the layout of `version`, whatever version
+    /// the driver reports
+    ///
+    /// For new steppings that report an unknown version but keep a known
+    /// layout, to get partial data rather than an `UnsupportedPmTableVersion`
+    /// error. Tables keep the reported `version` and carry a
+    /// `Warning::ForcedLayout`. Fails with `UnsupportedPmTableVersion` if
+    /// there's no layout for `version` either.
+    pub fn with_forced_layout(mut self, version: u32) -> Result<Self> {
+        if !supported_pm_table_versions().contains(&version) {
+            return Err(SmuError::UnsupportedPmTableVersion(version));
+        }
+        self.layout_override = Some(version);
+        Ok(self)
+    }
+
+    /// Parse every PM table with `offsets`, whatever version the driver
+    /// reports
+    ///
+    /// For hardware without a built-in layout, with offsets from e.g.
+    /// `PmTableOffsets::load`. Takes precedence over `with_forced_layout`;
+    /// tables keep the reported `version` and carry a
+    /// `Warning::CustomLayout`.
+    pub fn with_offsets(mut self, offsets: PmTableOffsets) -> Self {
+        self.custom_offsets = Some(offsets);
+        self
+    }
+
+    /// Multiply power readings by `scale` instead of the layout's
+    /// `power_scale`
+    ///
+    /// For boards whose telemetry is off by a known factor. Applies to
+    /// `ppt_value`, `package_power`, `soc_power` and `core_power`; limits
+    /// are left alone.
+    pub fn with_power_scale(mut self, scale: f32) -> Self {
+        self.power_scale = Some(scale);
+        self
+    }
+
+    /// Multiply `tdc_value` and `edc_value` by `scale` instead of the
+    /// layout's `current_scale`
+    pub fn with_current_scale(mut self, scale: f32) -> Self {
+        self.current_scale = Some(scale);
+        self
+    }
+
+    /// Skip `offset` bytes at the start of the `pm_table` blob before parsing
+    ///
+    /// For driver builds that put a header in front of the table, which
+    /// shows up as a blob longer than `pm_table_size`. The layout offsets
+    /// and the size check then apply to what follows the header; raw reads
+    /// still return the whole blob. Defaults to 0.
+    pub fn with_table_data_offset(mut self, offset: usize) -> Self {
+        self.table_data_offset = offset;
+        self
+    }
+
+    /// Whether PM table reads are preceded by a refresh write
+    pub fn refresh_before_read(&self) -> bool {
+        self.refresh_before_read
+    }
+
+    /// Directory this reader reads from
+    pub fn sysfs_path(&self) -> &Path {
+        &self.sysfs_path
     }
 
     /// Get the SMU firmware version string
+    ///
+    /// "Unknown" on stripped-down driver builds without a `version` file.
     pub fn smu_version(&self) -> Result<String> {
-        self.read_string("version")
+        Ok(self.read_optional_string("version")?.unwrap_or_else(|| "Unknown".to_string()))
     }
 
     /// Get the driver version string
@@ -36,72 +304,489 @@ impl SmuReader {
         self.read_string("drv_version")
     }
 
+    /// Labeled fan speeds (RPM) from hwmon; see `fan_speeds_from`
+    ///
+    /// Fans aren't part of the SMU, so this works whether or not the
+    /// driver is loaded, and finding none is an empty list.
+    pub fn fan_speeds(&self) -> Result<Vec<(String, u32)>> {
+        crate::hwmon::fan_speeds()
+    }
+
     /// Get the processor codename
+    ///
+    /// `Unsupported` on stripped-down driver builds without a `codename`
+    /// file.
     pub fn codename(&self) -> Result<Codename> {
-        let id_str = self.read_string("codename")?;
+        Ok(self.read_codename()?.unwrap_or_default())
+    }
+
+    /// The driver's codename, None if it has no `codename` file
+    fn read_codename(&self) -> Result<Option<Codename>> {
+        let Some(id_str) = self.read_optional_string("codename")? else {
+            return Ok(None);
+        };
         let id: u32 = id_str.trim().parse().unwrap_or(0);
-        Ok(Codename::from_id(id))
+        Ok(Some(Codename::from_id(id)))
     }
 
     /// Get the PM table version
     pub fn pm_table_version(&self) -> Result<u32> {
         let data = self.read_binary("pm_table_version")?;
-        // PM table version is a little-endian u32
+        // Some driver builds print the version as text instead of exposing
+        // a little-endian u32; a binary version never parses as text
+        if let Ok(text) = std::str::from_utf8(&data)
+            && let Some(version) = parse_pm_table_version(text)
+        {
+            return Ok(version);
+        }
         if data.len() >= 4 {
             Ok(u32::from_le_bytes([data[0], data[1], data[2], data[3]]))
         } else {
-            // Fallback: try reading as text (for compatibility)
-            let ver_str = String::from_utf8_lossy(&data);
-            let trimmed = ver_str.trim();
-            if trimmed.starts_with("0x") || trimmed.starts_with("0X") {
-                Ok(u32::from_str_radix(&trimmed[2..], 16).unwrap_or(0))
-            } else {
-                Ok(trimmed.parse().unwrap_or(0))
-            }
+            Ok(0)
         }
     }
 
     /// Get the PM table size in bytes
+    ///
+    /// A missing, empty or zero `pm_table_size` is `UnknownPmTableSize`:
+    /// without it, a short or padded blob can't be told from a good one.
     pub fn pm_table_size(&self) -> Result<usize> {
-        let size_str = self.read_string("pm_table_size")?;
-        Ok(size_str.trim().parse().unwrap_or(0))
+        let path = self.sysfs_path.join("pm_table_size");
+        let Some(size_str) = self.read_optional_string("pm_table_size")? else {
+            return Err(SmuError::UnknownPmTableSize(path));
+        };
+        match size_str.trim().parse() {
+            Ok(size) if size > 0 => Ok(size),
+            _ => Err(SmuError::UnknownPmTableSize(path)),
+        }
+    }
+
+    /// Read the raw PM table blob without parsing it
+    ///
+    /// Works for any PM table version, including unsupported ones, so the
+    /// bytes can be dumped for offline analysis.
+    pub fn read_pm_table_raw(&self) -> Result<Vec<u8>> {
+        if self.refresh_before_read {
+            // Best effort: a stale table beats no table
+            let _ = fs::write(self.sysfs_path.join(PM_TABLE_REFRESH_FILE), "1");
+        }
+        self.read_binary("pm_table")
     }
 
     /// Read and parse the PM table
+    ///
+    /// The version, codename, reported size and core count are read on the
+    /// first call and cached, so later calls only read the `pm_table` blob.
+    /// A driver that doesn't report the size fails with `UnknownPmTableSize`.
+    /// If the blob length disagrees with `pm_table_size`, the mismatch is
+    /// recorded in the table's `warnings`; a blob longer than the reported
+    /// size is cut down to the reported region before parsing. A blob too
+    /// short for the layout is parsed leniently (see `PmTable::parse_lenient`).
     pub fn read_pm_table(&self) -> Result<PmTable> {
-        let version = self.pm_table_version()?;
-        let codename = self.codename()?;
-        let data = self.read_binary("pm_table")?;
+        self.read_pm_table_with_raw().map(|(table, _)| table)
+    }
+
+    /// `read_pm_table` on tokio's blocking thread pool, so sysfs reads
+    /// don't stall the async runtime
+    ///
+    /// Shares this reader's metadata cache. Must be called from within a
+    /// tokio runtime.
+    ///
+    /// ```no_run
+    /// # async fn run() -> amd_smu_lib::Result<()> {
+    /// let reader = amd_smu_lib::SmuReader::new()?;
+    /// let table = reader.read_pm_table_async().await?;
+    /// println!("Tctl: {:.1}°C", table.tctl);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "async")]
+    pub async fn read_pm_table_async(&self) -> Result<PmTable> {
+        let reader = self.share();
+        tokio::task::spawn_blocking(move || reader.read_pm_table())
+            .await
+            .map_err(|e| SmuError::Io(std::io::Error::other(e)))?
+    }
+
+    /// Another handle on the same driver and metadata cache
+    #[cfg(feature = "async")]
+    fn share(&self) -> Self {
+        Self {
+            sysfs_path: self.sysfs_path.clone(),
+            metadata: Arc::clone(&self.metadata),
+            codename_override: self.codename_override,
+            refresh_before_read: self.refresh_before_read,
+            cpuinfo_path: self.cpuinfo_path.clone(),
+            core_count_override: self.core_count_override,
+            table_data_offset: self.table_data_offset,
+            layout_override: self.layout_override,
+            custom_offsets: self.custom_offsets,
+            power_scale: self.power_scale,
+            current_scale: self.current_scale,
+        }
+    }
+
+    /// Read and parse the PM table for exactly `core_count` cores, without
+    /// consulting cpuinfo; fails with `InvalidCoreCount` outside
+    /// `1..=MAX_CORES`
+    pub fn read_pm_table_with_cores(&self, core_count: usize) -> Result<PmTable> {
+        let core_count = check_core_count(core_count)?;
+        let meta = Metadata { core_count, core_count_guessed: false, ..self.metadata()? };
+        let data = self.read_pm_table_raw()?;
+        self.parse_with(&meta, data).map(|(table, _)| table)
+    }
+
+    /// Read and parse the PM table, also returning its warnings as
+    /// `Warning`s for callers that act on them
+    ///
+    /// The table's `warnings` hold the same list as text, as for every
+    /// other read.
+    pub fn read_pm_table_with_warnings(&self) -> Result<(PmTable, Vec<Warning>)> {
+        let meta = self.metadata()?;
+        let data = self.read_pm_table_raw()?;
+        self.parse_warned(&meta, &data)
+    }
+
+    /// Read and parse the PM table, retrying transient failures up to
+    /// `retries` more times with `delay` between attempts
+    pub fn read_pm_table_retry(&self, retries: u32, delay: Duration) -> Result<PmTable> {
+        with_retry(retries, delay, || self.read_pm_table())
+    }
+
+    /// Read and parse the PM table, also returning the raw bytes as read
+    /// from the driver
+    pub fn read_pm_table_with_raw(&self) -> Result<(PmTable, Vec<u8>)> {
+        let meta = self.metadata()?;
+        let data = self.read_pm_table_raw()?;
+        self.parse_with(&meta, data)
+    }
+
+    /// Take `count` samples, `interval` apart, as cheaply as possible
+    ///
+    /// Only the `pm_table` blob is re-read per sample; like every PM table
+    /// read this relies on the cached metadata, so it assumes the processor
+    /// and driver don't change mid-run.
+    pub fn sample_many(&self, count: usize, interval: Duration) -> Result<Vec<PmTable>> {
+        let meta = self.metadata()?;
+        let mut samples = Vec::with_capacity(count);
+        for i in 0..count {
+            if i > 0 && !interval.is_zero() {
+                thread::sleep(interval);
+            }
+            let data = self.read_pm_table_raw()?;
+            samples.push(self.parse_with(&meta, data)?.0);
+        }
+        Ok(samples)
+    }
+
+    /// Endless readings, `interval` apart
+    ///
+    /// The first reading is taken immediately; every later `next()` blocks
+    /// for `interval` first. Metadata is cached as for `sample_many`, and a
+    /// failed read is yielded as an error without ending the iteration, so
+    /// bound it with `take` or break out of the loop.
+    ///
+    /// ```no_run
+    /// use amd_smu_lib::SmuReader;
+    /// use std::time::Duration;
+    ///
+    /// let reader = SmuReader::new()?;
+    /// for table in reader.samples(Duration::from_secs(1)).take(10) {
+    ///     println!("Tctl: {:.1}°C", table?.tctl);
+    /// }
+    /// # Ok::<(), amd_smu_lib::SmuError>(())
+    /// ```
+    pub fn samples(&self, interval: Duration) -> impl Iterator<Item = Result<PmTable>> + '_ {
+        let mut first = true;
+        std::iter::from_fn(move || {
+            if !first && !interval.is_zero() {
+                thread::sleep(interval);
+            }
+            first = false;
+            Some(self.read_pm_table())
+        })
+    }
+
+    /// Call `callback` with a reading every `interval` until it returns
+    /// `ControlFlow::Break`, and return the break value
+    ///
+    /// Blocks the calling thread for the whole run; spawn a thread for it
+    /// to keep working meanwhile. The first reading is taken immediately.
+    /// Transient read failures are retried as for `with_retry`; anything
+    /// else ends the loop with the error.
+    ///
+    /// ```no_run
+    /// use amd_smu_lib::SmuReader;
+    /// use std::ops::ControlFlow;
+    /// use std::time::Duration;
+    ///
+    /// let reader = SmuReader::new()?;
+    /// let hot = reader.watch_with(Duration::from_secs(1), |table| {
+    ///     if table.tctl > 90.0 { ControlFlow::Break(table.tctl) } else { ControlFlow::Continue(()) }
+    /// })?;
+    /// println!("Tctl reached {:.1}°C", hot);
+    /// # Ok::<(), amd_smu_lib::SmuError>(())
+    /// ```
+    pub fn watch_with<B>(&self, interval: Duration, mut callback: impl FnMut(&PmTable) -> ControlFlow<B>) -> Result<B> {
+        loop {
+            let table = self.read_pm_table_retry(READ_RETRIES, RETRY_DELAY)?;
+            if let ControlFlow::Break(value) = callback(&table) {
+                return Ok(value);
+            }
+            if !interval.is_zero() {
+                thread::sleep(interval);
+            }
+        }
+    }
+
+    /// Re-read the cached PM table version, codename, size and core count
+    ///
+    /// PM table reads only fetch these once; call this if they may have
+    /// changed, e.g. after reloading the driver.
+    pub fn refresh_metadata(&self) -> Result<()> {
+        let meta = self.read_metadata()?;
+        *self.metadata.lock().unwrap_or_else(|e| e.into_inner()) = Some(meta);
+        Ok(())
+    }
 
-        // Detect core count from the data or use a reasonable default
-        let core_count = self.detect_core_count(&data, codename);
+    /// Cached metadata, loading it on first use
+    fn metadata(&self) -> Result<Metadata> {
+        let mut cached = self.metadata.lock().unwrap_or_else(|e| e.into_inner());
+        match *cached {
+            Some(meta) => Ok(meta),
+            None => {
+                let meta = self.read_metadata()?;
+                *cached = Some(meta);
+                Ok(meta)
+            }
+        }
+    }
 
-        PmTable::parse(&data, version, codename, core_count)
+    /// Static facts about the PM table, read from sysfs
+    fn read_metadata(&self) -> Result<Metadata> {
+        let (codename, codename_missing) = match self.codename_override {
+            Some(codename) => (codename, false),
+            None => match self.read_codename()? {
+                Some(codename) => (codename, false),
+                None => (Codename::Unsupported, true),
+            },
+        };
+        let (core_count, core_count_guessed) = match self.core_count_override.or_else(|| self.count_cores()) {
+            Some(count) => (count, false),
+            None => (codename.cores_per_ccd() * codename.max_ccds(), true),
+        };
+        Ok(Metadata {
+            version: self.pm_table_version()?,
+            codename,
+            codename_missing,
+            reported_size: self.pm_table_size()?,
+            core_count,
+            core_count_guessed,
+        })
     }
 
-    /// Detect the number of active cores
-    fn detect_core_count(&self, _data: &[u8], codename: Codename) -> usize {
-        // Try to read from /proc/cpuinfo or use codename defaults
-        if let Ok(cpuinfo) = fs::read_to_string("/proc/cpuinfo") {
-            let count = cpuinfo.matches("processor\t:").count();
-            if count > 0 {
-                return count;
+    fn parse_with(&self, meta: &Metadata, data: Vec<u8>) -> Result<(PmTable, Vec<u8>)> {
+        let (table, _) = self.parse_warned(meta, &data)?;
+        Ok((table, data))
+    }
+
+    /// Parse `data`, with the warnings both returned and recorded as text in
+    /// the table
+    fn parse_warned(&self, meta: &Metadata, data: &[u8]) -> Result<(PmTable, Vec<Warning>)> {
+        let Metadata { version, codename, core_count, .. } = *meta;
+        // The blob was just read; parsing takes microseconds
+        let read_at = SystemTime::now();
+
+        let table_data = data.get(self.table_data_offset..).unwrap_or_default();
+        let mut len = table_data.len();
+        let mut warnings = Vec::new();
+        if meta.codename_missing {
+            warnings.push(Warning::CodenameUnavailable { path: self.sysfs_path.join("codename") });
+        } else if !codename.expects_pm_table_version(version) {
+            // Usually stale driver state, e.g. after swapping CPUs
+            warnings.push(Warning::VersionCodenameMismatch { version, codename });
+        }
+        if meta.core_count_guessed {
+            warnings.push(Warning::CpuinfoUnavailable {
+                path: self.cpuinfo_path.clone(),
+                assumed_cores: core_count,
+                codename,
+            });
+        }
+        // The driver hands unprivileged readers a zeroed buffer on some
+        // kernels instead of failing with EACCES
+        if !table_data.is_empty() && table_data.iter().all(|&b| b == 0) {
+            warnings.push(Warning::AllZeroData);
+        }
+        let reported = meta.reported_size;
+        if reported != table_data.len() {
+            warnings.push(Warning::SizeMismatch {
+                reported,
+                actual: table_data.len(),
+                header_hint: table_data.len() > reported && self.table_data_offset == 0,
+            });
+            len = len.min(reported);
+        }
+
+        // A truncated table still yields whatever fields fit
+        let mut offsets = match (self.custom_offsets, self.layout_override) {
+            (Some(offsets), _) => {
+                warnings.push(Warning::CustomLayout { reported: version });
+                offsets
             }
+            (None, Some(forced)) if forced != version => {
+                warnings.push(Warning::ForcedLayout { reported: version, layout: forced });
+                layout(forced)?
+            }
+            _ => layout(version)?,
+        };
+        offsets.power_scale = self.power_scale.unwrap_or(offsets.power_scale);
+        offsets.current_scale = self.current_scale.unwrap_or(offsets.current_scale);
+        let cpuinfo = Some(self.cpuinfo_path.as_path());
+        let (mut table, mut parse_warnings) =
+            match PmTable::parse_inner(&table_data[..len], &offsets, version, codename, core_count, false, cpuinfo) {
+                Err(SmuError::InvalidPmTableSize { .. }) => {
+                    let (table, parse_warnings) =
+                        PmTable::parse_inner(&table_data[..len], &offsets, version, codename, core_count, true, cpuinfo)?;
+                    warnings.push(Warning::Truncated { len, missing: table.missing_fields.clone() });
+                    (table, parse_warnings)
+                }
+                result => result?,
+            };
+        // Size problems first, then whatever validation flagged
+        warnings.append(&mut parse_warnings);
+        table.warnings = warnings.iter().map(ToString::to_string).collect();
+        table.version = version;
+        table.timestamp = Some(read_at);
+        Ok((table, warnings))
+    }
+
+    /// Mailboxes this driver build has a command file for
+    ///
+    /// ryzen_smu only creates the files for mailboxes the processor has,
+    /// and older builds lack some, so this is what `send_smu_command` can
+    /// reach.
+    pub fn mailbox_interfaces(&self) -> Result<Vec<Mailbox>> {
+        if !self.sysfs_path.exists() {
+            return Err(SmuError::ModuleNotLoaded(self.sysfs_path.clone()));
+        }
+        Ok(Mailbox::ALL
+            .into_iter()
+            .filter(|mailbox| self.sysfs_path.join(mailbox.sysfs_name()).exists())
+            .collect())
+    }
+
+    /// Send a command to an SMU mailbox and return the argument slots as
+    /// the SMU left them
+    ///
+    /// Writes `args` to `smu_args`, the command to the mailbox's command
+    /// file, then reads back the status and response. Requires root and a
+    /// writable sysfs mount. Mailbox commands can change how the processor
+    /// behaves; only send commands you know are safe for your family.
+    pub fn send_smu_command(
+        &self,
+        mailbox: Mailbox,
+        cmd: u32,
+        args: [u32; SMU_ARG_COUNT],
+    ) -> Result<[u32; SMU_ARG_COUNT]> {
+        let arg_bytes: Vec<u8> = args.iter().flat_map(|a| a.to_le_bytes()).collect();
+        self.write_binary("smu_args", &arg_bytes)?;
+        self.write_binary(mailbox.sysfs_name(), &cmd.to_le_bytes())?;
+
+        let status = read_u32_le(&self.read_binary(mailbox.sysfs_name())?, 0);
+        if status != SMU_STATUS_OK {
+            return Err(SmuError::SmuCommandFailed { cmd, status });
         }
-        // Fallback based on codename
-        codename.cores_per_ccd() * codename.max_ccds()
+
+        let response = self.read_binary("smu_args")?;
+        Ok(std::array::from_fn(|i| read_u32_le(&response, i * 4)))
+    }
+
+    /// Set the package power (PPT) limit, in watts, until the next reboot
+    ///
+    /// Sent through RSMU with the command for the driver's codename (see
+    /// `limit_command`); a `with_codename` override is not used here. Fails
+    /// with `UnsupportedTuning` on families without a known command,
+    /// `TuningVersionMismatch` when the PM table version doesn't belong to
+    /// the codename, `LimitOutOfRange` outside the family's
+    /// `settable_range`, and `PermissionDenied` without root; nothing is
+    /// written in the first three cases.
+    pub fn set_ppt_limit(&self, watts: f32, ack: RiskAcknowledged) -> Result<()> {
+        self.set_limit(Limit::Ppt, watts, ack)
+    }
+
+    /// Set the TDC limit, in amps; see `set_ppt_limit`
+    pub fn set_tdc_limit(&self, amps: f32, ack: RiskAcknowledged) -> Result<()> {
+        self.set_limit(Limit::Tdc, amps, ack)
+    }
+
+    /// Set the EDC limit, in amps; see `set_ppt_limit`
+    pub fn set_edc_limit(&self, amps: f32, ack: RiskAcknowledged) -> Result<()> {
+        self.set_limit(Limit::Edc, amps, ack)
+    }
+
+    fn set_limit(&self, limit: Limit, value: f32, _ack: RiskAcknowledged) -> Result<()> {
+        // A wrong command number does something else entirely, so only the
+        // driver's codename will do, and only if the PM table agrees with it
+        let codename = self.codename()?;
+        let (Some(range), Some(cmd)) = (settable_range(codename, limit), limit_command(codename, limit)) else {
+            return Err(SmuError::UnsupportedTuning { limit, codename });
+        };
+        let version = self.pm_table_version()?;
+        if !codename.expects_pm_table_version(version) {
+            return Err(SmuError::TuningVersionMismatch { codename, version });
+        }
+        // NaN fails this too
+        if !range.contains(&value) {
+            return Err(SmuError::LimitOutOfRange { limit, value, range });
+        }
+        let mut args = [0; SMU_ARG_COUNT];
+        // The SMU takes milliwatts / milliamps
+        args[0] = (value * 1000.0).round() as u32;
+        self.send_smu_command(Mailbox::Rsmu, cmd, args).map(|_| ())
+    }
+
+    /// Number of physical cores in cpuinfo, if it can be read and lists them
+    fn count_cores(&self) -> Option<usize> {
+        let cpuinfo = fs::read_to_string(&self.cpuinfo_path).ok()?;
+        Some(count_physical_cores(&cpuinfo)).filter(|&count| count > 0)
     }
 
     fn read_string(&self, name: &str) -> Result<String> {
         let path = self.sysfs_path.join(name);
         self.check_readable(&path)?;
-        Ok(fs::read_to_string(&path)?)
+        fs::read_to_string(&path).map_err(|e| read_error(&path, e))
+    }
+
+    /// `read_string`, but None when only this file is missing from the
+    /// driver directory
+    fn read_optional_string(&self, name: &str) -> Result<Option<String>> {
+        match self.read_string(name) {
+            Err(SmuError::ModuleNotLoaded(_)) if self.sysfs_path.exists() => Ok(None),
+            result => result.map(Some),
+        }
     }
 
     fn read_binary(&self, name: &str) -> Result<Vec<u8>> {
         let path = self.sysfs_path.join(name);
         self.check_readable(&path)?;
-        Ok(fs::read(&path)?)
+        fs::read(&path).map_err(|e| read_error(&path, e))
+    }
+
+    fn write_binary(&self, name: &str, data: &[u8]) -> Result<()> {
+        let path = self.sysfs_path.join(name);
+        if !path.exists() {
+            return Err(SmuError::ModuleNotLoaded(path));
+        }
+        match fs::write(&path, data) {
+            Ok(()) => Ok(()),
+            // Read-only mounts surface as EROFS rather than EACCES
+            Err(e) if matches!(
+                e.kind(),
+                std::io::ErrorKind::PermissionDenied | std::io::ErrorKind::ReadOnlyFilesystem
+            ) => Err(SmuError::PermissionDenied(path)),
+            Err(e) => Err(e.into()),
+        }
     }
 
     fn check_readable(&self, path: &Path) -> Result<()> {
@@ -119,8 +804,142 @@ impl SmuReader {
     }
 }
 
+fn check_core_count(count: usize) -> Result<usize> {
+    if (1..=MAX_CORES).contains(&count) {
+        Ok(count)
+    } else {
+        Err(SmuError::InvalidCoreCount(count))
+    }
+}
+
+/// Metadata can be readable while the contents aren't (mode 0400 sysfs
+/// files), so the read itself also maps EACCES; anything else (e.g. EIO
+/// from the driver) keeps the path it happened on
+fn read_error(path: &Path, e: std::io::Error) -> SmuError {
+    if e.kind() == std::io::ErrorKind::PermissionDenied {
+        SmuError::PermissionDenied(path.to_path_buf())
+    } else {
+        SmuError::ReadFailed { path: path.to_path_buf(), source: e }
+    }
+}
+
 impl Default for SmuReader {
     fn default() -> Self {
         Self::new().expect("Failed to initialize SMU reader")
     }
 }
+
+/// Little-endian u32 at `offset`, 0 if the buffer is too short
+fn read_u32_le(data: &[u8], offset: usize) -> u32 {
+    data.get(offset..offset + 4)
+        .map_or(0, |b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+/// Run `read`, retrying transient errors up to `retries` more times
+///
+/// Errors that won't go away on their own (module missing, permissions,
+/// unsupported layout) are returned immediately.
+pub fn with_retry<T>(retries: u32, delay: Duration, mut read: impl FnMut() -> Result<T>) -> Result<T> {
+    let mut attempt = 0;
+    loop {
+        match read() {
+            Err(e) if e.is_transient() && attempt < retries => {
+                attempt += 1;
+                thread::sleep(delay);
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Count unique (`physical id`, `core id`) pairs in /proc/cpuinfo text
+///
+/// SMT siblings share a pair, so this counts physical cores rather than
+/// threads. Returns 0 if the kernel doesn't report core ids.
+fn count_physical_cores(cpuinfo: &str) -> usize {
+    let mut cores = HashSet::new();
+    for block in cpuinfo.split("\n\n") {
+        let mut physical_id = None;
+        let mut core_id = None;
+        for line in block.lines() {
+            let Some((key, value)) = line.split_once(':') else { continue };
+            match key.trim() {
+                "physical id" => physical_id = Some(value.trim()),
+                "core id" => core_id = Some(value.trim()),
+                _ => {}
+            }
+        }
+        if let (Some(package), Some(core)) = (physical_id, core_id) {
+            cores.insert((package, core));
+        }
+    }
+    cores.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cpuinfo_entry(processor: u32, physical_id: u32, core_id: u32) -> String {
+        format!(
+            "processor\t: {}\nvendor_id\t: AuthenticAMD\nmodel name\t: AMD Ryzen 7 5800X 8-Core Processor\n\
+             physical id\t: {}\nsiblings\t: 16\ncore id\t\t: {}\ncpu cores\t: 8\n",
+            processor, physical_id, core_id
+        )
+    }
+
+    #[test]
+    fn test_count_physical_cores_collapses_smt() {
+        // 8 cores / 16 threads: siblings are enumerated after all first threads
+        let cpuinfo: Vec<String> = (0..16).map(|cpu| cpuinfo_entry(cpu, 0, cpu % 8)).collect();
+        assert_eq!(count_physical_cores(&cpuinfo.join("\n")), 8);
+    }
+
+    #[test]
+    fn test_count_physical_cores_multiple_packages() {
+        let cpuinfo: Vec<String> = (0..8).map(|cpu| cpuinfo_entry(cpu, cpu / 4, cpu % 4)).collect();
+        assert_eq!(count_physical_cores(&cpuinfo.join("\n")), 8);
+    }
+
+    fn busy() -> SmuError {
+        SmuError::Io(std::io::Error::from(std::io::ErrorKind::ResourceBusy))
+    }
+
+    #[test]
+    fn test_with_retry_recovers_from_transient_errors() {
+        let mut calls = 0;
+        let result = with_retry(3, Duration::ZERO, || {
+            calls += 1;
+            if calls < 3 { Err(busy()) } else { Ok(calls) }
+        });
+        assert_eq!(result.unwrap(), 3);
+    }
+
+    #[test]
+    fn test_with_retry_gives_up() {
+        let mut calls = 0;
+        let result: Result<()> = with_retry(3, Duration::ZERO, || {
+            calls += 1;
+            Err(busy())
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, 4);
+    }
+
+    #[test]
+    fn test_with_retry_skips_permanent_errors() {
+        let mut calls = 0;
+        let result: Result<()> = with_retry(3, Duration::ZERO, || {
+            calls += 1;
+            Err(SmuError::UnsupportedPmTableVersion(0x123))
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn test_count_physical_cores_without_core_ids() {
+        let cpuinfo = "processor\t: 0\nvendor_id\t: AuthenticAMD\n\nprocessor\t: 1\nvendor_id\t: AuthenticAMD\n";
+        assert_eq!(count_physical_cores(cpuinfo), 0);
+    }
+}
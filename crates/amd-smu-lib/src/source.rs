@@ -0,0 +1,165 @@
+use crate::pmtable::{layout, with_warning_text};
+use crate::{Codename, PmTable, PmTableOffsets, Result, SmuReader, Warning};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Anything that produces PM table readings
+///
+/// Implemented by `SmuReader` for live hardware, `FileSource` for raw
+/// dumps and `MockSource` for canned tables, so monitoring loops can run
+/// without the driver.
+pub trait PmTableSource {
+    /// Take the next reading
+    fn read(&mut self) -> Result<PmTable>;
+
+    /// Firmware version, or a description of where readings come from
+    fn smu_version(&self) -> String;
+}
+
+impl PmTableSource for SmuReader {
+    fn read(&mut self) -> Result<PmTable> {
+        self.read_pm_table()
+    }
+
+    fn smu_version(&self) -> String {
+        SmuReader::smu_version(self).map_or_else(|_| "Unknown".to_string(), |v| v.trim().to_string())
+    }
+}
+
+impl<S: PmTableSource + ?Sized> PmTableSource for Box<S> {
+    fn read(&mut self) -> Result<PmTable> {
+        (**self).read()
+    }
+
+    fn smu_version(&self) -> String {
+        (**self).smu_version()
+    }
+}
+
+/// Raw PM table dump, decoded with a fixed layout version
+///
+/// The file is re-read on every `read`, so it can be overwritten between
+/// readings. A dump may come from another machine, so nothing is taken
+/// from this one: without `with_core_count` the per-core arrays run up to
+/// the last core the dump has readings for, and layouts without per-core
+/// clocks leave them unavailable.
+#[derive(Debug, Clone)]
+pub struct FileSource {
+    path: PathBuf,
+    version: u32,
+    codename: Codename,
+    core_count: Option<usize>,
+    offsets: Option<PmTableOffsets>,
+}
+
+impl FileSource {
+    /// Decode `path` as PM table `version`, as the one processor known to
+    /// report it, if there is exactly one
+    pub fn new<P: AsRef<Path>>(path: P, version: u32) -> Self {
+        let codename = match Codename::reporting_pm_table_version(version)[..] {
+            [codename] => codename,
+            _ => Codename::Unsupported,
+        };
+        Self { path: path.as_ref().to_path_buf(), version, codename, core_count: None, offsets: None }
+    }
+
+    /// Decode with `offsets` instead of the layout for the version, noting
+    /// it with a `Warning::CustomLayout`
+    pub fn with_offsets(mut self, offsets: PmTableOffsets) -> Self {
+        self.offsets = Some(offsets);
+        self
+    }
+
+    /// Decode as `codename` instead of the one guessed from the version
+    pub fn with_codename(mut self, codename: Codename) -> Self {
+        self.codename = codename;
+        self
+    }
+
+    /// Read `count` cores (capped by the layout) instead of stopping at
+    /// the last one with readings
+    pub fn with_core_count(mut self, count: usize) -> Self {
+        self.core_count = Some(count);
+        self
+    }
+}
+
+impl PmTableSource for FileSource {
+    fn read(&mut self) -> Result<PmTable> {
+        let offsets = match self.offsets {
+            Some(offsets) => offsets,
+            None => layout(self.version)?,
+        };
+        let data = fs::read(&self.path)?;
+        let parse = |core_count| PmTable::parse_inner(&data, &offsets, self.version, self.codename, core_count, false, None);
+        let core_count = match self.core_count {
+            Some(count) => count,
+            // Up to the last core with a temperature or power reading
+            None => {
+                let (table, _) = parse(offsets.max_cores)?;
+                (0..table.core_temps.len())
+                    .rfind(|&core| table.core_temps[core] > 0.0 || table.core_power.get(core).is_some_and(|p| *p > 0.0))
+                    .map_or(0, |core| core + 1)
+            }
+        };
+        let (table, mut warnings) = parse(core_count)?;
+        if self.offsets.is_some() {
+            warnings.insert(0, Warning::CustomLayout { reported: self.version });
+        }
+        Ok(with_warning_text((table, warnings)))
+    }
+
+    fn smu_version(&self) -> String {
+        format!("File {}", self.path.display())
+    }
+}
+
+/// Canned readings handed out in order; the last one repeats
+#[derive(Debug, Clone)]
+pub struct MockSource {
+    tables: Vec<PmTable>,
+    next: usize,
+}
+
+impl MockSource {
+    pub fn new(tables: Vec<PmTable>) -> Self {
+        Self { tables, next: 0 }
+    }
+}
+
+impl PmTableSource for MockSource {
+    fn read(&mut self) -> Result<PmTable> {
+        let table = self
+            .tables
+            .get(self.next)
+            .or(self.tables.last())
+            .cloned()
+            .unwrap_or_default();
+        self.next = (self.next + 1).min(self.tables.len());
+        Ok(table)
+    }
+
+    fn smu_version(&self) -> String {
+        "Mock".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_source_repeats_last() {
+        let tables = [60.0, 70.0].map(|tctl| PmTable { tctl, ..Default::default() });
+        let mut source = MockSource::new(tables.to_vec());
+        let readings: Vec<f32> = (0..4).map(|_| source.read().unwrap().tctl).collect();
+        assert_eq!(readings, vec![60.0, 70.0, 70.0, 70.0]);
+    }
+
+    #[test]
+    fn test_boxed_source() {
+        let mut source: Box<dyn PmTableSource> = Box::new(MockSource::new(vec![]));
+        assert_eq!(source.read().unwrap(), PmTable::default());
+        assert_eq!(source.smu_version(), "Mock");
+    }
+}
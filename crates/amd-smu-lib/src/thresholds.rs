@@ -0,0 +1,147 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use crate::{Result, SmuError};
+
+/// How a reading compares to its warn/critical thresholds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Normal,
+    Warning,
+    Critical,
+}
+
+/// Warn/critical pair; a value at or above a threshold is at that level
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Threshold {
+    pub warn: f32,
+    pub crit: f32,
+}
+
+impl Threshold {
+    pub const fn new(warn: f32, crit: f32) -> Self {
+        Self { warn, crit }
+    }
+
+    pub fn level(&self, value: f32) -> Level {
+        if value >= self.crit {
+            Level::Critical
+        } else if value >= self.warn {
+            Level::Warning
+        } else {
+            Level::Normal
+        }
+    }
+}
+
+/// Color thresholds shared by the CLI and the TUI
+///
+/// Loaded from TOML; sections left out keep their defaults:
+///
+/// ```toml
+/// [temperature]   # °C
+/// warn = 75.0
+/// crit = 90.0
+///
+/// [ppt]           # % of the limit, likewise [tdc] and [edc]
+/// warn = 80.0
+/// crit = 95.0
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThresholdConfig {
+    /// Tctl and per-core temperatures (°C)
+    pub temperature: Threshold,
+    /// PPT as a percentage of its limit
+    pub ppt: Threshold,
+    /// TDC as a percentage of its limit
+    pub tdc: Threshold,
+    /// EDC as a percentage of its limit
+    pub edc: Threshold,
+}
+
+impl Default for ThresholdConfig {
+    fn default() -> Self {
+        Self {
+            temperature: Threshold::new(70.0, 85.0),
+            ppt: Threshold::new(70.0, 90.0),
+            tdc: Threshold::new(70.0, 90.0),
+            edc: Threshold::new(70.0, 90.0),
+        }
+    }
+}
+
+impl ThresholdConfig {
+    /// `$XDG_CONFIG_HOME/amd-smu/config.toml`, falling back to
+    /// `~/.config/amd-smu/config.toml`
+    pub fn default_path() -> Option<PathBuf> {
+        let base = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(base.join("amd-smu").join("config.toml"))
+    }
+
+    /// Parse a config file, which must exist
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let invalid = |message: String| SmuError::InvalidConfig { path: path.to_path_buf(), message };
+        let text = fs::read_to_string(path).map_err(|e| invalid(e.to_string()))?;
+        toml::from_str(&text).map_err(|e| invalid(e.message().to_string()))
+    }
+
+    /// Load `path` if given, else the default path if that file exists,
+    /// else the defaults
+    pub fn load_or_default(path: Option<&Path>) -> Result<Self> {
+        if let Some(path) = path {
+            return Self::load(path);
+        }
+        match Self::default_path() {
+            Some(path) => match fs::metadata(&path) {
+                Ok(_) => Self::load(path),
+                Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+                Err(e) => Err(SmuError::InvalidConfig { path, message: e.to_string() }),
+            },
+            None => Ok(Self::default()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_threshold_levels() {
+        let temp = ThresholdConfig::default().temperature;
+        assert_eq!(temp.level(69.9), Level::Normal);
+        assert_eq!(temp.level(70.0), Level::Warning);
+        assert_eq!(temp.level(85.0), Level::Critical);
+    }
+
+    #[test]
+    fn test_load_partial_config() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(&path, "[temperature]\nwarn = 75.0\ncrit = 90\n\n[edc]\nwarn = 80.0\ncrit = 95.0\n").unwrap();
+
+        let config = ThresholdConfig::load(&path).unwrap();
+        assert_eq!(config.temperature, Threshold::new(75.0, 90.0));
+        assert_eq!(config.edc, Threshold::new(80.0, 95.0));
+        assert_eq!(config.ppt, ThresholdConfig::default().ppt);
+    }
+
+    #[test]
+    fn test_load_errors_name_the_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.toml");
+        assert!(matches!(ThresholdConfig::load(&path), Err(SmuError::InvalidConfig { .. })));
+
+        fs::write(&path, "[temprature]\nwarn = 1.0\ncrit = 2.0\n").unwrap();
+        let err = ThresholdConfig::load(&path).unwrap_err().to_string();
+        assert!(err.contains("config.toml") && err.contains("temprature"), "{}", err);
+    }
+}
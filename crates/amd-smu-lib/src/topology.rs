@@ -0,0 +1,127 @@
+use std::fs;
+use std::path::Path;
+use crate::Result;
+
+const DEFAULT_CPU_SYSFS_PATH: &str = "/sys/devices/system/cpu";
+
+/// Physical placement of a core as reported by the kernel's CPU topology
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CoreLocation {
+    /// Socket the core belongs to
+    pub package: u32,
+    /// Die (CCD) within the package
+    pub die: u32,
+    /// Core id within the package; gaps mark fused-off slots
+    pub core_id: u32,
+}
+
+/// Physical core topology, one entry per core (SMT siblings collapsed)
+///
+/// Cores are indexed in the order the kernel first enumerates them, which is
+/// the order PM table per-core data is reported in.
+#[derive(Debug, Clone, Default)]
+pub struct CoreTopology {
+    pub cores: Vec<CoreLocation>,
+}
+
+impl CoreTopology {
+    /// Read topology from the default sysfs location
+    pub fn read() -> Result<Self> {
+        Self::from_sysfs(DEFAULT_CPU_SYSFS_PATH)
+    }
+
+    /// Read topology from a sysfs-style `cpu` directory
+    pub fn from_sysfs<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let mut cpus: Vec<(u32, CoreLocation)> = Vec::new();
+
+        for entry in fs::read_dir(path.as_ref())? {
+            let entry = entry?;
+            let name = entry.file_name();
+            let Some(cpu) = name
+                .to_str()
+                .and_then(|n| n.strip_prefix("cpu"))
+                .and_then(|n| n.parse::<u32>().ok())
+            else {
+                continue;
+            };
+
+            let topo = entry.path().join("topology");
+            let read_id = |file: &str| -> Option<u32> {
+                fs::read_to_string(topo.join(file)).ok()?.trim().parse().ok()
+            };
+            // Offline CPUs have no topology directory
+            let (Some(package), Some(core_id)) = (read_id("physical_package_id"), read_id("core_id"))
+            else {
+                continue;
+            };
+            let die = read_id("die_id").unwrap_or(0);
+
+            cpus.push((cpu, CoreLocation { package, die, core_id }));
+        }
+
+        cpus.sort_by_key(|(cpu, _)| *cpu);
+        let mut cores: Vec<CoreLocation> = Vec::new();
+        for (_, location) in cpus {
+            if !cores.contains(&location) {
+                cores.push(location);
+            }
+        }
+
+        Ok(Self { cores })
+    }
+
+    /// Core indices ordered by physical placement (package, die, core id)
+    ///
+    /// Indices beyond the known topology keep their logical position at the
+    /// end, so the result always covers `0..core_count`.
+    pub fn physical_order(&self, core_count: usize) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..core_count.min(self.cores.len())).collect();
+        order.sort_by_key(|&i| self.cores[i]);
+        order.extend(self.cores.len()..core_count);
+        order
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn add_cpu(root: &Path, cpu: u32, package: u32, die: u32, core_id: u32) {
+        let topo = root.join(format!("cpu{}", cpu)).join("topology");
+        fs::create_dir_all(&topo).unwrap();
+        fs::write(topo.join("physical_package_id"), format!("{}\n", package)).unwrap();
+        fs::write(topo.join("die_id"), format!("{}\n", die)).unwrap();
+        fs::write(topo.join("core_id"), format!("{}\n", core_id)).unwrap();
+    }
+
+    #[test]
+    fn test_smt_siblings_collapse() {
+        let dir = TempDir::new().unwrap();
+        for core in 0..4 {
+            add_cpu(dir.path(), core, 0, 0, core);
+            add_cpu(dir.path(), core + 4, 0, 0, core);
+        }
+        // Non-cpu entries are ignored
+        fs::create_dir_all(dir.path().join("cpufreq")).unwrap();
+
+        let topo = CoreTopology::from_sysfs(dir.path()).unwrap();
+        assert_eq!(topo.cores.len(), 4);
+        assert_eq!(topo.physical_order(4), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_physical_order_follows_die_and_core_id() {
+        let dir = TempDir::new().unwrap();
+        // Kernel enumerates the second die first
+        add_cpu(dir.path(), 0, 0, 1, 8);
+        add_cpu(dir.path(), 1, 0, 1, 9);
+        add_cpu(dir.path(), 2, 0, 0, 1);
+        add_cpu(dir.path(), 3, 0, 0, 0);
+
+        let topo = CoreTopology::from_sysfs(dir.path()).unwrap();
+        assert_eq!(topo.physical_order(4), vec![3, 2, 0, 1]);
+        // Extra cores without topology keep their logical slot at the end
+        assert_eq!(topo.physical_order(6), vec![3, 2, 0, 1, 4, 5]);
+    }
+}
@@ -0,0 +1,83 @@
+use crate::{Codename, Limit};
+use std::ops::RangeInclusive;
+
+/// Proof that the caller means to write processor limits
+///
+/// Required by the `SmuReader::set_*_limit` methods. Limits outside what
+/// the board's VRMs and cooling handle can crash the system or shorten the
+/// processor's life, and nothing checks for that; only build one of these
+/// on an explicit request from the user.
+#[derive(Debug, Clone, Copy)]
+pub struct RiskAcknowledged(());
+
+impl RiskAcknowledged {
+    pub fn i_understand_the_risks() -> Self {
+        Self(())
+    }
+}
+
+/// RSMU command that sets `limit` on `codename`, if known
+///
+/// | Codename              | PPT  | TDC  | EDC  |
+/// |-----------------------|------|------|------|
+/// | Matisse, Vermeer      | 0x53 | 0x54 | 0x55 |
+/// | Raphael, GraniteRidge | 0x56 | 0x57 | 0x58 |
+///
+/// The argument is the new limit in mW or mA. Other families, and the
+/// thermal limit, have no known command.
+pub fn limit_command(codename: Codename, limit: Limit) -> Option<u32> {
+    let base = match codename {
+        Codename::Matisse | Codename::Vermeer => 0x53,
+        Codename::Raphael | Codename::GraniteRidge => 0x56,
+        _ => return None,
+    };
+    match limit {
+        Limit::Ppt => Some(base),
+        Limit::Tdc => Some(base + 1),
+        Limit::Edc => Some(base + 2),
+        Limit::Thermal => None,
+    }
+}
+
+/// Values `limit` may be set to on `codename`; None where
+/// `limit_command` has no command
+///
+/// | Codename              | PPT (W) | TDC (A) | EDC (A) |
+/// |-----------------------|---------|---------|---------|
+/// | Matisse, Vermeer      | 15-250  | 15-180  | 15-250  |
+/// | Raphael, GraniteRidge | 15-350  | 15-250  | 15-300  |
+///
+/// Wide enough for any PBO setting the socket's boards offer; mostly there
+/// to catch typos like 1420 for 142.0 before they reach the SMU.
+pub fn settable_range(codename: Codename, limit: Limit) -> Option<RangeInclusive<f32>> {
+    let (ppt, tdc, edc) = match codename {
+        Codename::Matisse | Codename::Vermeer => (250.0, 180.0, 250.0),
+        Codename::Raphael | Codename::GraniteRidge => (350.0, 250.0, 300.0),
+        _ => return None,
+    };
+    match limit {
+        Limit::Ppt => Some(15.0..=ppt),
+        Limit::Tdc => Some(15.0..=tdc),
+        Limit::Edc => Some(15.0..=edc),
+        Limit::Thermal => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limit_commands() {
+        assert_eq!(limit_command(Codename::Vermeer, Limit::Ppt), Some(0x53));
+        assert_eq!(limit_command(Codename::Matisse, Limit::Edc), Some(0x55));
+        assert_eq!(limit_command(Codename::GraniteRidge, Limit::Tdc), Some(0x57));
+        assert_eq!(limit_command(Codename::Raphael, Limit::Thermal), None);
+        assert_eq!(limit_command(Codename::Renoir, Limit::Ppt), None);
+        assert_eq!(limit_command(Codename::Unsupported, Limit::Ppt), None);
+        assert_eq!(settable_range(Codename::Vermeer, Limit::Tdc), Some(15.0..=180.0));
+        assert_eq!(settable_range(Codename::GraniteRidge, Limit::Ppt), Some(15.0..=350.0));
+        assert!(settable_range(Codename::Raphael, Limit::Thermal).is_none());
+        assert!(settable_range(Codename::Renoir, Limit::Ppt).is_none());
+    }
+}
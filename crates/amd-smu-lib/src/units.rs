@@ -0,0 +1,59 @@
+/// Unit to display temperatures in
+///
+/// The PM table always reports Celsius; conversion is a display concern.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TempUnit {
+    #[default]
+    Celsius,
+    Fahrenheit,
+}
+
+impl TempUnit {
+    /// Convert a Celsius reading into this unit
+    pub fn convert(self, celsius: f32) -> f32 {
+        match self {
+            Self::Celsius => celsius,
+            Self::Fahrenheit => to_fahrenheit(celsius),
+        }
+    }
+
+    /// Convert a Celsius difference (e.g. headroom) into this unit
+    pub fn convert_delta(self, celsius: f32) -> f32 {
+        match self {
+            Self::Celsius => celsius,
+            Self::Fahrenheit => celsius * 9.0 / 5.0,
+        }
+    }
+
+    /// Unit label, including the degree sign
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::Celsius => "°C",
+            Self::Fahrenheit => "°F",
+        }
+    }
+}
+
+pub fn to_fahrenheit(celsius: f32) -> f32 {
+    celsius * 9.0 / 5.0 + 32.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_fahrenheit() {
+        assert_eq!(to_fahrenheit(0.0), 32.0);
+        assert_eq!(to_fahrenheit(100.0), 212.0);
+        assert_eq!(to_fahrenheit(-40.0), -40.0);
+    }
+
+    #[test]
+    fn test_temp_unit() {
+        assert_eq!(TempUnit::Celsius.convert(65.0), 65.0);
+        assert_eq!(TempUnit::Fahrenheit.convert(65.0), 149.0);
+        assert_eq!(TempUnit::Fahrenheit.convert_delta(10.0), 18.0);
+        assert_eq!(TempUnit::Fahrenheit.symbol(), "°F");
+    }
+}
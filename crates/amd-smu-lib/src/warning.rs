@@ -0,0 +1,126 @@
+use crate::Codename;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+
+/// Non-fatal problem noticed while reading a PM table
+///
+/// `SmuReader::read_pm_table_with_warnings` returns these alongside the
+/// table; every read also records their text in `PmTable::warnings`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Warning {
+    /// The processor isn't known to report this PM table version, usually
+    /// stale driver state
+    VersionCodenameMismatch { version: u32, codename: Codename },
+    /// The driver has no `codename` file, so the table was parsed by its
+    /// version alone as `Codename::Unsupported`
+    CodenameUnavailable { path: PathBuf },
+    /// Cores couldn't be counted from cpuinfo, so the codename's maximum
+    /// was assumed
+    CpuinfoUnavailable { path: PathBuf, assumed_cores: usize, codename: Codename },
+    /// More cores were asked for than the layout holds; only the first
+    /// `max_cores` were read
+    CoresBeyondLayout { requested: usize, max_cores: usize },
+    /// The layout has no per-core clocks and cpuinfo couldn't supply them
+    CoreClocksUnavailable { path: PathBuf, error: String },
+    /// Parsed with another version's layout, as asked, instead of the
+    /// reported version's
+    ForcedLayout { reported: u32, layout: u32 },
+    /// Parsed with caller-supplied offsets instead of any built-in layout
+    CustomLayout { reported: u32 },
+    /// The blob is all zeros, which the driver hands out instead of EACCES
+    /// on some kernels
+    AllZeroData,
+    /// The blob length disagrees with `pm_table_size`; `header_hint` when a
+    /// longer blob may start with a header
+    SizeMismatch { reported: usize, actual: usize, header_hint: bool },
+    /// The blob ended early; these fields read as 0.0
+    Truncated { len: usize, missing: Vec<&'static str> },
+    /// Per-core power sums to far more or less than `package`, the
+    /// package's core power; the per-core offsets are likely wrong
+    CorePowerMismatch { sum: f32, package: f32 },
+    /// A reading outside `range` was reset to 0.0
+    ClampedValue { field: &'static str, value: f32, range: RangeInclusive<f32> },
+    /// Per-core readings outside `range` were reset to 0.0 on these cores
+    ClampedCores { field: &'static str, cores: Vec<usize>, range: RangeInclusive<f32> },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::VersionCodenameMismatch { version, codename } => write!(
+                f,
+                "PM table version {:#x} is not one {} is known to report; readings may be wrong \
+                 (try reloading the ryzen_smu module)",
+                version, codename
+            ),
+            Self::CodenameUnavailable { path } => write!(
+                f,
+                "{} is missing; processor unknown, parsing by PM table version alone",
+                path.display()
+            ),
+            Self::CpuinfoUnavailable { path, assumed_cores, codename } => write!(
+                f,
+                "could not count cores from {}; assuming {} for {}",
+                path.display(),
+                assumed_cores,
+                codename
+            ),
+            Self::CoresBeyondLayout { requested, max_cores } => write!(
+                f,
+                "{} cores requested but the PM table layout holds {}; reading only those",
+                requested, max_cores
+            ),
+            Self::CoreClocksUnavailable { path, error } => write!(
+                f,
+                "per-core clocks unavailable: this layout has none and {} can't be read ({})",
+                path.display(),
+                error
+            ),
+            Self::ForcedLayout { reported, layout } => write!(
+                f,
+                "PM table version {:#x} parsed with the {:#x} layout as forced; readings may be wrong \
+                 or missing if the layouts differ",
+                reported, layout
+            ),
+            Self::CustomLayout { reported } => write!(
+                f,
+                "PM table version {:#x} parsed with custom offsets; readings are only as good as those offsets",
+                reported
+            ),
+            Self::AllZeroData => f.write_str(
+                "PM table read back as all zeros; the driver may be denying access \
+                 (try sudo or set up udev rules)",
+            ),
+            Self::SizeMismatch { reported, actual, header_hint } => write!(
+                f,
+                "PM table size mismatch: driver reports {} bytes, read {}{}",
+                reported,
+                actual,
+                if *header_hint { " (if the blob starts with a header, skip it with a table data offset)" } else { "" }
+            ),
+            Self::Truncated { len, missing } => {
+                write!(f, "PM table truncated at {} bytes; unavailable: {}", len, missing.join(", "))
+            }
+            Self::CorePowerMismatch { sum, package } => write!(
+                f,
+                "per-core power sums to {:.1} W against {:.1} W package core power; per-core readings may be wrong",
+                sum, package
+            ),
+            Self::ClampedValue { field, value, range } => {
+                write!(f, "{} reading {} outside {}..{}, ignored", field, value, range.start(), range.end())
+            }
+            Self::ClampedCores { field, cores, range } => {
+                let cores: Vec<String> = cores.iter().map(|i| i.to_string()).collect();
+                write!(
+                    f,
+                    "{} outside {}..{} on core(s) {}, ignored",
+                    field,
+                    range.start(),
+                    range.end(),
+                    cores.join(", ")
+                )
+            }
+        }
+    }
+}
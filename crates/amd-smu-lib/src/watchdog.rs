@@ -0,0 +1,133 @@
+use crate::{PmTable, PmTableSource, Result, SmuError, SmuReader};
+use std::time::{Duration, Instant};
+
+/// First wait before trying to reopen a vanished driver
+pub const RECONNECT_MIN_BACKOFF: Duration = Duration::from_millis(250);
+/// Longest wait between attempts to reopen a vanished driver
+pub const RECONNECT_MAX_BACKOFF: Duration = Duration::from_secs(5);
+
+/// `SmuReader` that survives the ryzen_smu module being reloaded
+///
+/// When a read finds the sysfs files gone (`ModuleNotLoaded`), the reader
+/// is re-created with `SmuReader::reopen` on a doubling backoff until they
+/// are back. Reads in between fail with `ModuleNotLoaded` without touching
+/// sysfs.
+///
+/// Every read also re-checks `pm_table_version`, which can change after a
+/// firmware update or resume, and reloads the cached metadata when it
+/// does so the table is parsed with the new version's offsets.
+pub struct ReconnectingReader {
+    reader: SmuReader,
+    /// Version of the last table read
+    version: Option<u32>,
+    min_backoff: Duration,
+    max_backoff: Duration,
+    /// While the driver is gone: current backoff and when to try next
+    retry: Option<(Duration, Instant)>,
+}
+
+impl ReconnectingReader {
+    pub fn new(reader: SmuReader) -> Self {
+        Self {
+            reader,
+            version: None,
+            min_backoff: RECONNECT_MIN_BACKOFF,
+            max_backoff: RECONNECT_MAX_BACKOFF,
+            retry: None,
+        }
+    }
+
+    /// Back off from `min` up to `max` between reconnect attempts
+    pub fn with_backoff(mut self, min: Duration, max: Duration) -> Self {
+        self.min_backoff = min;
+        self.max_backoff = max.max(min);
+        self
+    }
+
+    /// The driver went away and hasn't come back yet
+    pub fn is_reconnecting(&self) -> bool {
+        self.retry.is_some()
+    }
+
+    /// Read and parse the PM table, also returning the raw bytes, like
+    /// `SmuReader::read_pm_table_with_raw`
+    pub fn read_with_raw(&mut self) -> Result<(PmTable, Vec<u8>)> {
+        if let Some((backoff, next_attempt)) = self.retry {
+            if Instant::now() < next_attempt {
+                return Err(SmuError::ModuleNotLoaded(self.reader.sysfs_path().to_path_buf()));
+            }
+            match self.reader.reopen() {
+                Ok(reader) => self.reader = reader,
+                Err(e) => {
+                    self.schedule_retry((backoff * 2).min(self.max_backoff));
+                    return Err(e);
+                }
+            }
+        }
+
+        let result = match (self.version, self.reader.pm_table_version()) {
+            (Some(last), Ok(current)) if last != current => self.reader.refresh_metadata(),
+            _ => Ok(()),
+        }
+        .and_then(|()| self.reader.read_pm_table_with_raw());
+        if let Ok((table, _)) = &result {
+            self.version = Some(table.version);
+        }
+        match &result {
+            // The directory may be back before the module has created
+            // every file, so keep backing off from where we were
+            Err(SmuError::ModuleNotLoaded(_)) => {
+                let backoff = self.retry.map_or(self.min_backoff, |(b, _)| (b * 2).min(self.max_backoff));
+                self.schedule_retry(backoff);
+            }
+            _ => self.retry = None,
+        }
+        result
+    }
+
+    fn schedule_retry(&mut self, backoff: Duration) {
+        self.retry = Some((backoff, Instant::now() + backoff));
+    }
+}
+
+impl PmTableSource for ReconnectingReader {
+    fn read(&mut self) -> Result<PmTable> {
+        self.read_with_raw().map(|(table, _)| table)
+    }
+
+    fn smu_version(&self) -> String {
+        PmTableSource::smu_version(&self.reader)
+    }
+}
+
+/// Notices when the PM table version differs from the previous reading
+///
+/// Monitoring loops feed it every table so a version change mid-run can be
+/// reported instead of passing unnoticed.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct VersionTracker {
+    last: Option<u32>,
+}
+
+impl VersionTracker {
+    /// Record `table`'s version, returning the previous one if it changed
+    pub fn update(&mut self, table: &PmTable) -> Option<u32> {
+        let previous = self.last.replace(table.version)?;
+        (previous != table.version).then_some(previous)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockSource;
+
+    #[test]
+    fn test_version_tracker_across_ticks() {
+        let tables = [0x240903, 0x240903, 0x380904, 0x380904].map(|version| PmTable { version, ..Default::default() });
+        let mut source = MockSource::new(tables.to_vec());
+        let mut tracker = VersionTracker::default();
+        let changes: Vec<Option<u32>> = (0..4).map(|_| tracker.update(&source.read().unwrap())).collect();
+        assert_eq!(changes, vec![None, None, Some(0x240903), None]);
+    }
+}
@@ -1,26 +1,33 @@
-use amd_smu_lib::{Codename, SmuReader};
+use amd_smu_lib::{
+    crc32, pm_table_offsets, Codename, FileSource, FreqSource, Mailbox, PmTable, PmTableSource, ReconnectingReader, RiskAcknowledged, SmuError,
+    SmuReader, Warning, MAX_CORES, PM_TABLE_REFRESH_FILE, SMU_STATUS_OK,
+};
 use std::fs;
 use std::io::Write;
+use std::ops::ControlFlow;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tempfile::TempDir;
 
 fn create_mock_sysfs() -> TempDir {
     let dir = TempDir::new().unwrap();
-    let path = dir.path();
+    populate_mock_sysfs(dir.path());
+    dir
+}
 
+fn populate_mock_sysfs(path: &Path) {
     // Create mock sysfs files
     fs::write(path.join("version"), "SMU v46.54.0\n").unwrap();
     fs::write(path.join("drv_version"), "0.1.7\n").unwrap();
     fs::write(path.join("codename"), "12\n").unwrap(); // Vermeer
     // PM table version is stored as binary little-endian u32
-    fs::write(path.join("pm_table_version"), &0x240903u32.to_le_bytes()).unwrap();
+    fs::write(path.join("pm_table_version"), 0x240903u32.to_le_bytes()).unwrap();
     fs::write(path.join("pm_table_size"), "6832\n").unwrap();
 
     // Create mock PM table
     let pm_table = create_mock_pm_table();
     let mut file = fs::File::create(path.join("pm_table")).unwrap();
     file.write_all(&pm_table).unwrap();
-
-    dir
 }
 
 fn create_mock_pm_table() -> Vec<u8> {
@@ -84,8 +91,712 @@ fn test_read_pm_table_with_mock() {
     assert!((table.fclk - 1800.0).abs() < 0.01);
 }
 
+#[test]
+fn test_explicit_path_wins_over_env() {
+    let mock_dir = create_mock_sysfs();
+    let reader = SmuReader::with_path_or_env(Some(mock_dir.path())).unwrap();
+    assert_eq!(reader.pm_table_version().unwrap(), 0x240903);
+
+    assert!(SmuReader::with_path_or_env(Some("/nonexistent/path")).is_err());
+}
+
+#[test]
+fn test_send_smu_command() {
+    let mock_dir = create_mock_sysfs();
+    fs::write(mock_dir.path().join("smu_args"), [0u8; 24]).unwrap();
+    fs::write(mock_dir.path().join("rsmu_cmd"), [0u8; 4]).unwrap();
+    let reader = SmuReader::with_path(mock_dir.path()).unwrap();
+
+    // A plain file echoes the command back where the driver would put the
+    // status, so command 0x01 reads back as success
+    let response = reader.send_smu_command(Mailbox::Rsmu, 0x01, [7, 0, 0, 0, 0, 9]).unwrap();
+    assert_eq!(response, [7, 0, 0, 0, 0, 9]);
+
+    let failed = reader.send_smu_command(Mailbox::Rsmu, 0x5D, [0; 6]);
+    assert!(matches!(failed, Err(SmuError::SmuCommandFailed { cmd: 0x5D, status: 0x5D })));
+}
+
+#[test]
+fn test_set_limits() {
+    let mock_dir = create_mock_sysfs();
+    let args_path = mock_dir.path().join("smu_args");
+    fs::write(&args_path, [0u8; 24]).unwrap();
+    fs::write(mock_dir.path().join("rsmu_cmd"), [0u8; 4]).unwrap();
+    let reader = SmuReader::with_path(mock_dir.path()).unwrap();
+    let ack = RiskAcknowledged::i_understand_the_risks();
+
+    // The mock echoes the command back as the status, so the send itself
+    // "fails" after writing Vermeer's PPT command with the limit in mW
+    let result = reader.set_ppt_limit(120.5, ack);
+    assert!(matches!(result, Err(SmuError::SmuCommandFailed { cmd: 0x53, .. })), "{:?}", result);
+    assert_eq!(fs::read(&args_path).unwrap()[..4], 120_500u32.to_le_bytes());
+    assert!(matches!(reader.set_edc_limit(140.0, ack), Err(SmuError::SmuCommandFailed { cmd: 0x55, .. })));
+
+    // Rejected before anything is written
+    fs::write(&args_path, [0u8; 24]).unwrap();
+    for (result, value) in [(reader.set_ppt_limit(1420.0, ack), 1420.0), (reader.set_tdc_limit(5.0, ack), 5.0)] {
+        assert!(matches!(result, Err(SmuError::LimitOutOfRange { value: v, .. }) if v == value));
+    }
+    assert!(matches!(reader.set_tdc_limit(f32::NAN, ack), Err(SmuError::LimitOutOfRange { .. })));
+    // Within AM5's TDC range but not AM4's
+    assert!(matches!(reader.set_tdc_limit(200.0, ack), Err(SmuError::LimitOutOfRange { .. })));
+    fs::write(mock_dir.path().join("codename"), "20\n").unwrap(); // Raphael
+    let err = reader.set_ppt_limit(120.0, ack).unwrap_err();
+    assert!(matches!(err, SmuError::TuningVersionMismatch { codename: Codename::Raphael, version: 0x240903 }), "{:?}", err);
+    fs::write(mock_dir.path().join("codename"), "2\n").unwrap(); // Renoir
+    let err = reader.set_ppt_limit(25.0, ack).unwrap_err();
+    assert_eq!(err.to_string(), "Setting the PPT limit is not supported on Renoir");
+    assert_eq!(fs::read(&args_path).unwrap(), [0u8; 24]);
+
+    // The override only affects parsing; the command still follows the driver
+    fs::write(mock_dir.path().join("codename"), "12\n").unwrap();
+    let renoir = SmuReader::with_path(mock_dir.path()).unwrap().with_codename(Codename::Renoir);
+    assert!(matches!(renoir.set_ppt_limit(25.0, ack), Err(SmuError::SmuCommandFailed { cmd: 0x53, .. })));
+}
+
+#[test]
+fn test_set_limit_success() {
+    let mock_dir = create_mock_sysfs();
+    let args_path = mock_dir.path().join("smu_args");
+    fs::write(&args_path, [0u8; 24]).unwrap();
+    // A FIFO stands in for the driver: it takes the command, then answers
+    // with the OK status
+    let mailbox = mock_dir.path().join("rsmu_cmd");
+    assert!(std::process::Command::new("mkfifo").arg(&mailbox).status().unwrap().success());
+    let smu = std::thread::spawn(move || {
+        let cmd = fs::read(&mailbox).unwrap();
+        fs::write(&mailbox, SMU_STATUS_OK.to_le_bytes()).unwrap();
+        cmd
+    });
+
+    let reader = SmuReader::with_path(mock_dir.path()).unwrap();
+    reader.set_tdc_limit(95.0, RiskAcknowledged::i_understand_the_risks()).unwrap();
+    assert_eq!(smu.join().unwrap(), 0x54u32.to_le_bytes());
+    assert_eq!(fs::read(&args_path).unwrap()[..4], 95_000u32.to_le_bytes());
+}
+
+#[test]
+fn test_send_smu_command_missing_mailbox() {
+    let mock_dir = create_mock_sysfs();
+    fs::write(mock_dir.path().join("smu_args"), [0u8; 24]).unwrap();
+    let reader = SmuReader::with_path(mock_dir.path()).unwrap();
+
+    let result = reader.send_smu_command(Mailbox::Hsmp, 0x01, [0; 6]);
+    assert!(matches!(result, Err(SmuError::ModuleNotLoaded(_))));
+}
+
+#[test]
+fn test_reader_stamps_tables() {
+    let mock_dir = create_mock_sysfs();
+    let reader = SmuReader::with_path(mock_dir.path()).unwrap();
+    let before = SystemTime::now();
+    let table = reader.read_pm_table().unwrap();
+    let stamped = table.timestamp.expect("reader tables carry a timestamp");
+    assert!(stamped >= before && stamped <= SystemTime::now());
+
+    let parsed = PmTable::parse(&create_mock_pm_table(), 0x240903, Codename::Vermeer, 8).unwrap();
+    assert_eq!(parsed.timestamp, None);
+
+    // Unix millis in JSON, and back
+    let json = serde_json::to_value(&table).unwrap();
+    let millis = stamped.duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+    assert_eq!(json["timestamp"], millis);
+    let loaded: PmTable = serde_json::from_value(json).unwrap();
+    assert_eq!(loaded.timestamp, Some(UNIX_EPOCH + Duration::from_millis(millis)));
+    assert!(serde_json::to_value(&parsed).unwrap().get("timestamp").is_none());
+}
+
+#[test]
+fn test_mailbox_interfaces() {
+    let mock_dir = create_mock_sysfs();
+    let reader = SmuReader::with_path(mock_dir.path()).unwrap();
+    assert!(reader.mailbox_interfaces().unwrap().is_empty());
+
+    fs::write(mock_dir.path().join("rsmu_cmd"), [0u8; 4]).unwrap();
+    fs::write(mock_dir.path().join("hsmp_smu_cmd"), [0u8; 4]).unwrap();
+    assert_eq!(reader.mailbox_interfaces().unwrap(), vec![Mailbox::Rsmu, Mailbox::Hsmp]);
+}
+
+#[test]
+fn test_sample_many() {
+    let mock_dir = create_mock_sysfs();
+    let reader = SmuReader::with_path(mock_dir.path()).unwrap();
+
+    let samples = reader.sample_many(5, Duration::ZERO).unwrap();
+    assert_eq!(samples.len(), 5);
+    assert!(samples.iter().all(|t| (t.tctl - 65.2).abs() < 0.01));
+    assert!(reader.sample_many(0, Duration::ZERO).unwrap().is_empty());
+}
+
+#[test]
+fn test_samples_iterator() {
+    let mock_dir = create_mock_sysfs();
+    let reader = SmuReader::with_path(mock_dir.path()).unwrap();
+
+    let samples: Vec<PmTable> = reader.samples(Duration::from_millis(1)).take(3).collect::<Result<_, _>>().unwrap();
+    assert_eq!(samples.len(), 3);
+    assert!(samples.iter().all(|t| (t.tctl - 65.2).abs() < 0.01 && t.warnings.is_empty()));
+
+    // Errors are yielded, not the end of the stream
+    fs::remove_file(mock_dir.path().join("pm_table")).unwrap();
+    let mut samples = reader.samples(Duration::ZERO);
+    assert!(samples.next().unwrap().is_err());
+    assert!(samples.next().unwrap().is_err());
+}
+
+#[test]
+fn test_watch_with() {
+    let mock_dir = create_mock_sysfs();
+    let reader = SmuReader::with_path(mock_dir.path()).unwrap();
+
+    let mut samples = Vec::new();
+    let count = reader
+        .watch_with(Duration::from_millis(1), |table| {
+            samples.push(table.clone());
+            if samples.len() == 3 { ControlFlow::Break(samples.len()) } else { ControlFlow::Continue(()) }
+        })
+        .unwrap();
+    assert_eq!(count, 3);
+    assert!(samples.iter().all(|t| (t.tctl - 65.2).abs() < 0.01));
+
+    // A read that keeps failing ends the loop
+    fs::remove_file(mock_dir.path().join("pm_table")).unwrap();
+    let mut calls = 0;
+    assert!(reader.watch_with(Duration::ZERO, |_| -> ControlFlow<()> {
+        calls += 1;
+        ControlFlow::Continue(())
+    }).is_err());
+    assert_eq!(calls, 0);
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn test_read_pm_table_async() {
+    let mock_dir = create_mock_sysfs();
+    let reader = SmuReader::with_path(mock_dir.path()).unwrap();
+    let table = reader.read_pm_table_async().await.unwrap();
+    assert!((table.tctl - 65.2).abs() < 0.01);
+    // Same cache as the synchronous reads
+    fs::write(mock_dir.path().join("pm_table_size"), "100\n").unwrap();
+    assert!(reader.read_pm_table().unwrap().warnings.is_empty());
+}
+
+#[test]
+fn test_metadata_cached_until_refresh() {
+    let mock_dir = create_mock_sysfs();
+    let reader = SmuReader::with_path(mock_dir.path()).unwrap();
+    assert!(reader.read_pm_table().unwrap().warnings.is_empty());
+
+    // Cached size is still used after the driver starts reporting another one
+    fs::write(mock_dir.path().join("pm_table_size"), "4096\n").unwrap();
+    assert!(reader.read_pm_table().unwrap().warnings.is_empty());
+
+    reader.refresh_metadata().unwrap();
+    assert_eq!(reader.read_pm_table().unwrap().warnings.len(), 1);
+}
+
+#[test]
+fn test_discover_multiple_sockets() {
+    let root = TempDir::new().unwrap();
+    for name in ["ryzen_smu_drv", "ryzen_smu_drv1", "ryzen_smu_drv_old"] {
+        fs::create_dir(root.path().join(name)).unwrap();
+        populate_mock_sysfs(&root.path().join(name));
+    }
+    // Tell the sockets apart by Tctl
+    let mut second = create_mock_pm_table();
+    second[0x014..0x018].copy_from_slice(&71.0f32.to_le_bytes());
+    fs::write(root.path().join("ryzen_smu_drv1/pm_table"), second).unwrap();
+
+    let readers = SmuReader::discover_from(root.path().join("ryzen_smu_drv")).unwrap();
+    assert_eq!(readers.len(), 2);
+    assert!((readers[0].read_pm_table().unwrap().tctl - 65.2).abs() < 0.01);
+    assert!((readers[1].read_pm_table().unwrap().tctl - 71.0).abs() < 0.01);
+
+    let missing = SmuReader::discover_from(root.path().join("other_drv"));
+    assert!(matches!(missing, Err(SmuError::ModuleNotLoaded(_))));
+}
+
+#[test]
+fn test_text_pm_table_version() {
+    let mock_dir = create_mock_sysfs();
+    let reader = SmuReader::with_path(mock_dir.path()).unwrap();
+
+    fs::write(mock_dir.path().join("pm_table_version"), "0X00240903\n").unwrap();
+    assert_eq!(reader.pm_table_version().unwrap(), 0x240903);
+    assert!((reader.read_pm_table().unwrap().tctl - 65.2).abs() < 0.01);
+
+    fs::write(mock_dir.path().join("pm_table_version"), " 2361603 ").unwrap();
+    assert_eq!(reader.pm_table_version().unwrap(), 0x240903);
+
+    // Unknown versions are reported normalized
+    fs::write(mock_dir.path().join("pm_table_version"), "0x00999999\n").unwrap();
+    reader.refresh_metadata().unwrap();
+    let err = reader.read_pm_table().unwrap_err();
+    assert!(matches!(err, SmuError::UnsupportedPmTableVersion(0x999999)));
+    assert_eq!(err.to_string(), "Unsupported PM table version: 0x999999");
+}
+
+#[test]
+fn test_version_codename_mismatch_warns() {
+    let mock_dir = create_mock_sysfs();
+    fs::write(mock_dir.path().join("codename"), "20\n").unwrap(); // Raphael
+    let reader = SmuReader::with_path(mock_dir.path()).unwrap();
+
+    // Still read with the layout the version names
+    let table = reader.read_pm_table().unwrap();
+    assert!((table.tctl - 65.2).abs() < 0.01);
+    assert_eq!(table.warnings.len(), 1);
+    assert!(table.warnings[0].contains("0x240903"));
+    assert!(table.warnings[0].contains("Raphael"));
+}
+
+#[test]
+fn test_forced_layout() {
+    let mock_dir = create_mock_sysfs();
+    fs::write(mock_dir.path().join("pm_table_version"), 0x240904u32.to_le_bytes()).unwrap();
+    let reader = SmuReader::with_path(mock_dir.path()).unwrap();
+    assert!(matches!(reader.read_pm_table(), Err(SmuError::UnsupportedPmTableVersion(0x240904))));
+
+    // Parse the unknown minor revision with the Vermeer offsets
+    let reader = reader.with_forced_layout(0x240903).unwrap();
+    let (table, warnings) = reader.read_pm_table_with_warnings().unwrap();
+    assert!((table.tctl - 65.2).abs() < 0.01);
+    assert_eq!(table.version, 0x240904);
+    assert!(warnings.contains(&Warning::ForcedLayout { reported: 0x240904, layout: 0x240903 }));
+
+    let reader = SmuReader::with_path(mock_dir.path()).unwrap();
+    assert!(matches!(reader.with_forced_layout(0x123456), Err(SmuError::UnsupportedPmTableVersion(0x123456))));
+}
+
+#[test]
+fn test_custom_offsets() {
+    let mock_dir = create_mock_sysfs();
+    fs::write(mock_dir.path().join("pm_table_version"), 0x240904u32.to_le_bytes()).unwrap();
+    // Only Tctl located, at the SoC temperature's offset
+    let mut offsets = pm_table_offsets(0x240903).unwrap();
+    offsets.thm_value = offsets.soc_temp;
+    let reader = SmuReader::with_path(mock_dir.path()).unwrap().with_offsets(offsets);
+    let (table, warnings) = reader.read_pm_table_with_warnings().unwrap();
+    assert_eq!(table.tctl, table.soc_temp);
+    assert_eq!(table.version, 0x240904);
+    assert!(!warnings.iter().any(|w| matches!(w, Warning::ForcedLayout { .. })), "{:?}", warnings);
+    assert!(warnings.contains(&Warning::CustomLayout { reported: 0x240904 }));
+
+    // Wins over a forced layout
+    let reader = reader.with_forced_layout(0x240903).unwrap();
+    assert_eq!(reader.read_pm_table().unwrap().tctl, table.tctl);
+
+    let dump = mock_dir.path().join("pm_table");
+    let mut file = FileSource::new(&dump, 0x240904).with_offsets(offsets);
+    let from_file = file.read().unwrap();
+    assert_eq!(from_file.tctl, table.soc_temp);
+    assert!(from_file.warnings.iter().any(|w| w.contains("custom offsets")), "{:?}", from_file.warnings);
+}
+
+#[test]
+fn test_telemetry_scale_override() {
+    let mock_dir = create_mock_sysfs();
+    let plain = SmuReader::with_path(mock_dir.path()).unwrap().read_pm_table().unwrap();
+    let reader = SmuReader::with_path(mock_dir.path()).unwrap().with_power_scale(1.1).with_current_scale(2.0);
+    let scaled = reader.read_pm_table().unwrap();
+    assert_eq!(scaled.ppt_value, plain.ppt_value * 1.1);
+    assert_eq!(scaled.tdc_value, plain.tdc_value * 2.0);
+    assert_eq!(scaled.ppt_limit, plain.ppt_limit);
+    assert_eq!(scaled.tctl, plain.tctl);
+
+    // Survives a reopen, like the other overrides
+    assert_eq!(reader.reopen().unwrap().read_pm_table().unwrap().ppt_value, scaled.ppt_value);
+}
+
+#[test]
+fn test_codename_override() {
+    let mock_dir = create_mock_sysfs();
+    fs::write(mock_dir.path().join("codename"), "0\n").unwrap();
+    let reader = SmuReader::with_path(mock_dir.path()).unwrap().with_codename(Codename::Vermeer);
+
+    let table = reader.read_pm_table().unwrap();
+    assert_eq!(table.codename, Codename::Vermeer);
+    assert!(table.warnings.is_empty());
+    assert_eq!(reader.codename().unwrap(), Codename::Unsupported);
+}
+
+#[test]
+fn test_core_count_from_cpuinfo() {
+    let mock_dir = create_mock_sysfs();
+    let cpuinfo = mock_dir.path().join("cpuinfo");
+    let entry = |cpu: u32, core: u32| format!("processor\t: {}\nphysical id\t: 0\ncore id\t\t: {}\n\n", cpu, core);
+    fs::write(&cpuinfo, [entry(0, 0), entry(1, 1), entry(2, 0), entry(3, 1)].concat()).unwrap();
+
+    let reader = SmuReader::with_path(mock_dir.path()).unwrap().with_cpuinfo_path(&cpuinfo);
+    let table = reader.read_pm_table().unwrap();
+    assert_eq!(table.core_temps.len(), 2);
+    assert!(table.warnings.is_empty());
+}
+
+#[test]
+fn test_clocks_from_cpuinfo_path() {
+    let mock_dir = create_mock_sysfs();
+    fs::write(mock_dir.path().join("pm_table_version"), 0x620205u32.to_le_bytes()).unwrap();
+    let cpuinfo = mock_dir.path().join("cpuinfo");
+    let entry = |core: u32, mhz: u32| format!("processor\t: {}\nphysical id\t: 0\ncpu MHz\t\t: {}.000\ncore id\t\t: {}\n\n", core, mhz, core);
+    fs::write(&cpuinfo, [entry(0, 3000), entry(1, 3100)].concat()).unwrap();
+
+    // Granite Ridge has no per-core clocks in the table
+    let reader = SmuReader::with_path(mock_dir.path()).unwrap().with_cpuinfo_path(&cpuinfo);
+    let table = reader.read_pm_table().unwrap();
+    assert_eq!(table.freq_source, FreqSource::Cpuinfo);
+    assert_eq!(table.core_freqs, vec![3000.0, 3100.0]);
+}
+
+#[test]
+fn test_core_count_override_beats_cpuinfo() {
+    let mock_dir = create_mock_sysfs();
+    let cpuinfo = mock_dir.path().join("cpuinfo");
+    fs::write(&cpuinfo, "processor\t: 0\nphysical id\t: 0\ncore id\t\t: 0\n\n").unwrap();
+
+    let reader = SmuReader::with_path(mock_dir.path()).unwrap().with_cpuinfo_path(&cpuinfo);
+    assert_eq!(reader.read_pm_table().unwrap().core_temps.len(), 1);
+    let reader = reader.with_core_count(6).unwrap();
+    assert_eq!(reader.read_pm_table().unwrap().core_temps.len(), 6);
+    assert_eq!(reader.reopen().unwrap().read_pm_table().unwrap().core_temps.len(), 6);
+
+    // The 0x240903 layout stops at 16 cores, and says so
+    let table = reader.reopen().unwrap().with_core_count(32).unwrap().read_pm_table().unwrap();
+    assert_eq!(table.core_temps.len(), 16);
+    assert!(table.warnings.iter().any(|w| w.contains("32 cores requested")), "{:?}", table.warnings);
+
+    for count in [0, MAX_CORES + 1] {
+        assert!(matches!(reader.reopen().unwrap().with_core_count(count), Err(SmuError::InvalidCoreCount(n)) if n == count));
+        assert!(matches!(reader.read_pm_table_with_cores(count), Err(SmuError::InvalidCoreCount(_))));
+    }
+}
+
+#[test]
+fn test_metric_map() {
+    let mock_dir = create_mock_sysfs();
+    let reader = SmuReader::with_path(mock_dir.path()).unwrap();
+
+    let map = reader.read_pm_table_with_cores(4).unwrap().as_metric_map();
+    assert_eq!(map["tctl"], 65.2);
+    assert_eq!(map["ppt_limit"], 142.0);
+    assert_eq!(map["core.3.temp"], 61.5);
+    assert_eq!(map["core.3.freq_eff"], 4550.0);
+    assert!(!map.contains_key("core.4.temp"));
+
+    let cores = |map: &std::collections::BTreeMap<String, f32>| map.keys().filter(|k| k.starts_with("core.")).count();
+    let wide = reader.read_pm_table_with_cores(8).unwrap().as_metric_map();
+    assert_eq!(cores(&wide), 2 * cores(&map));
+    assert_eq!(wide.len() - cores(&wide), map.len() - cores(&map));
+}
+
+#[test]
+fn test_unreadable_cpuinfo_warns() {
+    let mock_dir = create_mock_sysfs();
+    let reader = SmuReader::with_path(mock_dir.path())
+        .unwrap()
+        .with_cpuinfo_path(mock_dir.path().join("missing"));
+
+    let table = reader.read_pm_table().unwrap();
+    assert!(!table.core_temps.is_empty());
+    assert!(table.warnings.iter().any(|w| w.contains("could not count cores")), "{:?}", table.warnings);
+
+    // An explicit count needs no guess
+    let table = reader.read_pm_table_with_cores(4).unwrap();
+    assert_eq!(table.core_temps.len(), 4);
+    assert!((table.core_temps[3] - 61.5).abs() < 0.01);
+    assert!(table.warnings.is_empty());
+}
+
+#[test]
+fn test_reconnect_after_driver_reload() {
+    let mock_dir = create_mock_sysfs();
+    let path = mock_dir.path().to_path_buf();
+    let mut reader = ReconnectingReader::new(SmuReader::with_path(&path).unwrap())
+        .with_backoff(Duration::from_millis(10), Duration::from_millis(40));
+    assert!(reader.read().is_ok());
+
+    // Module unloaded: reads fail until the directory is back
+    fs::remove_dir_all(&path).unwrap();
+    assert!(matches!(reader.read(), Err(SmuError::ModuleNotLoaded(_))));
+    assert!(reader.is_reconnecting());
+    std::thread::sleep(Duration::from_millis(20));
+    assert!(matches!(reader.read(), Err(SmuError::ModuleNotLoaded(_))));
+
+    fs::create_dir(&path).unwrap();
+    populate_mock_sysfs(&path);
+    std::thread::sleep(Duration::from_millis(50));
+    let table = reader.read().unwrap();
+    assert!((table.tctl - 65.2).abs() < 0.01);
+    assert!(!reader.is_reconnecting());
+}
+
+#[test]
+fn test_reconnecting_reader_follows_version_change() {
+    let mock_dir = create_mock_sysfs();
+    let mut reader = ReconnectingReader::new(SmuReader::with_path(mock_dir.path()).unwrap());
+    assert_eq!(reader.read().unwrap().version, 0x240903);
+
+    // The same bytes are now decoded with the Granite Ridge layout, whose
+    // Tctl sits where 0x240903 keeps TDC
+    fs::write(mock_dir.path().join("pm_table_version"), 0x620205u32.to_le_bytes()).unwrap();
+    let table = reader.read().unwrap();
+    assert_eq!(table.version, 0x620205);
+    assert!((table.tctl - 62.3).abs() < 0.01);
+}
+
+#[test]
+fn test_refresh_before_read() {
+    // Without a refresh file, reads go straight to pm_table
+    let mock_dir = create_mock_sysfs();
+    let reader = SmuReader::with_path(mock_dir.path()).unwrap();
+    assert!(!reader.refresh_before_read());
+    assert!((reader.read_pm_table().unwrap().tctl - 65.2).abs() < 0.01);
+
+    let refresh = mock_dir.path().join(PM_TABLE_REFRESH_FILE);
+    fs::write(&refresh, "").unwrap();
+    let reader = SmuReader::with_path(mock_dir.path()).unwrap();
+    assert!(reader.refresh_before_read());
+    assert!((reader.read_pm_table().unwrap().tctl - 65.2).abs() < 0.01);
+    assert_eq!(fs::read_to_string(&refresh).unwrap(), "1");
+
+    // Turned off, the file is left alone
+    fs::write(&refresh, "").unwrap();
+    let reader = reader.with_refresh_before_read(false);
+    reader.read_pm_table().unwrap();
+    assert_eq!(fs::read_to_string(&refresh).unwrap(), "");
+}
+
 #[test]
 fn test_module_not_loaded() {
     let result = SmuReader::with_path("/nonexistent/path");
     assert!(result.is_err());
 }
+
+#[test]
+fn test_pm_table_larger_than_reported_size() {
+    let mock_dir = create_mock_sysfs();
+    let mut pm_table = create_mock_pm_table();
+    pm_table.extend_from_slice(&[0xAA; 128]);
+    fs::write(mock_dir.path().join("pm_table"), &pm_table).unwrap();
+
+    let reader = SmuReader::with_path(mock_dir.path()).unwrap();
+    let table = reader.read_pm_table().unwrap();
+
+    assert!((table.tctl - 65.2).abs() < 0.01);
+    assert_eq!(table.warnings.len(), 1);
+    assert!(table.warnings[0].contains("6832"));
+}
+
+#[test]
+fn test_table_data_offset_skips_header() {
+    let mock_dir = create_mock_sysfs();
+    let mut blob = vec![0xAA; 16];
+    blob.extend_from_slice(&create_mock_pm_table());
+    fs::write(mock_dir.path().join("pm_table"), &blob).unwrap();
+
+    // Unskipped, the header shifts every field and trips the size check
+    let reader = SmuReader::with_path(mock_dir.path()).unwrap();
+    let table = reader.read_pm_table().unwrap();
+    assert!((table.tctl - 65.2).abs() > 0.01);
+    assert!(table.warnings[0].contains("header"), "{:?}", table.warnings);
+
+    let reader = reader.with_table_data_offset(16);
+    let (table, raw) = reader.read_pm_table_with_raw().unwrap();
+    assert!((table.tctl - 65.2).abs() < 0.01);
+    assert!(table.warnings.is_empty(), "{:?}", table.warnings);
+    assert_eq!(raw.len(), blob.len());
+}
+
+#[test]
+fn test_pm_table_smaller_than_reported_size() {
+    let mock_dir = create_mock_sysfs();
+    let pm_table = create_mock_pm_table();
+    fs::write(mock_dir.path().join("pm_table"), &pm_table[..4096]).unwrap();
+
+    let reader = SmuReader::with_path(mock_dir.path()).unwrap();
+    let table = reader.read_pm_table().unwrap();
+
+    assert!((table.tctl - 65.2).abs() < 0.01);
+    assert_eq!(table.warnings.len(), 1);
+}
+
+#[test]
+fn test_unknown_pm_table_size() {
+    let mock_dir = create_mock_sysfs();
+    let size_path = mock_dir.path().join("pm_table_size");
+    for size in ["0\n", "", "\n"] {
+        fs::write(&size_path, size).unwrap();
+        let reader = SmuReader::with_path(mock_dir.path()).unwrap();
+        assert!(matches!(reader.pm_table_size(), Err(SmuError::UnknownPmTableSize(ref path)) if *path == size_path));
+        let err = reader.read_pm_table().unwrap_err();
+        assert!(matches!(err, SmuError::UnknownPmTableSize(_)), "{:?}", err);
+        assert!(!err.is_transient());
+    }
+
+    fs::remove_file(&size_path).unwrap();
+    let reader = SmuReader::with_path(mock_dir.path()).unwrap();
+    assert!(matches!(reader.read_pm_table(), Err(SmuError::UnknownPmTableSize(_))));
+    // The blob itself is still there to dump
+    assert_eq!(reader.read_pm_table_raw().unwrap().len(), 6832);
+}
+
+#[test]
+fn test_missing_codename_and_version_files() {
+    let mock_dir = create_mock_sysfs();
+    fs::remove_file(mock_dir.path().join("codename")).unwrap();
+    fs::remove_file(mock_dir.path().join("version")).unwrap();
+    let reader = SmuReader::with_path(mock_dir.path()).unwrap();
+
+    assert_eq!(reader.codename().unwrap(), Codename::Unsupported);
+    assert_eq!(reader.smu_version().unwrap(), "Unknown");
+    let (table, warnings) = reader.read_pm_table_with_warnings().unwrap();
+    assert_eq!(table.codename, Codename::Unsupported);
+    assert_eq!(table.version, 0x240903);
+    assert!(table.tctl > 0.0);
+    assert_eq!(warnings[0], Warning::CodenameUnavailable { path: mock_dir.path().join("codename") });
+    assert!(!warnings.iter().any(|w| matches!(w, Warning::VersionCodenameMismatch { .. })));
+}
+
+#[test]
+fn test_truncated_pm_table_parsed_leniently() {
+    let mock_dir = create_mock_sysfs();
+    let pm_table = create_mock_pm_table();
+    // Ends before the per-core block
+    fs::write(mock_dir.path().join("pm_table"), &pm_table[..0x200]).unwrap();
+    fs::write(mock_dir.path().join("pm_table_size"), "512\n").unwrap();
+
+    let reader = SmuReader::with_path(mock_dir.path()).unwrap();
+    let table = reader.read_pm_table().unwrap();
+
+    assert!((table.tctl - 65.2).abs() < 0.01);
+    assert!(table.missing_fields.contains(&"core_temps"));
+    assert_eq!(table.warnings.len(), 1);
+    assert!(table.warnings[0].contains("core_temps"));
+}
+
+#[test]
+fn test_zero_filled_pm_table_warns() {
+    let temp_dir = create_mock_sysfs();
+    fs::write(temp_dir.path().join("pm_table"), vec![0u8; 6832]).unwrap();
+    let reader = SmuReader::with_path(temp_dir.path()).unwrap();
+    let table = reader.read_pm_table().unwrap();
+    assert!(table.is_blank());
+    assert!(table.warnings.iter().any(|w| w.contains("all zeros") && w.contains("udev")), "{:?}", table.warnings);
+}
+
+#[test]
+fn test_read_pm_table_with_warnings() {
+    let temp_dir = create_mock_sysfs();
+    let reader = SmuReader::with_path(temp_dir.path())
+        .unwrap()
+        .with_cpuinfo_path(temp_dir.path().join("missing"));
+    fs::write(temp_dir.path().join("pm_table"), vec![0u8; 6832]).unwrap();
+    let (table, warnings) = reader.read_pm_table_with_warnings().unwrap();
+    assert!(matches!(
+        warnings.as_slice(),
+        [Warning::CpuinfoUnavailable { assumed_cores: 16, codename: Codename::Vermeer, .. }, Warning::AllZeroData]
+    ));
+    assert_eq!(table.warnings, warnings.iter().map(ToString::to_string).collect::<Vec<_>>());
+
+    let mut pm_table = create_mock_pm_table();
+    pm_table[0x014..0x018].copy_from_slice(&400.0f32.to_le_bytes());
+    fs::write(temp_dir.path().join("pm_table"), pm_table).unwrap();
+    let (table, warnings) = reader.read_pm_table_with_warnings().unwrap();
+    assert_eq!(table.tctl, 0.0);
+    assert!(warnings.contains(&Warning::ClampedValue { field: "tctl", value: 400.0, range: -50.0..=150.0 }), "{:?}", warnings);
+}
+
+#[test]
+fn test_read_failure_keeps_path() {
+    let temp_dir = create_mock_sysfs();
+    // Present but unreadable as a file (EISDIR), like a driver returning EIO
+    let path = temp_dir.path().join("pm_table");
+    fs::remove_file(&path).unwrap();
+    fs::create_dir(&path).unwrap();
+    let reader = SmuReader::with_path(temp_dir.path()).unwrap();
+    let err = reader.read_pm_table_raw().unwrap_err();
+    assert!(matches!(&err, SmuError::ReadFailed { path: failed, .. } if *failed == path), "{:?}", err);
+    assert!(err.is_transient());
+}
+
+#[test]
+fn test_pm_table_matching_size_has_no_warnings() {
+    let mock_dir = create_mock_sysfs();
+    let reader = SmuReader::with_path(mock_dir.path()).unwrap();
+    let table = reader.read_pm_table().unwrap();
+    assert!(table.warnings.is_empty());
+}
+
+#[test]
+fn test_read_pm_table_with_raw() {
+    let mock_dir = create_mock_sysfs();
+    let reader = SmuReader::with_path(mock_dir.path()).unwrap();
+    let (table, raw) = reader.read_pm_table_with_raw().unwrap();
+
+    assert!((table.tctl - 65.2).abs() < 0.01);
+    assert_eq!(raw, create_mock_pm_table());
+    assert_eq!(crc32(&raw), crc32(&create_mock_pm_table()));
+}
+
+#[test]
+fn test_read_pm_table_raw_matches_size() {
+    let mock_dir = create_mock_sysfs();
+    // Raw reads don't care whether the version is supported
+    fs::write(mock_dir.path().join("pm_table_version"), 0x999999u32.to_le_bytes()).unwrap();
+    let reader = SmuReader::with_path(mock_dir.path()).unwrap();
+
+    let raw = reader.read_pm_table_raw().unwrap();
+    assert_eq!(raw.len(), reader.pm_table_size().unwrap());
+}
+
+#[test]
+fn test_parse_from_file() {
+    let dir = TempDir::new().unwrap();
+    let dump = dir.path().join("pm_table.bin");
+    fs::write(&dump, create_mock_pm_table()).unwrap();
+
+    let table = PmTable::parse_from_file(&dump, 0x240903, Codename::Vermeer, 8).unwrap();
+    assert!((table.tctl - 65.2).abs() < 0.01);
+    assert_eq!(table.core_temps.len(), 8);
+
+    let missing = PmTable::parse_from_file(dir.path().join("missing.bin"), 0x240903, Codename::Vermeer, 8);
+    assert!(missing.is_err());
+}
+
+#[test]
+fn test_file_source_sizes_from_dump() {
+    let dir = TempDir::new().unwrap();
+    let dump = dir.path().join("pm_table.bin");
+    fs::write(&dump, create_mock_pm_table()).unwrap();
+
+    // Stops at the last core with readings rather than a global maximum
+    let table = FileSource::new(&dump, 0x240903).read().unwrap();
+    assert_eq!(table.core_temps.len(), 8);
+    assert_eq!(table.core_freqs.len(), 8);
+    let table = FileSource::new(&dump, 0x240903).with_core_count(4).read().unwrap();
+    assert_eq!(table.core_temps.len(), 4);
+
+    // The only family reporting the version; clocks aren't taken from
+    // this machine's cpuinfo
+    let table = FileSource::new(&dump, 0x620205).read().unwrap();
+    assert_eq!(table.codename, Codename::GraniteRidge);
+    assert_eq!(table.freq_source, FreqSource::Unavailable);
+    assert!(table.core_freqs.is_empty());
+}
+
+#[test]
+fn test_pm_table_sources() {
+    let mock_dir = create_mock_sysfs();
+    let dump = mock_dir.path().join("dump.bin");
+    fs::write(&dump, create_mock_pm_table()).unwrap();
+
+    let mut sources: Vec<Box<dyn PmTableSource>> = vec![
+        Box::new(SmuReader::with_path(mock_dir.path()).unwrap()),
+        Box::new(FileSource::new(&dump, 0x240903).with_codename(Codename::Vermeer)),
+    ];
+    for source in &mut sources {
+        assert!((source.read().unwrap().tctl - 65.2).abs() < 0.01);
+    }
+    assert_eq!(sources[0].smu_version(), "SMU v46.54.0");
+    assert_eq!(sources[1].smu_version(), format!("File {}", dump.display()));
+}
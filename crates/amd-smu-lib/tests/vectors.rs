@@ -0,0 +1,115 @@
+//! Data-driven PM table tests
+//!
+//! Each vector in `tests/vectors/` is a `<name>.bin` PM table blob paired with
+//! a `<name>.json` describing how to parse it and which values to expect:
+//!
+//! ```json
+//! {
+//!   "description": "Synthetic Vermeer (Zen 3) table, 8 cores",
+//!   "version": "0x240903",
+//!   "codename": 12,
+//!   "core_count": 8,
+//!   "expected": { "tctl": 65.25, "core_temps": [60.0, 60.5] }
+//! }
+//! ```
+//!
+//! `codename` is the numeric sysfs id. Keys under `expected` are `PmTable`
+//! JSON field names; scalars and arrays are compared with a small tolerance,
+//! and fields not listed are not checked. Dropping a new pair into the
+//! directory is enough to cover a new offset table.
+
+use amd_smu_lib::{Codename, PmTable};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const TOLERANCE: f64 = 0.01;
+
+#[derive(Debug, Deserialize)]
+struct VectorSpec {
+    #[serde(default)]
+    description: String,
+    version: String,
+    codename: u32,
+    core_count: usize,
+    expected: Map<String, Value>,
+}
+
+fn vectors_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("vectors")
+}
+
+fn parse_version(s: &str) -> u32 {
+    let s = s.trim();
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16).expect("invalid hex version"),
+        None => s.parse().expect("invalid version"),
+    }
+}
+
+fn check_value(name: &str, field: &str, actual: &Value, expected: &Value) -> Vec<String> {
+    match (actual, expected) {
+        (Value::Number(a), Value::Number(e)) => {
+            let (a, e) = (a.as_f64().unwrap(), e.as_f64().unwrap());
+            if (a - e).abs() > TOLERANCE {
+                vec![format!("{}: {} = {}, expected {}", name, field, a, e)]
+            } else {
+                Vec::new()
+            }
+        }
+        (Value::Array(a), Value::Array(e)) => {
+            if a.len() != e.len() {
+                return vec![format!(
+                    "{}: {} has {} entries, expected {}",
+                    name, field, a.len(), e.len()
+                )];
+            }
+            a.iter()
+                .zip(e)
+                .enumerate()
+                .flat_map(|(i, (a, e))| check_value(name, &format!("{}[{}]", field, i), a, e))
+                .collect()
+        }
+        _ => vec![format!("{}: {} = {}, expected {}", name, field, actual, expected)],
+    }
+}
+
+fn run_vector(spec_path: &Path) -> Vec<String> {
+    let name = spec_path.file_stem().unwrap().to_string_lossy().into_owned();
+    let spec: VectorSpec = serde_json::from_str(&fs::read_to_string(spec_path).unwrap())
+        .unwrap_or_else(|e| panic!("{}: invalid vector spec: {}", name, e));
+    let data = fs::read(spec_path.with_extension("bin"))
+        .unwrap_or_else(|e| panic!("{}: missing PM table blob: {}", name, e));
+
+    let table = PmTable::parse(
+        &data,
+        parse_version(&spec.version),
+        Codename::from_id(spec.codename),
+        spec.core_count,
+    )
+    .unwrap_or_else(|e| panic!("{} ({}): parse failed: {}", name, spec.description, e));
+    let actual = serde_json::to_value(&table).unwrap();
+
+    spec.expected
+        .iter()
+        .flat_map(|(field, expected)| match actual.get(field) {
+            Some(value) => check_value(&name, field, value, expected),
+            None => vec![format!("{}: unknown field {}", name, field)],
+        })
+        .collect()
+}
+
+#[test]
+fn test_pm_table_vectors() {
+    let mut specs: Vec<_> = fs::read_dir(vectors_dir())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    specs.sort();
+    assert!(!specs.is_empty(), "no test vectors found");
+
+    let failures: Vec<String> = specs.iter().flat_map(|spec| run_vector(spec)).collect();
+    assert!(failures.is_empty(), "vector mismatches:\n{}", failures.join("\n"));
+}
@@ -1,72 +1,414 @@
-use amd_smu_lib::{PmTable, SmuReader};
-use std::time::Duration;
+use crate::history::History;
+use crate::replay::Replay;
+use crate::sampler::{Fans, Sample, Sampler};
+use amd_smu_lib::{EnergyMeter, OutputSections, PmTable, PmTableSource, Smoother, TempUnit, ThresholdConfig, VersionTracker};
+use std::cell::Cell;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Smoothing factor for the `s` key when none was given on the command line
+const DEFAULT_SMOOTH_ALPHA: f32 = 0.3;
+/// C0 residency (%) below which a core counts as idle
+const IDLE_C0_PERCENT: f32 = 5.0;
+
+/// Where readings come from
+pub enum Source {
+    /// The driver, sampled in the background
+    Live(Sampler),
+    /// A recording stepped through at the refresh interval
+    Replay(Replay),
+}
+
+/// Highest raw readings seen since start or the last clear
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Peaks {
+    pub tctl: f32,
+    pub ppt: f32,
+    pub tdc: f32,
+    pub edc: f32,
+}
+
+impl Peaks {
+    fn update(&mut self, table: &PmTable) {
+        self.tctl = self.tctl.max(table.tctl);
+        self.ppt = self.ppt.max(table.ppt_value);
+        self.tdc = self.tdc.max(table.tdc_value);
+        self.edc = self.edc.max(table.edc_value);
+    }
+}
+
+/// Whether `core` spent under 5% of its time in C0
+///
+/// False when the layout has no C0 residency, so those cores show normally.
+pub fn is_idle_core(table: &PmTable, core: usize) -> bool {
+    table.core_c0.get(core).is_some_and(|&c0| c0 < IDLE_C0_PERCENT)
+}
+
+/// `duration` as HH:MM:SS; hours keep counting past 99
+pub fn format_uptime(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
 
 pub struct App {
-    pub reader: SmuReader,
+    pub source: Source,
     pub smu_version: String,
     pub pm_table: Option<PmTable>,
+    /// Peak hold for the limit and Tctl gauges
+    pub peaks: Peaks,
+    /// PPT integrated since start or the last reset
+    pub energy: EnergyMeter,
+    /// hwmon fan speeds, live readings only
+    pub fans: Fans,
+    pub history: History,
     pub error: Option<String>,
+    /// Set once the PM table version changes mid-run, e.g. "0x240903 → 0x380904"
+    pub version_change: Option<String>,
+    versions: VersionTracker,
+    /// When the dashboard started, for the uptime in the header
+    pub started: Instant,
+    /// Readings picked up since start
+    pub samples: u64,
     pub interval: Duration,
     pub running: bool,
-    pub show_temps: bool,
-    pub show_power: bool,
-    pub show_freq: bool,
+    /// Keep showing the current reading instead of picking up new ones
+    pub paused: bool,
+    /// One sample was requested while paused
+    refresh_requested: bool,
+    /// Panels shown: power limits, temperatures (and fans), per-core table
+    pub sections: OutputSections,
+    pub show_graphs: bool,
+    pub temp_unit: TempUnit,
+    /// Render with colors; monochrome otherwise
+    pub color: bool,
+    /// Warning/critical levels for gauge and temperature colors
+    pub thresholds: ThresholdConfig,
+    /// Shade limit gauges along a truecolor gradient rather than by
+    /// threshold
+    pub gradient: bool,
+    /// Label cores as CCD<n>/Core<m> instead of by flat index
+    pub ccd_relative: bool,
+    /// First core shown in the per-core table
+    pub core_scroll: usize,
+    /// Furthest `core_scroll` goes, as of the last draw (see
+    /// `core_scroll_offset`)
+    core_scroll_max: Cell<usize>,
+    /// Leave idle cores out of the per-core table instead of dimming them
+    pub hide_idle_cores: bool,
+    /// Show per-core temperatures as a colored grid by CCD instead of the
+    /// per-core table
+    pub heatmap: bool,
+    /// Show the metric and key reference over the dashboard
+    pub show_help: bool,
+    /// Smooths displayed readings while on
+    pub smoother: Option<Smoother>,
+    /// Smoothing factor used when smoothing is toggled on
+    smooth_alpha: f32,
 }
 
 impl App {
-    pub fn new(interval: Duration) -> Result<Self, String> {
-        let reader = SmuReader::new().map_err(|e| e.to_string())?;
-        let smu_version = reader.smu_version().unwrap_or_else(|_| "Unknown".to_string());
+    /// Dashboard sampling `source` every `interval` in the background
+    pub fn new<S: PmTableSource + Send + 'static>(
+        source: S,
+        interval: Duration,
+        temp_unit: TempUnit,
+        color: bool,
+        smooth: Option<f32>,
+    ) -> Self {
+        let smu_version = source.smu_version();
+        Self::with_source(Source::Live(Sampler::spawn(source, interval)), smu_version, interval, temp_unit, color, smooth)
+    }
 
-        Ok(Self {
-            reader,
+    /// Dashboard playing back the JSON lines recorded in `path`
+    pub fn replay(
+        path: &Path,
+        interval: Duration,
+        temp_unit: TempUnit,
+        color: bool,
+        smooth: Option<f32>,
+    ) -> Result<Self, String> {
+        let replay = Replay::load(path, interval)?;
+        let name = format!("Replay {}", path.display());
+        Ok(Self::with_source(Source::Replay(replay), name, interval, temp_unit, color, smooth))
+    }
+
+    fn with_source(
+        source: Source,
+        smu_version: String,
+        interval: Duration,
+        temp_unit: TempUnit,
+        color: bool,
+        smooth: Option<f32>,
+    ) -> Self {
+        Self {
+            source,
             smu_version,
             pm_table: None,
+            peaks: Peaks::default(),
+            energy: EnergyMeter::default(),
+            fans: Fans::new(),
+            history: History::default(),
             error: None,
+            version_change: None,
+            versions: VersionTracker::default(),
+            started: Instant::now(),
+            samples: 0,
             interval,
             running: true,
-            show_temps: true,
-            show_power: true,
-            show_freq: true,
-        })
+            paused: false,
+            refresh_requested: false,
+            sections: OutputSections::all(),
+            show_graphs: false,
+            temp_unit,
+            color,
+            thresholds: ThresholdConfig::default(),
+            gradient: true,
+            ccd_relative: false,
+            core_scroll: 0,
+            core_scroll_max: Cell::new(0),
+            hide_idle_cores: false,
+            heatmap: false,
+            show_help: false,
+            smoother: smooth.and_then(Smoother::for_factor),
+            smooth_alpha: smooth.filter(|alpha| *alpha > 0.0).unwrap_or(DEFAULT_SMOOTH_ALPHA),
+        }
+    }
+
+    /// Color readings by `thresholds` instead of the defaults
+    pub fn with_thresholds(mut self, thresholds: ThresholdConfig) -> Self {
+        self.thresholds = thresholds;
+        self
+    }
+
+    /// Shade the limit gauges by gradient (the default) or by threshold
+    pub fn with_gradient(mut self, gradient: bool) -> Self {
+        self.gradient = gradient;
+        self
+    }
+
+    /// Number cores within their CCD in the per-core table
+    pub fn with_ccd_relative(mut self, ccd_relative: bool) -> Self {
+        self.ccd_relative = ccd_relative;
+        self
     }
 
+    /// Pick up the latest background sample, if one has arrived
+    ///
+    /// Does nothing while paused, unless a refresh was requested.
     pub fn tick(&mut self) {
-        match self.reader.read_pm_table() {
-            Ok(table) => {
-                self.pm_table = Some(table);
+        if self.paused && !self.refresh_requested {
+            return;
+        }
+        let latest: Option<Sample> = match &mut self.source {
+            Source::Live(sampler) => {
+                if let Some(fans) = sampler.latest_fans() {
+                    self.fans = fans;
+                }
+                sampler.latest()
+            }
+            Source::Replay(replay) => replay.latest(),
+        };
+        match latest {
+            Some(Ok(table)) => {
+                // Graphs and peaks keep the raw readings, so smoothing
+                // can't hide a spike
+                self.history.push(&table);
+                self.peaks.update(&table);
+                self.energy.update(&table);
+                self.samples += 1;
+                if let Some(previous) = self.versions.update(&table) {
+                    self.version_change = Some(format!("{:#x} → {:#x}", previous, table.version));
+                }
+                self.pm_table = Some(match self.smoother.as_mut() {
+                    Some(smoother) => smoother.apply(&table),
+                    None => table,
+                });
                 self.error = None;
+                self.refresh_requested = false;
             }
-            Err(e) => {
-                self.error = Some(e.to_string());
+            Some(Err(e)) => {
+                self.error = Some(e);
+                self.refresh_requested = false;
             }
+            None => {}
+        }
+    }
+
+    /// Switch smoothing on or off; switching on starts a fresh average
+    pub fn toggle_smoothing(&mut self) {
+        self.smoother = match self.smoother {
+            Some(_) => None,
+            None => Some(Smoother::new(self.smooth_alpha)),
+        };
+    }
+
+    /// Restart peak hold from the reading on screen
+    pub fn clear_peaks(&mut self) {
+        self.peaks = Peaks::default();
+        if let Some(table) = &self.pm_table {
+            self.peaks.update(table);
+        }
+    }
+
+    /// Start measuring energy and average power afresh from the next reading
+    pub fn reset_energy(&mut self) {
+        self.energy.reset();
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    /// Fetch one new reading now, even while paused
+    ///
+    /// In a replay this steps to the next sample.
+    pub fn refresh(&mut self) {
+        self.refresh_requested = true;
+        match &mut self.source {
+            Source::Live(sampler) => sampler.refresh(),
+            Source::Replay(replay) => replay.seek(1),
+        }
+    }
+
+    /// Move through a replay by `delta` samples; no-op for live readings
+    pub fn seek(&mut self, delta: isize) {
+        if let Source::Replay(replay) = &mut self.source {
+            replay.seek(delta);
+            // Graphs would otherwise splice unrelated stretches together,
+            // and energy would count the skipped time
+            self.history = History::default();
+            self.energy.reset();
+            self.refresh_requested = true;
+        }
+    }
+
+    /// Zero-based replay position and sample count, when replaying
+    pub fn replay_progress(&self) -> Option<(usize, usize)> {
+        match &self.source {
+            Source::Live(_) => None,
+            Source::Replay(replay) => Some(replay.progress()),
         }
     }
 
     pub fn quit(&mut self) {
         self.running = false;
+        if let Source::Live(sampler) = &self.source {
+            sampler.stop();
+        }
+    }
+
+    /// Show or hide a panel
+    pub fn toggle_section(&mut self, section: OutputSections) {
+        self.sections.toggle(section);
+    }
+
+    pub fn toggle_graphs(&mut self) {
+        self.show_graphs = !self.show_graphs;
+    }
+
+    pub fn toggle_help(&mut self) {
+        self.show_help = !self.show_help;
+    }
+
+    /// Hide or show idle cores; the list changes length, so scrolling restarts
+    pub fn toggle_idle_cores(&mut self) {
+        self.hide_idle_cores = !self.hide_idle_cores;
+        self.core_scroll = 0;
     }
 
-    pub fn toggle_temps(&mut self) {
-        self.show_temps = !self.show_temps;
+    /// Switch the per-core panel between the table and the heatmap
+    pub fn toggle_heatmap(&mut self) {
+        self.heatmap = !self.heatmap;
+        self.core_scroll = 0;
     }
 
-    pub fn toggle_power(&mut self) {
-        self.show_power = !self.show_power;
+    /// Scroll the per-core table up by `rows`
+    pub fn scroll_cores_up(&mut self, rows: usize) {
+        self.core_scroll = self.core_scroll.min(self.core_scroll_max.get()).saturating_sub(rows);
     }
 
-    pub fn toggle_freq(&mut self) {
-        self.show_freq = !self.show_freq;
+    /// Scroll the per-core table down by `rows`, stopping once the last
+    /// row is on screen
+    pub fn scroll_cores_down(&mut self, rows: usize) {
+        self.core_scroll = (self.core_scroll + rows).min(self.core_scroll_max.get());
+    }
+
+    /// First of `rows` rows to draw in a panel with room for `visible`
+    ///
+    /// The one place the scroll limit is worked out; drawing records it so
+    /// scrolling stops where the panel does.
+    pub fn core_scroll_offset(&self, rows: usize, visible: usize) -> usize {
+        let max = rows.saturating_sub(visible);
+        self.core_scroll_max.set(max);
+        self.core_scroll.min(max)
     }
 
     pub fn increase_interval(&mut self) {
         self.interval = self.interval.saturating_add(Duration::from_millis(100));
+        self.set_source_interval();
+    }
+
+    fn set_source_interval(&mut self) {
+        match &mut self.source {
+            Source::Live(sampler) => sampler.set_interval(self.interval),
+            Source::Replay(replay) => replay.set_interval(self.interval),
+        }
     }
 
     pub fn decrease_interval(&mut self) {
         let new_interval = self.interval.saturating_sub(Duration::from_millis(100));
         if new_interval >= Duration::from_millis(100) {
             self.interval = new_interval;
+            self.set_source_interval();
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use amd_smu_lib::MockSource;
+
+    #[test]
+    fn test_peaks_hold_maximum() {
+        let mut peaks = Peaks::default();
+        peaks.update(&PmTable { tctl: 80.0, ppt_value: 60.0, ..Default::default() });
+        peaks.update(&PmTable { tctl: 65.0, ppt_value: 120.0, tdc_value: 40.0, ..Default::default() });
+        assert_eq!(peaks, Peaks { tctl: 80.0, ppt: 120.0, tdc: 40.0, edc: 0.0 });
+    }
+
+    #[test]
+    fn test_core_scroll_stops_at_drawn_limit() {
+        let mut app = App::new(MockSource::new(Vec::new()), Duration::from_secs(1), TempUnit::Celsius, false, None);
+        // 20 rows in a 5-row panel
+        assert_eq!(app.core_scroll_offset(20, 5), 0);
+        app.scroll_cores_down(100);
+        assert_eq!(app.core_scroll_offset(20, 5), 15);
+        app.scroll_cores_up(1);
+        assert_eq!(app.core_scroll_offset(20, 5), 14);
+
+        // After the panel grows, one press up moves the view
+        app.scroll_cores_down(100);
+        assert_eq!(app.core_scroll_offset(20, 10), 10);
+        app.scroll_cores_up(1);
+        assert_eq!(app.core_scroll_offset(20, 10), 9);
+    }
+
+    #[test]
+    fn test_format_uptime() {
+        assert_eq!(format_uptime(Duration::from_millis(999)), "00:00:00");
+        assert_eq!(format_uptime(Duration::from_secs(5025)), "01:23:45");
+        assert_eq!(format_uptime(Duration::from_secs(100 * 3600 + 61)), "100:01:01");
+    }
+
+    #[test]
+    fn test_idle_cores() {
+        let table = PmTable { core_temps: vec![40.0; 3], core_c0: vec![2.5, 60.0, f32::NAN], ..Default::default() };
+        assert!(is_idle_core(&table, 0));
+        assert!(!is_idle_core(&table, 1));
+        assert!(!is_idle_core(&table, 2));
+        // Without C0 data nothing is idle
+        let table = PmTable { core_temps: vec![40.0; 3], ..Default::default() };
+        assert!(!is_idle_core(&table, 0));
+    }
+}
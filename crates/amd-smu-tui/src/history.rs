@@ -0,0 +1,49 @@
+use amd_smu_lib::PmTable;
+use std::collections::VecDeque;
+
+/// Number of samples kept for each graph
+pub const HISTORY_LEN: usize = 120;
+
+/// Bounded per-metric history for the sparkline panel
+///
+/// Values are rounded to whole units, which is all a sparkline can show.
+#[derive(Debug, Default)]
+pub struct History {
+    pub tctl: VecDeque<u64>,
+    pub package_power: VecDeque<u64>,
+    pub max_core_freq: VecDeque<u64>,
+}
+
+impl History {
+    pub fn push(&mut self, table: &PmTable) {
+        push_bounded(&mut self.tctl, table.tctl);
+        push_bounded(&mut self.package_power, table.package_power);
+        push_bounded(&mut self.max_core_freq, table.max_core_freq());
+    }
+}
+
+fn push_bounded(buf: &mut VecDeque<u64>, value: f32) {
+    if buf.len() == HISTORY_LEN {
+        buf.pop_front();
+    }
+    buf.push_back(value.max(0.0).round() as u64);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_is_bounded() {
+        let mut history = History::default();
+        for i in 0..HISTORY_LEN + 10 {
+            let table = PmTable { tctl: i as f32, ..Default::default() };
+            history.push(&table);
+        }
+
+        assert_eq!(history.tctl.len(), HISTORY_LEN);
+        // Oldest samples were dropped first
+        assert_eq!(history.tctl.front(), Some(&10));
+        assert_eq!(history.tctl.back(), Some(&(HISTORY_LEN as u64 + 9)));
+    }
+}
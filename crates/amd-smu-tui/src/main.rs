@@ -1,16 +1,91 @@
 mod app;
+mod history;
+mod replay;
+mod sampler;
 mod ui;
 
+use amd_smu_lib::{parse_interval, parse_smoothing, OutputSections, ReconnectingReader, SmuReader, TempUnit, ThresholdConfig};
 use app::App;
+use clap::Parser;
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
-use std::{io, time::Duration};
+use std::{io, path::PathBuf, time::Duration};
+
+#[derive(Parser, Debug)]
+#[command(name = "amd-smu-tui")]
+#[command(about = "Live dashboard for AMD Ryzen CPU sensors via ryzen_smu")]
+#[command(version = amd_smu_lib::full_version())]
+struct Args {
+    /// Refresh interval at startup (e.g. "250ms", "2s"; at least 100ms);
+    /// +/- still adjust it while running
+    #[arg(short, long, default_value = "500ms", value_parser = parse_interval)]
+    interval: Duration,
+
+    /// Show temperatures in Fahrenheit
+    #[arg(long)]
+    fahrenheit: bool,
+
+    /// Monochrome display (also enabled by $NO_COLOR)
+    #[arg(long)]
+    no_color: bool,
+
+    /// Color the PPT/TDC/EDC gauges in green/yellow/red steps instead of a
+    /// smooth gradient, for terminals without truecolor
+    #[arg(long)]
+    no_gradient: bool,
+
+    /// Start with readings smoothed by an exponential moving average;
+    /// ALPHA is the newest sample's weight (1 = raw, lower = smoother,
+    /// 0 = off). `s` toggles smoothing either way
+    #[arg(long, value_name = "ALPHA", value_parser = parse_smoothing)]
+    smooth: Option<f32>,
+
+    /// Play back samples recorded with `amd-smu-sensors watch --json-lines`
+    /// (or a JSONL --log-file) instead of reading the driver
+    #[arg(long, value_name = "FILE", conflicts_with = "sysfs_path")]
+    replay: Option<PathBuf>,
+
+    /// ryzen_smu sysfs directory (overrides $AMD_SMU_SYSFS; default
+    /// /sys/kernel/ryzen_smu_drv)
+    #[arg(long, value_name = "DIR")]
+    sysfs_path: Option<PathBuf>,
+
+    /// Number cores within their CCD (CCD1/Core0 rather than C8)
+    #[arg(long)]
+    ccd_relative: bool,
+
+    /// Read warning/critical color thresholds from this TOML file
+    /// (default ~/.config/amd-smu/config.toml, if it exists)
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+}
+
+/// Cores moved by PgUp/PgDn in the per-core table
+const CORE_PAGE: usize = 8;
+
+/// Upper bound on time between redraws
+const FRAME_INTERVAL: Duration = Duration::from_millis(100);
 
 fn main() -> io::Result<()> {
+    let args = Args::parse();
+    let color = !args.no_color && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty());
+    let temp_unit = if args.fahrenheit {
+        TempUnit::Fahrenheit
+    } else {
+        TempUnit::Celsius
+    };
+    let thresholds = match ThresholdConfig::load_or_default(args.config.as_deref()) {
+        Ok(thresholds) => thresholds,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -19,8 +94,18 @@ fn main() -> io::Result<()> {
     let mut terminal = Terminal::new(backend)?;
 
     // Create app
-    let mut app = match App::new(Duration::from_millis(500)) {
-        Ok(a) => a,
+    let interval = args.interval;
+    let app = match &args.replay {
+        Some(path) => App::replay(path, interval, temp_unit, color, args.smooth),
+        None => SmuReader::with_path_or_env(args.sysfs_path)
+            .map(|reader| App::new(ReconnectingReader::new(reader), interval, temp_unit, color, args.smooth))
+            .map_err(|e| e.to_string()),
+    };
+    let mut app = match app {
+        Ok(a) => a
+            .with_thresholds(thresholds)
+            .with_ccd_relative(args.ccd_relative)
+            .with_gradient(!args.no_gradient),
         Err(e) => {
             // Restore terminal before printing error
             disable_raw_mode()?;
@@ -34,7 +119,7 @@ fn main() -> io::Result<()> {
         }
     };
 
-    // Initial data fetch
+    // Pick up the first sample if the sampler already has one
     app.tick();
 
     // Run event loop
@@ -63,26 +148,66 @@ fn run_app(
     while app.running {
         terminal.draw(|f| ui::draw(f, app))?;
 
-        // Poll for events with timeout matching refresh interval
-        if event::poll(app.interval)? {
-            if let Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press {
-                    match key.code {
-                        KeyCode::Char('q') | KeyCode::Esc => app.quit(),
-                        KeyCode::Char('t') => app.toggle_temps(),
-                        KeyCode::Char('p') => app.toggle_power(),
-                        KeyCode::Char('f') => app.toggle_freq(),
-                        KeyCode::Char('+') | KeyCode::Char('=') => app.decrease_interval(),
-                        KeyCode::Char('-') => app.increase_interval(),
-                        _ => {}
-                    }
-                }
+        // Poll for events; sampling runs in the background, so redraw at
+        // least this often to pick up new samples promptly
+        if event::poll(app.interval.min(FRAME_INTERVAL))?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            match key.code {
+                // The help overlay takes the keys until it's closed
+                KeyCode::Char('?') | KeyCode::Esc if app.show_help => app.toggle_help(),
+                KeyCode::Char('q') => app.quit(),
+                _ if app.show_help => {}
+                KeyCode::Char('?') => app.toggle_help(),
+                KeyCode::Esc => app.quit(),
+                KeyCode::Char('t') => app.toggle_section(OutputSections::TEMPS),
+                KeyCode::Char('p') => app.toggle_section(OutputSections::POWER),
+                KeyCode::Char('f') => app.toggle_section(OutputSections::FREQ),
+                KeyCode::Char('g') => app.toggle_graphs(),
+                KeyCode::Char(' ') => app.toggle_pause(),
+                KeyCode::Char('s') => app.toggle_smoothing(),
+                KeyCode::Char('c') => app.clear_peaks(),
+                KeyCode::Char('e') => app.reset_energy(),
+                KeyCode::Char('i') => app.toggle_idle_cores(),
+                KeyCode::Char('h') => app.toggle_heatmap(),
+                KeyCode::Char('r') => app.refresh(),
+                KeyCode::Up | KeyCode::Char('k') => app.scroll_cores_up(1),
+                KeyCode::Down | KeyCode::Char('j') => app.scroll_cores_down(1),
+                KeyCode::PageUp => app.scroll_cores_up(CORE_PAGE),
+                KeyCode::PageDown => app.scroll_cores_down(CORE_PAGE),
+                KeyCode::Left => app.seek(-1),
+                KeyCode::Right => app.seek(1),
+                KeyCode::Home => app.seek(isize::MIN),
+                KeyCode::End => app.seek(isize::MAX),
+                KeyCode::Char('+') | KeyCode::Char('=') => app.decrease_interval(),
+                KeyCode::Char('-') => app.increase_interval(),
+                _ => {}
             }
         }
 
-        // Refresh data
+        // Pick up any new sample
         app.tick();
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_args_definition() {
+        Args::command().debug_assert();
+    }
+
+    #[test]
+    fn test_interval_argument() {
+        assert_eq!(Args::parse_from(["amd-smu-tui"]).interval, Duration::from_millis(500));
+        assert_eq!(Args::parse_from(["amd-smu-tui", "-i", "2s"]).interval, Duration::from_secs(2));
+        assert!(Args::try_parse_from(["amd-smu-tui", "--interval", "50ms"]).is_err());
+        assert!(Args::try_parse_from(["amd-smu-tui", "--interval", "soon"]).is_err());
+    }
+}
@@ -0,0 +1,125 @@
+use crate::sampler::Sample;
+use amd_smu_lib::PmTable;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Steps through PM tables recorded as JSON lines (`--json-lines` or
+/// `--log-file` output), one per interval, stopping at the last one
+pub struct Replay {
+    samples: Vec<PmTable>,
+    position: usize,
+    interval: Duration,
+    /// When the current sample was handed out; None until the first one is
+    shown_at: Option<Instant>,
+    /// A seek happened; hand out the new position right away
+    seeked: bool,
+}
+
+impl Replay {
+    pub fn load(path: &Path, interval: Duration) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let samples = parse_jsonl(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Ok(Self::new(samples, interval))
+    }
+
+    fn new(samples: Vec<PmTable>, interval: Duration) -> Self {
+        Self { samples, position: 0, interval, shown_at: None, seeked: false }
+    }
+
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.interval = interval;
+    }
+
+    /// Sample due now, if it differs from the one last returned
+    pub fn latest(&mut self) -> Option<Sample> {
+        if let Some(shown_at) = self.shown_at
+            && !self.seeked
+        {
+            if shown_at.elapsed() < self.interval || self.position + 1 >= self.samples.len() {
+                return None;
+            }
+            self.position += 1;
+        }
+        self.seeked = false;
+        self.shown_at = Some(Instant::now());
+        self.samples.get(self.position).cloned().map(Ok)
+    }
+
+    /// Move by `delta` samples, clamped to the recording
+    pub fn seek(&mut self, delta: isize) {
+        let last = self.samples.len().saturating_sub(1);
+        self.position = self.position.saturating_add_signed(delta).min(last);
+        self.seeked = true;
+    }
+
+    /// Zero-based position and total number of samples
+    pub fn progress(&self) -> (usize, usize) {
+        (self.position, self.samples.len())
+    }
+}
+
+/// One `PmTable` per non-empty line; extra keys such as `timestamp` are
+/// ignored
+fn parse_jsonl(text: &str) -> Result<Vec<PmTable>, String> {
+    let samples = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| serde_json::from_str(line).map_err(|e| format!("line {}: {}", i + 1, e)))
+        .collect::<Result<Vec<PmTable>, String>>()?;
+    if samples.is_empty() {
+        return Err("no samples recorded".to_string());
+    }
+    Ok(samples)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recording(tctls: &[f32]) -> Vec<PmTable> {
+        tctls.iter().map(|&tctl| PmTable { tctl, ..Default::default() }).collect()
+    }
+
+    fn tctl(sample: Option<Sample>) -> Option<f32> {
+        sample.map(|s| s.unwrap().tctl)
+    }
+
+    #[test]
+    fn test_steps_and_stops_at_last_sample() {
+        let mut replay = Replay::new(recording(&[60.0, 70.0]), Duration::ZERO);
+        assert_eq!(tctl(replay.latest()), Some(60.0));
+        assert_eq!(tctl(replay.latest()), Some(70.0));
+        assert_eq!(tctl(replay.latest()), None);
+        assert_eq!(replay.progress(), (1, 2));
+    }
+
+    #[test]
+    fn test_waits_for_interval() {
+        let mut replay = Replay::new(recording(&[60.0, 70.0]), Duration::from_secs(60));
+        assert_eq!(tctl(replay.latest()), Some(60.0));
+        assert_eq!(tctl(replay.latest()), None);
+    }
+
+    #[test]
+    fn test_seek_is_clamped_and_immediate() {
+        let mut replay = Replay::new(recording(&[60.0, 70.0, 80.0]), Duration::from_secs(60));
+        replay.latest();
+        replay.seek(5);
+        assert_eq!(tctl(replay.latest()), Some(80.0));
+        replay.seek(-10);
+        assert_eq!(tctl(replay.latest()), Some(60.0));
+    }
+
+    #[test]
+    fn test_parse_jsonl() {
+        let table = PmTable { tctl: 65.0, ..Default::default() };
+        let mut line = serde_json::to_string(&table).unwrap();
+        line.insert_str(1, "\"timestamp\":1700000000000,");
+        let samples = parse_jsonl(&format!("{}\n\n{}\n", line, line)).unwrap();
+        assert_eq!(samples.len(), 2);
+        assert!(parse_jsonl("").is_err());
+        assert!(parse_jsonl("{}").unwrap_err().starts_with("line 1"));
+    }
+}
@@ -0,0 +1,135 @@
+use amd_smu_lib::{fan_speeds, with_retry, PmTable, PmTableSource, READ_RETRIES, RETRY_DELAY};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::Duration;
+
+/// Result of one background read, with errors already stringified for display
+pub type Sample = Result<PmTable, String>;
+
+/// Labeled fan speeds (RPM) read alongside each sample
+pub type Fans = Vec<(String, u32)>;
+
+enum Control {
+    SetInterval(Duration),
+    /// Take a sample now instead of waiting out the interval
+    Refresh,
+    Stop,
+}
+
+/// Reads PM tables from a source on a background thread so slow sysfs reads never
+/// block rendering or input handling
+pub struct Sampler {
+    control: Sender<Control>,
+    samples: Receiver<Sample>,
+    fans: Receiver<Fans>,
+}
+
+impl Sampler {
+    pub fn spawn<S: PmTableSource + Send + 'static>(source: S, interval: Duration) -> Self {
+        let (control_tx, control_rx) = mpsc::channel();
+        let (sample_tx, sample_rx) = mpsc::channel();
+        let (fans_tx, fans_rx) = mpsc::channel();
+
+        // Detached: a read stalled in the driver must not hold up quitting
+        thread::spawn(move || run(source, interval, control_rx, sample_tx, fans_tx));
+
+        Self {
+            control: control_tx,
+            samples: sample_rx,
+            fans: fans_rx,
+        }
+    }
+
+    pub fn set_interval(&self, interval: Duration) {
+        let _ = self.control.send(Control::SetInterval(interval));
+    }
+
+    /// Ask for a sample right away; anything not yet consumed is dropped so
+    /// the next `latest` returns the fresh reading
+    pub fn refresh(&self) {
+        self.samples.try_iter().for_each(drop);
+        let _ = self.control.send(Control::Refresh);
+    }
+
+    /// Most recent sample produced since the last call, without blocking
+    pub fn latest(&self) -> Option<Sample> {
+        self.samples.try_iter().last()
+    }
+
+    /// Most recent fan speeds read since the last call, without blocking
+    pub fn latest_fans(&self) -> Option<Fans> {
+        self.fans.try_iter().last()
+    }
+
+    /// Tell the sampling thread to stop, without waiting for it
+    ///
+    /// A read in progress finishes (or stays stuck) in the background; the
+    /// thread exits as soon as it sees the message or finds the UI gone.
+    pub fn stop(&self) {
+        let _ = self.control.send(Control::Stop);
+    }
+}
+
+impl Drop for Sampler {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn run(
+    mut source: impl PmTableSource,
+    mut interval: Duration,
+    control: Receiver<Control>,
+    samples: Sender<Sample>,
+    fans: Sender<Fans>,
+) {
+    loop {
+        let sample = with_retry(READ_RETRIES, RETRY_DELAY, || source.read()).map_err(|e| e.to_string());
+        // hwmon is optional; without fans the panel just stays hidden
+        let _ = fans.send(fan_speeds().unwrap_or_default());
+        if samples.send(sample).is_err() {
+            // UI side is gone
+            return;
+        }
+
+        // Sleep until the next read, waking early for control messages
+        loop {
+            match control.recv_timeout(interval) {
+                Ok(Control::SetInterval(new_interval)) => interval = new_interval,
+                Ok(Control::Refresh) => break,
+                Ok(Control::Stop) | Err(RecvTimeoutError::Disconnected) => return,
+                Err(RecvTimeoutError::Timeout) => break,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    /// A source whose reads never return, like a wedged driver
+    struct Stalled;
+
+    impl PmTableSource for Stalled {
+        fn read(&mut self) -> amd_smu_lib::Result<PmTable> {
+            loop {
+                thread::park();
+            }
+        }
+
+        fn smu_version(&self) -> String {
+            "stalled".to_string()
+        }
+    }
+
+    #[test]
+    fn test_stop_does_not_wait_for_a_stalled_read() {
+        let sampler = Sampler::spawn(Stalled, Duration::from_millis(10));
+        let start = Instant::now();
+        sampler.stop();
+        drop(sampler);
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+}
@@ -1,12 +1,65 @@
-use crate::app::App;
-use amd_smu_lib::PmTable;
+use crate::app::{format_uptime, is_idle_core, App, Peaks};
+use crate::history::History;
+use amd_smu_lib::{
+    Level, OutputSections, PmTable, TempUnit, Threshold, ThresholdConfig, ThrottleStatus, ACTIVE_C0_PERCENT,
+};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Gauge, Paragraph},
+    widgets::{
+        Block, Borders, Cell, Clear, Gauge, Paragraph, Row, Scrollbar, ScrollbarOrientation, ScrollbarState,
+        Sparkline, Table,
+    },
     Frame,
 };
+use std::collections::VecDeque;
+
+/// SoC temperature (°C); not configurable, the SoC runs cooler than Tctl
+const SOC_TEMP_THRESHOLD: Threshold = Threshold::new(50.0, 70.0);
+/// Share of the thermal limit already used (%)
+const HEADROOM_USED_THRESHOLD: Threshold = Threshold::new(80.0, 90.0);
+/// Mean C0 residency (%)
+const ACTIVITY_THRESHOLD: Threshold = Threshold::new(70.0, 90.0);
+/// Terminals narrower than this stack gauges vertically and drop per-core
+/// columns
+const NARROW_WIDTH: u16 = 80;
+/// Rows taken by one bordered gauge
+const GAUGE_HEIGHT: u16 = 3;
+/// Columns per heatmap cell: core index and temperature, plus a gap
+const HEATMAP_CELL_WIDTH: u16 = 9;
+/// Columns for the "CCD<n>" label in front of each heatmap row
+const HEATMAP_LABEL_WIDTH: u16 = 6;
+/// Columns of the help overlay, borders included
+const HELP_WIDTH: u16 = 72;
+
+/// What the dashboard's abbreviations mean, for the help overlay
+const HELP_METRICS: &[(&str, &str)] = &[
+    ("Tctl", "Control temperature that boost and fan curves follow"),
+    ("PPT", "Package Power Tracking: socket power limit (W)"),
+    ("TDC", "Thermal Design Current: sustained core current limit (A)"),
+    ("EDC", "Electrical Design Current: peak core current limit (A)"),
+    ("SoC", "I/O die and memory controller"),
+    ("FCLK/MCLK", "Infinity Fabric / memory clock"),
+    ("Eff", "Effective clock: average including time asleep"),
+    ("C0", "Share of time a core is awake and running"),
+];
+
+/// Every key binding, for the help overlay
+const HELP_KEYS: &[(&str, &str)] = &[
+    ("q / Esc", "Quit (Esc closes this help first)"),
+    ("?", "Show or hide this help"),
+    ("t / p / f", "Toggle temperatures / power / frequencies"),
+    ("g", "Toggle history graphs"),
+    ("Space", "Pause or resume updates"),
+    ("r", "Fetch one new reading (steps forward in a replay)"),
+    ("s", "Toggle smoothing"),
+    ("c / e", "Clear peaks / reset energy"),
+    ("i / h", "Hide idle cores / per-core heatmap"),
+    ("↑ ↓ j k PgUp PgDn", "Scroll the per-core table"),
+    ("+ / -", "Shorter / longer refresh interval"),
+    ("← → Home End", "Seek while replaying"),
+];
 
 pub fn draw(frame: &mut Frame, app: &App) {
     let chunks = Layout::default()
@@ -20,27 +73,62 @@ pub fn draw(frame: &mut Frame, app: &App) {
 
     draw_header(frame, app, chunks[0]);
     draw_main(frame, app, chunks[1]);
-    draw_footer(frame, chunks[2]);
+    draw_footer(frame, app, chunks[2]);
+    if app.show_help {
+        draw_help(frame, chunks[1]);
+    }
+
+    // Monochrome: drop every color but keep bold/reversed emphasis
+    if !app.color {
+        for cell in frame.buffer_mut().content.iter_mut() {
+            cell.set_fg(Color::Reset).set_bg(Color::Reset);
+        }
+    }
 }
 
 fn draw_header(frame: &mut Frame, app: &App, area: Rect) {
     let codename = app.pm_table.as_ref()
-        .map(|t| t.codename_str.as_str())
+        .map(|t| t.codename_str.as_ref())
         .unwrap_or("Unknown");
 
     let version = app.pm_table.as_ref()
         .map(|t| format!("{:#x}", t.version))
         .unwrap_or_else(|| "?".to_string());
 
+    let active = app.pm_table.as_ref()
+        .filter(|t| t.present_core_count() > 0)
+        .map_or(String::new(), |t| format!(" | Active cores: {}/{}", t.active_core_count(ACTIVE_C0_PERCENT), t.present_core_count()));
+
     let title = format!(
-        " AMD Ryzen ({}) | {} | PM Table v{} | Refresh: {}ms ",
+        " AMD Ryzen ({}) | {} | PM Table v{}{} | Refresh: {}ms | Uptime: {} | Samples: {} {}{}{}",
         codename,
         app.smu_version,
         version,
-        app.interval.as_millis()
+        active,
+        app.interval.as_millis(),
+        format_uptime(app.started.elapsed()),
+        app.samples,
+        if app.paused { "[PAUSED] " } else { "" },
+        app.replay_progress().map_or(String::new(), |(pos, len)| format!("[{}/{}] ", pos + 1, len)),
+        app.smoother.as_ref().map_or(String::new(), |s| format!("[SMOOTH {:.2}] ", s.alpha()))
     );
 
-    let header = Paragraph::new(title)
+    let mut spans = vec![Span::raw(title)];
+    if let Some(ThrottleStatus::Limited(limit)) = app.pm_table.as_ref().map(PmTable::throttle_status) {
+        spans.push(Span::styled(
+            format!(" {} LIMITED ", limit),
+            Style::default().fg(Color::Black).bg(Color::Red),
+        ));
+    }
+
+    if let Some(change) = &app.version_change {
+        spans.push(Span::styled(
+            format!(" PM TABLE VERSION {} ", change),
+            Style::default().fg(Color::Black).bg(Color::Yellow),
+        ));
+    }
+
+    let header = Paragraph::new(Line::from(spans))
         .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
         .block(Block::default().borders(Borders::ALL));
 
@@ -48,7 +136,10 @@ fn draw_header(frame: &mut Frame, app: &App, area: Rect) {
 }
 
 fn draw_main(frame: &mut Frame, app: &App, area: Rect) {
-    if let Some(ref error) = app.error {
+    // Only take over the screen if there's no good reading to keep showing
+    if let Some(ref error) = app.error
+        && app.pm_table.is_none()
+    {
         let error_msg = Paragraph::new(format!("Error: {}", error))
             .style(Style::default().fg(Color::Red))
             .block(Block::default().borders(Borders::ALL).title("Error"));
@@ -66,153 +157,506 @@ fn draw_main(frame: &mut Frame, app: &App, area: Rect) {
     let main_chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(5),   // Limits (PPT/TDC/EDC)
-            Constraint::Length(6),   // Temperatures
+            Constraint::Length(gauge_panel_height(area.width, 4, 5)),  // Limits (PPT/TDC/EDC)
+            Constraint::Length(gauge_panel_height(area.width, 3, 6)),  // Temperatures
+            Constraint::Length(cooling_height(app)),  // Fans
             Constraint::Min(4),      // Cores
+            Constraint::Length(if app.show_graphs { 6 } else { 0 }),  // History graphs
         ])
         .split(area);
 
-    if app.show_power {
-        draw_limits(frame, table, main_chunks[0]);
+    if app.sections.contains(OutputSections::POWER) {
+        draw_limits(frame, app, table, main_chunks[0]);
+    }
+    if app.sections.contains(OutputSections::TEMPS) {
+        draw_temps(frame, table, &app.peaks, app.temp_unit, &app.thresholds, main_chunks[1]);
+    }
+    if app.sections.contains(OutputSections::TEMPS) && !app.fans.is_empty() {
+        draw_cooling(frame, &app.fans, main_chunks[2]);
+    }
+    if app.sections.contains(OutputSections::FREQ) {
+        if app.heatmap {
+            draw_heatmap(frame, app, table, main_chunks[3]);
+        } else {
+            draw_cores(frame, app, table, main_chunks[3]);
+        }
+    }
+    if app.show_graphs {
+        draw_graphs(frame, app, main_chunks[4]);
+    }
+}
+
+/// Gauges sit side by side, or stack on narrow terminals
+fn gauge_direction(width: u16) -> Direction {
+    if width < NARROW_WIDTH { Direction::Vertical } else { Direction::Horizontal }
+}
+
+/// Height of a panel of `count` gauges: `wide_height` side by side, one
+/// gauge's worth each when stacked
+fn gauge_panel_height(width: u16, count: u16, wide_height: u16) -> u16 {
+    match gauge_direction(width) {
+        Direction::Horizontal => wide_height,
+        Direction::Vertical => count * GAUGE_HEIGHT,
     }
-    if app.show_temps {
-        draw_temps(frame, table, main_chunks[1]);
+}
+
+/// Per-core table columns for `width`; narrow terminals lose the
+/// effective clock and power columns
+fn core_columns<T>(mut columns: Vec<T>, width: u16) -> Vec<T> {
+    if width < NARROW_WIDTH {
+        columns.drain(3..5);
     }
-    if app.show_freq {
-        draw_cores(frame, table, main_chunks[2]);
+    columns
+}
+
+/// Rows for the fan panel: two fans per line, hidden without fans
+fn cooling_height(app: &App) -> u16 {
+    if app.sections.contains(OutputSections::TEMPS) && !app.fans.is_empty() {
+        (app.fans.len().div_ceil(2).min(3) + 2) as u16
+    } else {
+        0
     }
 }
 
-fn draw_limits(frame: &mut Frame, table: &PmTable, area: Rect) {
+fn draw_cooling(frame: &mut Frame, fans: &[(String, u32)], area: Rect) {
+    let lines: Vec<Line> = fans
+        .chunks(2)
+        .map(|pair| {
+            let spans: Vec<Span> = pair
+                .iter()
+                .map(|(label, rpm)| Span::raw(format!("{:<24} {:>5} RPM    ", label, rpm)))
+                .collect();
+            Line::from(spans)
+        })
+        .collect();
+    let panel = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("Cooling"));
+    frame.render_widget(panel, area);
+}
+
+fn draw_limits(frame: &mut Frame, app: &App, table: &PmTable, area: Rect) {
+    let (peaks, thresholds) = (&app.peaks, &app.thresholds);
+    let limit_color = |pct: u16, threshold: Threshold| {
+        if app.gradient { gradient_color(pct as f32) } else { temp_color(pct as f32, threshold) }
+    };
+
     let chunks = Layout::default()
-        .direction(Direction::Horizontal)
+        .direction(gauge_direction(area.width))
         .constraints([
-            Constraint::Percentage(33),
-            Constraint::Percentage(33),
-            Constraint::Percentage(34),
+            Constraint::Percentage(25),
+            Constraint::Percentage(25),
+            Constraint::Percentage(25),
+            Constraint::Percentage(25),
         ])
         .split(area);
 
     // PPT gauge
-    let ppt_pct = (table.ppt_value / table.ppt_limit * 100.0).min(100.0) as u16;
+    let ppt_pct = gauge_percent(table.ppt_value, table.ppt_limit);
     let ppt_gauge = Gauge::default()
-        .block(Block::default().borders(Borders::ALL).title("PPT (Power)"))
-        .gauge_style(Style::default().fg(temp_color(ppt_pct as f32, 70.0, 90.0)))
+        .block(Block::default().borders(Borders::ALL).title(ppt_title(app)))
+        .gauge_style(Style::default().fg(limit_color(ppt_pct, thresholds.ppt)))
         .percent(ppt_pct)
-        .label(format!("{:.1}W / {:.1}W", table.ppt_value, table.ppt_limit));
+        .label(format!("{} / {} (peak {})", reading(table.ppt_value, 1, "W"), reading(table.ppt_limit, 1, "W"), reading(peaks.ppt, 1, "W")));
     frame.render_widget(ppt_gauge, chunks[0]);
 
     // TDC gauge
-    let tdc_pct = (table.tdc_value / table.tdc_limit * 100.0).min(100.0) as u16;
+    let tdc_pct = gauge_percent(table.tdc_value, table.tdc_limit);
     let tdc_gauge = Gauge::default()
         .block(Block::default().borders(Borders::ALL).title("TDC (Current)"))
-        .gauge_style(Style::default().fg(temp_color(tdc_pct as f32, 70.0, 90.0)))
+        .gauge_style(Style::default().fg(limit_color(tdc_pct, thresholds.tdc)))
         .percent(tdc_pct)
-        .label(format!("{:.1}A / {:.1}A", table.tdc_value, table.tdc_limit));
+        .label(format!("{} / {} (peak {})", reading(table.tdc_value, 1, "A"), reading(table.tdc_limit, 1, "A"), reading(peaks.tdc, 1, "A")));
     frame.render_widget(tdc_gauge, chunks[1]);
 
     // EDC gauge
-    let edc_pct = (table.edc_value / table.edc_limit * 100.0).min(100.0) as u16;
+    let edc_pct = gauge_percent(table.edc_value, table.edc_limit);
     let edc_gauge = Gauge::default()
         .block(Block::default().borders(Borders::ALL).title("EDC (Peak)"))
-        .gauge_style(Style::default().fg(temp_color(edc_pct as f32, 70.0, 90.0)))
+        .gauge_style(Style::default().fg(limit_color(edc_pct, thresholds.edc)))
         .percent(edc_pct)
-        .label(format!("{:.1}A / {:.1}A", table.edc_value, table.edc_limit));
+        .label(format!("{} / {} (peak {})", reading(table.edc_value, 1, "A"), reading(table.edc_limit, 1, "A"), reading(peaks.edc, 1, "A")));
     frame.render_widget(edc_gauge, chunks[2]);
+
+    // Package activity (mean C0 residency)
+    let activity = table.package_c0();
+    let activity_gauge = Gauge::default().block(Block::default().borders(Borders::ALL).title("Activity (C0)"));
+    let activity_gauge = if !activity.is_finite() {
+        activity_gauge.percent(0).label("—")
+    } else {
+        activity_gauge
+            .gauge_style(Style::default().fg(temp_color(activity, ACTIVITY_THRESHOLD)))
+            .percent(activity.clamp(0.0, 100.0) as u16)
+            .label(format!("{:.1}%", activity))
+    };
+    frame.render_widget(activity_gauge, chunks[3]);
 }
 
-fn draw_temps(frame: &mut Frame, table: &PmTable, area: Rect) {
-    let chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-        .split(area);
+// Colors and gauge fill are computed in Celsius; only labels are converted
+fn draw_temps(
+    frame: &mut Frame,
+    table: &PmTable,
+    peaks: &Peaks,
+    unit: TempUnit,
+    thresholds: &ThresholdConfig,
+    area: Rect,
+) {
+    let direction = gauge_direction(area.width);
+    let constraints = match direction {
+        Direction::Horizontal => [Constraint::Percentage(40), Constraint::Percentage(30), Constraint::Percentage(30)],
+        Direction::Vertical => [Constraint::Length(GAUGE_HEIGHT); 3],
+    };
+    let chunks = Layout::default().direction(direction).constraints(constraints).split(area);
 
     // Tctl gauge
-    let tctl_pct = (table.tctl / table.thm_limit * 100.0).min(100.0) as u16;
+    let tctl_pct = gauge_percent(table.tctl, table.thm_limit);
     let tctl_gauge = Gauge::default()
         .block(Block::default().borders(Borders::ALL).title("Tctl (Junction)"))
-        .gauge_style(Style::default().fg(temp_color(table.tctl, 70.0, 85.0)))
+        .gauge_style(Style::default().fg(temp_color(table.tctl, thresholds.temperature)))
         .percent(tctl_pct)
-        .label(format!("{:.1}°C / {:.1}°C", table.tctl, table.thm_limit));
+        .label(format!("{} / {} (peak {})",
+            reading(unit.convert(table.tctl), 1, unit.symbol()),
+            reading(unit.convert(table.thm_limit), 1, unit.symbol()),
+            reading(unit.convert(peaks.tctl), 1, unit.symbol())));
     frame.render_widget(tctl_gauge, chunks[0]);
 
     // SoC temp
-    let soc_pct = (table.soc_temp / 80.0 * 100.0).min(100.0) as u16;
+    let soc_pct = gauge_percent(table.soc_temp, 80.0);
     let soc_gauge = Gauge::default()
         .block(Block::default().borders(Borders::ALL).title("SoC Temperature"))
-        .gauge_style(Style::default().fg(temp_color(table.soc_temp, 50.0, 70.0)))
+        .gauge_style(Style::default().fg(temp_color(table.soc_temp, SOC_TEMP_THRESHOLD)))
         .percent(soc_pct)
-        .label(format!("{:.1}°C", table.soc_temp));
+        .label(reading(unit.convert(table.soc_temp), 1, unit.symbol()));
     frame.render_widget(soc_gauge, chunks[1]);
+
+    // Headroom, filled by the share of the limit still left
+    let headroom = table.thermal_headroom();
+    let headroom_gauge = Gauge::default().block(Block::default().borders(Borders::ALL).title("Thermal Headroom"));
+    let headroom_gauge = if !headroom.is_finite() {
+        headroom_gauge.percent(0).label("—")
+    } else {
+        let left_pct = gauge_percent(headroom, table.thm_limit);
+        headroom_gauge
+            .gauge_style(Style::default().fg(temp_color(100.0 - left_pct as f32, HEADROOM_USED_THRESHOLD)))
+            .percent(left_pct)
+            .label(format!("{:.1}{}", unit.convert_delta(headroom), unit.symbol()))
+    };
+    frame.render_widget(headroom_gauge, chunks[2]);
 }
 
-fn draw_cores(frame: &mut Frame, table: &PmTable, area: Rect) {
-    let mut lines = Vec::new();
+/// One row per populated core, scrolled so the table fits on high
+/// core-count parts
+fn draw_cores(frame: &mut Frame, app: &App, table: &PmTable, area: Rect) {
+    let (unit, thresholds, ccd_relative) = (app.temp_unit, &app.thresholds, app.ccd_relative);
 
-    // Core temps line
-    let mut temp_spans = vec![Span::raw("Temps:  ")];
-    for (i, temp) in table.core_temps.iter().enumerate() {
-        if *temp > 0.0 {
-            let color = temp_color(*temp, 70.0, 85.0);
-            temp_spans.push(Span::styled(
-                format!("C{}: {:5.1}°C  ", i, temp),
-                Style::default().fg(color),
-            ));
-        }
-    }
-    lines.push(Line::from(temp_spans));
+    let title = if app.hide_idle_cores { "Per-Core Metrics (idle hidden)" } else { "Per-Core Metrics" };
+    let block = Block::default().borders(Borders::ALL).title(title);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
 
-    // Core freqs line
-    let mut freq_spans = vec![Span::raw("Freqs:  ")];
-    for (i, freq) in table.core_freqs.iter().enumerate() {
-        if *freq > 0.0 {
-            freq_spans.push(Span::styled(
-                format!("C{}: {:4.0}MHz  ", i, freq),
-                Style::default().fg(Color::White),
-            ));
-        }
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0)])
+        .split(inner);
+
+    let hottest = table.temperatures().hottest_core();
+    if let Some((core, temp)) = hottest {
+        let summary = Paragraph::new(Span::styled(
+            format!(
+                "Hottest: {} @ {:.1}{}",
+                if ccd_relative { table.ccd_core_label(core) } else { format!("Core {}", core) },
+                unit.convert(temp),
+                unit.symbol()
+            ),
+            Style::default().fg(temp_color(temp, thresholds.temperature)).add_modifier(Modifier::BOLD),
+        ));
+        frame.render_widget(summary, chunks[0]);
     }
-    lines.push(Line::from(freq_spans));
 
-    // Core power line
-    let mut power_spans = vec![Span::raw("Power:  ")];
-    for (i, power) in table.core_power.iter().enumerate() {
-        if *power > 0.0 {
-            power_spans.push(Span::styled(
-                format!("C{}: {:5.2}W  ", i, power),
-                Style::default().fg(Color::Yellow),
-            ));
+    let cores: Vec<usize> = (0..table.core_temps.len())
+        .filter(|&i| table.core_temps[i] > 0.0)
+        .filter(|&i| !(app.hide_idle_cores && is_idle_core(table, i)))
+        .collect();
+    // One line goes to the column headers
+    let visible = (chunks[1].height as usize).saturating_sub(1);
+    let offset = app.core_scroll_offset(cores.len(), visible);
+
+    let value = |values: &[f32], i: usize, fmt: fn(f32) -> String| {
+        values.get(i).filter(|v| v.is_finite() && **v > 0.0).map_or_else(|| "-".to_string(), |v| fmt(*v))
+    };
+    let rows = cores.iter().skip(offset).take(visible).map(|&i| {
+        let temp = table.core_temps[i];
+        let mut temp_style = Style::default().fg(temp_color(temp, thresholds.temperature));
+        if hottest.is_some_and(|(core, _)| core == i) {
+            temp_style = temp_style.add_modifier(Modifier::BOLD | Modifier::REVERSED);
         }
+        Row::new(core_columns(vec![
+            Cell::from(if ccd_relative { table.ccd_core_label(i) } else { format!("C{}", i) }),
+            Cell::from(format!("{:5.1}{}", unit.convert(temp), unit.symbol())).style(temp_style),
+            Cell::from(value(&table.core_freqs, i, |f| format!("{:4.0}MHz", f))).style(Style::default().fg(Color::White)),
+            Cell::from(value(&table.core_freqs_eff, i, |f| format!("{:4.0}MHz", f))).style(Style::default().fg(Color::White)),
+            Cell::from(value(&table.core_power, i, |p| format!("{:5.2}W", p))).style(Style::default().fg(Color::Yellow)),
+            Cell::from(value(&table.core_c0, i, |c| format!("{:5.1}%", c))).style(Style::default().fg(Color::Cyan)),
+        ], area.width))
+        .style(if is_idle_core(table, i) { Style::default().add_modifier(Modifier::DIM) } else { Style::default() })
+    });
+
+    let widths = core_columns(vec![
+        Constraint::Length(if ccd_relative { 11 } else { 5 }),
+        Constraint::Length(9),
+        Constraint::Length(9),
+        Constraint::Length(9),
+        Constraint::Length(8),
+        Constraint::Length(7),
+    ], area.width);
+    let header = Row::new(core_columns(vec!["Core", "Temp", "Freq", "Eff", "Power", "C0"], area.width))
+        .style(Style::default().add_modifier(Modifier::BOLD));
+    frame.render_widget(Table::new(rows, widths).header(header), chunks[1]);
+
+    if cores.len() > visible {
+        let mut state = ScrollbarState::new(cores.len() - visible).position(offset);
+        frame.render_stateful_widget(
+            Scrollbar::new(ScrollbarOrientation::VerticalRight),
+            chunks[1],
+            &mut state,
+        );
     }
-    lines.push(Line::from(power_spans));
+}
 
-    // C0 residency line
-    let mut c0_spans = vec![Span::raw("C0:     ")];
-    for (i, c0) in table.core_c0.iter().enumerate() {
-        c0_spans.push(Span::styled(
-            format!("C{}: {:5.1}%  ", i, c0),
-            Style::default().fg(Color::Cyan),
-        ));
+/// Heatmap rows: each CCD's cores, split into lines of at most `per_line`
+fn heatmap_rows(table: &PmTable, per_line: usize) -> Vec<(usize, Vec<usize>)> {
+    let per_line = per_line.max(1);
+    (0..table.ccd_count())
+        .flat_map(|ccd| {
+            let cores: Vec<usize> = table.cores_in_ccd(ccd).collect();
+            cores.chunks(per_line).map(|line| (ccd, line.to_vec())).collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Per-core temperatures as colored cells, one row per CCD (wrapped on
+/// narrow terminals), scrolled like the per-core table
+fn draw_heatmap(frame: &mut Frame, app: &App, table: &PmTable, area: Rect) {
+    let unit = app.temp_unit;
+    let block = Block::default().borders(Borders::ALL).title(format!("Core Temperatures ({}, by CCD)", unit.symbol()));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let per_line = (inner.width.saturating_sub(HEATMAP_LABEL_WIDTH) / HEATMAP_CELL_WIDTH) as usize;
+    let rows = heatmap_rows(table, per_line);
+    let visible = inner.height as usize;
+    let offset = app.core_scroll_offset(rows.len(), visible);
+
+    let lines: Vec<Line> = rows
+        .iter()
+        .enumerate()
+        .skip(offset)
+        .take(visible)
+        .map(|(row, (ccd, cores))| {
+            // Only a CCD's first line carries its label
+            let label = if row == 0 || rows[row - 1].0 != *ccd { format!("CCD{}", ccd) } else { String::new() };
+            let mut spans = vec![Span::styled(
+                format!("{:<width$}", label, width = HEATMAP_LABEL_WIDTH as usize),
+                Style::default().add_modifier(Modifier::BOLD),
+            )];
+            for &core in cores {
+                let temp = table.core_temps[core];
+                let cell = if temp.is_finite() && temp > 0.0 {
+                    Span::styled(
+                        format!(" {:>2} {:>3.0} ", core, unit.convert(temp)),
+                        Style::default().fg(Color::Black).bg(temp_color(temp, app.thresholds.temperature)),
+                    )
+                } else {
+                    Span::styled(format!(" {:>2}   — ", core), Style::default().add_modifier(Modifier::DIM))
+                };
+                spans.push(cell);
+                spans.push(Span::raw(" "));
+            }
+            Line::from(spans)
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+fn draw_graphs(frame: &mut Frame, app: &App, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(33),
+            Constraint::Percentage(33),
+            Constraint::Percentage(34),
+        ])
+        .split(area);
+
+    let History { tctl, package_power, max_core_freq } = &app.history;
+    let unit = app.temp_unit;
+
+    // Graphs are plotted in Celsius; the title shows the latest reading
+    let graphs = [
+        (format!("Tctl {:.0}{}", unit.convert(last(tctl) as f32), unit.symbol()), tctl, Color::Red),
+        (format!("Package {}W", last(package_power)), package_power, Color::Yellow),
+        (format!("Max Freq {}MHz", last(max_core_freq)), max_core_freq, Color::Cyan),
+    ];
+    for ((title, data, color), chunk) in graphs.into_iter().zip(chunks.iter()) {
+        let sparkline = Sparkline::default()
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .data(data)
+            .style(Style::default().fg(color));
+        frame.render_widget(sparkline, *chunk);
     }
-    lines.push(Line::from(c0_spans));
+}
 
-    let cores = Paragraph::new(lines)
-        .block(Block::default().borders(Borders::ALL).title("Per-Core Metrics"));
-    frame.render_widget(cores, area);
+fn last(data: &VecDeque<u64>) -> u64 {
+    data.back().copied().unwrap_or(0)
 }
 
-fn draw_footer(frame: &mut Frame, area: Rect) {
-    let footer = Paragraph::new(" [q] Quit  [t] Temps  [p] Power  [f] Freq  [+/-] Interval ")
-        .style(Style::default().fg(Color::DarkGray));
+fn draw_footer(frame: &mut Frame, app: &App, area: Rect) {
+    let footer = match (&app.error, &app.pm_table) {
+        (Some(error), Some(_)) => Paragraph::new(format!(" Read failed, showing last reading: {} ", error))
+            .style(Style::default().fg(Color::Red)),
+        _ if app.replay_progress().is_some() => Paragraph::new(" [q] Quit  [?] Help  [Space] Pause  [←/→] Seek  [Home/End] Start/End  [s] Smooth  [c] Clear peaks  [e] Reset energy  [i] Idle cores  [h] Heatmap  [↑/↓] Cores  [+/-] Interval ")
+            .style(Style::default().fg(Color::DarkGray)),
+        _ => Paragraph::new(" [q] Quit  [?] Help  [t] Temps  [p] Power  [f] Freq  [g] Graphs  [Space] Pause  [r] Refresh  [s] Smooth  [c] Clear peaks  [e] Reset energy  [i] Idle cores  [h] Heatmap  [↑/↓] Cores  [+/-] Interval ")
+            .style(Style::default().fg(Color::DarkGray)),
+    };
     frame.render_widget(footer, area);
 }
 
-fn temp_color(value: f32, warn: f32, crit: f32) -> Color {
-    if value >= crit {
-        Color::Red
-    } else if value >= warn {
-        Color::Yellow
-    } else {
-        Color::Green
+/// Centered popup listing `HELP_METRICS` and `HELP_KEYS`, drawn over
+/// `area` and clipped to it
+fn draw_help(frame: &mut Frame, area: Rect) {
+    let label = Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD);
+    let heading = Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+    let rows = |entries: &[(&'static str, &'static str)]| {
+        entries
+            .iter()
+            .map(|(name, text)| Line::from(vec![Span::styled(format!(" {:<18}", name), label), Span::raw(*text)]))
+            .collect::<Vec<_>>()
+    };
+    let mut lines = vec![Line::styled(" Metrics", heading)];
+    lines.extend(rows(HELP_METRICS));
+    lines.push(Line::raw(""));
+    lines.push(Line::styled(" Keys", heading));
+    lines.extend(rows(HELP_KEYS));
+
+    let popup = centered_rect(HELP_WIDTH, lines.len() as u16 + 2, area);
+    let help = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(" Help (? or Esc to close) "));
+    // Blank what's underneath so the dashboard doesn't show through
+    frame.render_widget(Clear, popup);
+    frame.render_widget(help, popup);
+}
+
+/// `width` x `height` in the middle of `area`, shrunk to fit
+fn centered_rect(width: u16, height: u16, area: Rect) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+/// PPT gauge title, with the energy used and average power since start
+/// or the last reset once there are two readings
+fn ppt_title(app: &App) -> String {
+    match app.energy.average_watts() {
+        Some(average) => format!("PPT (Power) | avg {:.1}W, {:.2}Wh", average, app.energy.watt_hours()),
+        None => "PPT (Power)".to_string(),
+    }
+}
+
+/// Share of `limit` that `value` uses, as a gauge percentage clamped to
+/// 0..=100; 0 when it isn't a number (NaN/inf readings, zero limit)
+fn gauge_percent(value: f32, limit: f32) -> u16 {
+    let percent = value / limit * 100.0;
+    if percent.is_finite() { percent.clamp(0.0, 100.0) as u16 } else { 0 }
+}
+
+/// `value` to `precision` decimals followed by `unit`, or "—" when it
+/// isn't finite
+fn reading(value: f32, precision: usize, unit: &str) -> String {
+    if value.is_finite() { format!("{:.*}{}", precision, value, unit) } else { "—".to_string() }
+}
+
+/// Green through yellow (50%) to red (100%) for a utilization percentage
+fn gradient_color(percent: f32) -> Color {
+    let p = percent.clamp(0.0, 100.0);
+    let (red, green) = if p <= 50.0 { (p / 50.0, 1.0) } else { (1.0, (100.0 - p) / 50.0) };
+    Color::Rgb((red * 255.0).round() as u8, (green * 255.0).round() as u8, 0)
+}
+
+fn temp_color(value: f32, threshold: Threshold) -> Color {
+    match threshold.level(value) {
+        Level::Critical => Color::Red,
+        Level::Warning => Color::Yellow,
+        Level::Normal => Color::Green,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use amd_smu_lib::Codename;
+
+    #[test]
+    fn test_gradient_color() {
+        assert_eq!(gradient_color(0.0), Color::Rgb(0, 255, 0));
+        assert_eq!(gradient_color(50.0), Color::Rgb(255, 255, 0));
+        assert_eq!(gradient_color(100.0), Color::Rgb(255, 0, 0));
+        assert_eq!(gradient_color(25.0), Color::Rgb(128, 255, 0));
+        assert_eq!(gradient_color(150.0), gradient_color(100.0));
+    }
+
+    #[test]
+    fn test_non_finite_gauges() {
+        assert_eq!(gauge_percent(f32::NAN, 142.0), 0);
+        assert_eq!(gauge_percent(f32::INFINITY, 142.0), 0);
+        assert_eq!(gauge_percent(50.0, 0.0), 0);
+        assert_eq!(gauge_percent(-5.0, 100.0), 0);
+        assert_eq!(gauge_percent(200.0, 100.0), 100);
+        assert_eq!(gauge_percent(71.0, 142.0), 50);
+        assert_eq!(reading(f32::NAN, 1, "W"), "—");
+        assert_eq!(reading(89.54, 1, "W"), "89.5W");
+    }
+
+    #[test]
+    fn test_narrow_layout() {
+        assert_eq!(gauge_direction(120), Direction::Horizontal);
+        assert_eq!(gauge_panel_height(80, 3, 6), 6);
+        assert_eq!(gauge_direction(79), Direction::Vertical);
+        assert_eq!(gauge_panel_height(79, 4, 5), 12);
+        assert_eq!(gauge_panel_height(40, 3, 6), 9);
+
+        let columns = vec!["Core", "Temp", "Freq", "Eff", "Power", "C0"];
+        assert_eq!(core_columns(columns.clone(), 100), columns);
+        assert_eq!(core_columns(columns, 60), vec!["Core", "Temp", "Freq", "C0"]);
+    }
+
+    #[test]
+    fn test_help_overlay() {
+        let area = Rect::new(0, 3, 100, 40);
+        assert_eq!(centered_rect(HELP_WIDTH, 24, area), Rect::new(14, 11, 72, 24));
+        // Clipped to a small terminal rather than spilling out
+        assert_eq!(centered_rect(HELP_WIDTH, 24, Rect::new(0, 3, 50, 10)), Rect::new(0, 3, 50, 10));
+
+        let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(100, 40)).unwrap();
+        terminal.draw(|frame| draw_help(frame, area)).unwrap();
+        let screen: String = terminal.backend().buffer().content.iter().map(|cell| cell.symbol()).collect();
+        assert!(screen.contains("Help (? or Esc to close)"));
+        assert!(screen.contains("Package Power Tracking"));
+        assert!(screen.contains("Seek while replaying"));
+    }
+
+    #[test]
+    fn test_heatmap_rows_by_ccd() {
+        let table = PmTable { codename: Codename::Vermeer, core_temps: vec![60.0; 12], ..Default::default() };
+        assert_eq!(heatmap_rows(&table, 8), vec![(0, (0..8).collect()), (1, (8..12).collect())]);
+
+        // Narrow: each CCD wraps onto several lines
+        let rows = heatmap_rows(&table, 3);
+        assert_eq!(rows.iter().map(|(ccd, _)| *ccd).collect::<Vec<_>>(), vec![0, 0, 0, 1, 1]);
+        assert_eq!(rows[2].1, vec![6, 7]);
+        assert_eq!(heatmap_rows(&table, 0).len(), 12);
     }
 }